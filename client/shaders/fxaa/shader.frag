@@ -0,0 +1,103 @@
+#version 460
+
+in vec2 frag_uv;
+
+out vec4 frag_col;
+
+uniform sampler2D scene;
+uniform vec2 inv_resolution;
+
+// quality parameters
+uniform float edge_threshold;
+uniform float edge_threshold_min;
+uniform float subpix;
+uniform int search_steps;
+
+
+// perceived brightness
+float luma(vec3 rgb) {
+    return dot(rgb, vec3(0.299, 0.587, 0.114));
+}
+
+float sample_luma(vec2 uv) {
+    return luma(texture(scene, uv).rgb);
+}
+
+
+void main() {
+    vec3 center = texture(scene, frag_uv).rgb;
+
+    // luma of the neighbourhood
+    float l_m = luma(center);
+    float l_n = sample_luma(frag_uv + vec2(0.0, inv_resolution.y));
+    float l_s = sample_luma(frag_uv - vec2(0.0, inv_resolution.y));
+    float l_e = sample_luma(frag_uv + vec2(inv_resolution.x, 0.0));
+    float l_w = sample_luma(frag_uv - vec2(inv_resolution.x, 0.0));
+
+    float l_min = min(l_m, min(min(l_n, l_s), min(l_e, l_w)));
+    float l_max = max(l_m, max(max(l_n, l_s), max(l_e, l_w)));
+    float range = l_max - l_min;
+
+    // not an edge
+    if (range < max(edge_threshold_min, l_max * edge_threshold)) {
+        frag_col = vec4(center, 1.0);
+        return;
+    }
+
+    // edge orientation
+    bool horizontal = abs(l_n + l_s - 2.0 * l_m) >= abs(l_e + l_w - 2.0 * l_m);
+
+    float l_pos = horizontal ? l_n : l_e;
+    float l_neg = horizontal ? l_s : l_w;
+
+    vec2 step_dir = horizontal ? vec2(0.0, inv_resolution.y) : vec2(inv_resolution.x, 0.0);
+    vec2 edge_dir = horizontal ? vec2(inv_resolution.x, 0.0) : vec2(0.0, inv_resolution.y);
+
+    // the side of the edge with the larger gradient
+    bool positive = abs(l_pos - l_m) >= abs(l_neg - l_m);
+    float gradient = 0.25 * max(abs(l_pos - l_m), abs(l_neg - l_m));
+    float l_edge = 0.5 * (l_m + (positive ? l_pos : l_neg));
+    vec2 uv_edge = frag_uv + (positive ? 0.5 : -0.5) * step_dir;
+
+    // search along the edge for its ends
+    vec2 uv_p = uv_edge + edge_dir;
+    vec2 uv_n = uv_edge - edge_dir;
+    float d_p = sample_luma(uv_p) - l_edge;
+    float d_n = sample_luma(uv_n) - l_edge;
+
+    for (int i = 1; i < search_steps; i++) {
+        bool done_p = abs(d_p) >= gradient;
+        bool done_n = abs(d_n) >= gradient;
+
+        if (done_p && done_n) {
+            break;
+        }
+        if (!done_p) {
+            uv_p += edge_dir;
+            d_p = sample_luma(uv_p) - l_edge;
+        }
+        if (!done_n) {
+            uv_n -= edge_dir;
+            d_n = sample_luma(uv_n) - l_edge;
+        }
+    }
+
+    float dist_p = horizontal ? uv_p.x - frag_uv.x : uv_p.y - frag_uv.y;
+    float dist_n = horizontal ? frag_uv.x - uv_n.x : frag_uv.y - uv_n.y;
+    float dist = min(dist_p, dist_n);
+
+    // blend towards the nearest end of the edge
+    bool correct = ((dist_p < dist_n ? d_p : d_n) < 0.0) != (l_m - l_edge < 0.0);
+    float edge_blend = correct ? 0.5 - dist / (dist_p + dist_n) : 0.0;
+
+    // sub-pixel aliasing
+    float l_avg = (l_n + l_s + l_e + l_w) / 4.0;
+    float sub = clamp(abs(l_avg - l_m) / range, 0.0, 1.0);
+    float sub_blend = smoothstep(0.0, 1.0, sub);
+    sub_blend = sub_blend * sub_blend * subpix;
+
+    float blend = max(edge_blend, sub_blend);
+    vec2 uv = frag_uv + (positive ? blend : -blend) * step_dir;
+
+    frag_col = vec4(texture(scene, uv).rgb, 1.0);
+}
@@ -0,0 +1,12 @@
+#version 460
+
+out vec2 frag_uv;
+
+
+void main() {
+    // a single triangle covering the screen (no vertex buffer required)
+    vec2 uv = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+
+    frag_uv = uv;
+    gl_Position = vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+}
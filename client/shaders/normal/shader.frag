@@ -2,15 +2,65 @@
 
 in vec3 frag_pos;
 in vec3 frag_norm;
+#ifdef LOG_DEPTH
+in float frag_log_z;
+#endif
+in vec4 frag_light_pos;
 
-out vec4 frag_col;
+layout(location = 0) out vec4 frag_col;
+layout(location = 1) out vec4 frag_pos_out;
+layout(location = 2) out vec4 frag_norm_out;
 
 uniform vec4 obj_col;
+uniform vec3 tint;
 uniform vec3 view_pos;
 
 uniform vec3 light_pos;
 uniform vec3 light_col;
 
+uniform float shininess;
+uniform vec3 spec_col;
+uniform vec3 emissive;
+
+#ifdef LOG_DEPTH
+uniform bool use_log_depth;
+uniform float log_depth_coef;
+#endif
+
+uniform bool shader_gamma;
+
+uniform bool use_shadows;
+uniform bool receive_shadow;
+uniform sampler2D shadow_map;
+uniform float shadow_bias;
+
+
+// linear -> sRGB (when the framebuffer doesn't convert)
+vec4 gamma_correct(vec4 col) {
+    if (shader_gamma) {
+        return vec4(pow(col.rgb, vec3(1.0 / 2.2)), col.a);
+    }
+    return col;
+}
+
+// color -> color multiplied by alpha (blending is `ONE, ONE_MINUS_SRC_ALPHA`)
+vec4 premultiply(vec4 col) {
+    return vec4(col.rgb * col.a, col.a);
+}
+
+// logarithmic depth (when enabled)
+//
+// `gl_FragDepth` is only written by the `LOG_DEPTH` variant, since writing it at all
+// disables early depth testing.
+void write_depth() {
+#ifdef LOG_DEPTH
+    if (use_log_depth) {
+        gl_FragDepth = log2(frag_log_z) * log_depth_coef;
+    } else {
+        gl_FragDepth = gl_FragCoord.z;
+    }
+#endif
+}
 
 // ambient shading property
 vec3 get_ambient(float strength, vec3 light_col) {
@@ -25,11 +75,11 @@ vec3 get_diffuse(vec3 frag_norm, vec3 light_dir, vec3 light_col) {
     return diffuse;
 }
 
-// specular shading property
-vec3 get_specular(float strength, vec3 view_pos, vec3 frag_pos, vec3 frag_norm, vec3 light_dir, vec3 light_col) {
+// specular shading property (Blinn-Phong)
+vec3 get_specular(vec3 strength, vec3 view_pos, vec3 frag_pos, vec3 frag_norm, vec3 light_dir, vec3 light_col) {
     vec3 view_dir = normalize(view_pos - frag_pos);
-    vec3 reflect_dir = reflect(-light_dir, frag_norm);  
-    float spec = pow(max(dot(view_dir, reflect_dir), 0.0), 16);
+    vec3 half_dir = normalize(light_dir + view_dir);
+    float spec = pow(max(dot(frag_norm, half_dir), 0.0), shininess);
     vec3 specular = strength * spec * light_col;
     return specular;
 }
@@ -50,6 +100,24 @@ float get_attenuation(vec3 frag_to_light) {
     return clamp(att, 0.0, 1.0);
 }
 
+// whether the fragment is hidden from the light (1.0) or not (0.0)
+float get_shadow() {
+    if (!use_shadows || !receive_shadow) {
+        return 0.0;
+    }
+
+    // light clip space -> shadow map coordinates and depth
+    vec3 p = frag_light_pos.xyz / frag_light_pos.w * 0.5 + 0.5;
+
+    // beyond the far plane of the light
+    if (p.z > 1.0) {
+        return 0.0;
+    }
+
+    float closest = texture(shadow_map, p.xy).r;
+    return p.z - shadow_bias > closest ? 1.0 : 0.0;
+}
+
 
 void main() {
     // difference between light and fragment vectors
@@ -65,14 +133,22 @@ void main() {
     vec3 diffuse = get_diffuse(frag_norm, light_dir, light_col);
 
     // specular
-    vec3 specular = get_specular(0.5, view_pos, frag_pos, frag_norm, light_dir, light_col);
+    vec3 specular = get_specular(spec_col, view_pos, frag_pos, frag_norm, light_dir, light_col);
 
     // attenuation
     float att = get_attenuation(frag_to_light);
 
+    // shadowed fragments only keep the ambient light
+    float shadow = get_shadow();
+
     // putting everything together
-    vec3 rgb = ((ambient + diffuse + specular) * obj_col.rgb) * att;
+    vec3 rgb = ((ambient + (1.0 - shadow) * (diffuse + specular)) * obj_col.rgb * tint) * att + emissive;
     float alpha = obj_col.a;
 
-    frag_col = vec4(rgb, alpha);
+    frag_col = premultiply(gamma_correct(vec4(rgb, alpha)));
+    write_depth();
+
+    // G-buffer outputs (discarded without the matching draw buffers)
+    frag_pos_out = vec4(frag_pos, 1.0);
+    frag_norm_out = vec4(frag_norm, 0.0);
 }
\ No newline at end of file
@@ -5,20 +5,41 @@ in vec3 norm;
 
 out vec3 frag_pos;
 out vec3 frag_norm;
+#ifdef LOG_DEPTH
+out float frag_log_z;
+#endif
+out vec4 frag_light_pos;
 
 uniform mat4 model;
+uniform mat3 normal_matrix;
 uniform mat4 view;
 uniform mat4 proj;
 
+uniform vec4 clip_plane;
+
+uniform mat4 light_space;
+uniform float shadow_normal_bias;
+
 
 void main() {
     // frag position to world space
     vec4 world_pos = model * vec4(pos, 1.0);
     frag_pos = vec3(world_pos);
 
+    // cross-section (only when `GL_CLIP_DISTANCE0` is enabled)
+    gl_ClipDistance[0] = dot(clip_plane, world_pos);
+
     // normal to world space
-    frag_norm = normalize(mat3(transpose(inverse(model))) * norm);
+    frag_norm = normalize(normal_matrix * norm);
+
+    // shadow map position, offset along the normal (against acne)
+    frag_light_pos = light_space * vec4(frag_pos + frag_norm * shadow_normal_bias, 1.0);
 
     // frag position to clip space
     gl_Position = proj * view * world_pos;
+
+#ifdef LOG_DEPTH
+    // view-space depth for logarithmic depth
+    frag_log_z = 1.0 + gl_Position.w;
+#endif
 }
\ No newline at end of file
@@ -1,10 +1,51 @@
 #version 460
 
+#ifdef LOG_DEPTH
+in float frag_log_z;
+#endif
+
 out vec4 frag_col;
 
 uniform vec4 obj_col;
+uniform vec3 tint;
+
+#ifdef LOG_DEPTH
+uniform bool use_log_depth;
+uniform float log_depth_coef;
+#endif
+
+uniform bool shader_gamma;
+
+
+// linear -> sRGB (when the framebuffer doesn't convert)
+vec4 gamma_correct(vec4 col) {
+    if (shader_gamma) {
+        return vec4(pow(col.rgb, vec3(1.0 / 2.2)), col.a);
+    }
+    return col;
+}
+
+// color -> color multiplied by alpha (blending is `ONE, ONE_MINUS_SRC_ALPHA`)
+vec4 premultiply(vec4 col) {
+    return vec4(col.rgb * col.a, col.a);
+}
+
+// logarithmic depth (when enabled)
+//
+// `gl_FragDepth` is only written by the `LOG_DEPTH` variant, since writing it at all
+// disables early depth testing.
+void write_depth() {
+#ifdef LOG_DEPTH
+    if (use_log_depth) {
+        gl_FragDepth = log2(frag_log_z) * log_depth_coef;
+    } else {
+        gl_FragDepth = gl_FragCoord.z;
+    }
+#endif
+}
 
 
 void main() {
-    frag_col = obj_col;
+    frag_col = premultiply(gamma_correct(vec4(obj_col.rgb * tint, obj_col.a)));
+    write_depth();
 }
\ No newline at end of file
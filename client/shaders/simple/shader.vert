@@ -3,17 +3,30 @@
 in vec3 pos;
 
 out vec3 frag_pos;
+#ifdef LOG_DEPTH
+out float frag_log_z;
+#endif
 
 uniform mat4 model;
 uniform mat4 view;
 uniform mat4 proj;
 
+uniform vec4 clip_plane;
+
 
 void main() {
     // frag position to world space
     vec4 world_pos = model * vec4(pos, 1.0);
     frag_pos = vec3(world_pos);
 
+    // cross-section (only when `GL_CLIP_DISTANCE0` is enabled)
+    gl_ClipDistance[0] = dot(clip_plane, world_pos);
+
     // frag position to clip space
     gl_Position = proj * view * world_pos;
+
+#ifdef LOG_DEPTH
+    // view-space depth for logarithmic depth
+    frag_log_z = 1.0 + gl_Position.w;
+#endif
 }
@@ -0,0 +1,115 @@
+use crate::*;
+use glow::{
+    Context, HasContext, NativeFramebuffer, PixelPackData, COLOR_ATTACHMENT0, COLOR_BUFFER_BIT,
+    DRAW_FRAMEBUFFER, FRAMEBUFFER_COMPLETE, NEAREST, READ_FRAMEBUFFER, RENDERBUFFER, RGBA, RGBA8,
+    SAMPLES, UNSIGNED_BYTE,
+};
+
+/// Read the color contents of `fbo` (or the default framebuffer) as tightly packed RGBA8.
+///
+/// Multisampled sources can't be read directly, so they are first blit-resolved
+/// into a temporary single-sample framebuffer. Rows are bottom-up (GL order).
+pub fn read_framebuffer(
+    gl: &Context,
+    fbo: Option<NativeFramebuffer>,
+    width: i32,
+    height: i32,
+) -> Result<Vec<u8>> {
+    let mut pixels = vec![0; width.max(0) as usize * height.max(0) as usize * 4];
+
+    unsafe {
+        gl.bind_framebuffer(READ_FRAMEBUFFER, fbo);
+
+        if gl.get_parameter_i32(SAMPLES) > 0 {
+            let resolve = gl.create_framebuffer()?;
+            let color = gl.create_renderbuffer()?;
+
+            gl.bind_renderbuffer(RENDERBUFFER, Some(color));
+            gl.renderbuffer_storage(RENDERBUFFER, RGBA8, width, height);
+            gl.bind_renderbuffer(RENDERBUFFER, None);
+
+            gl.bind_framebuffer(DRAW_FRAMEBUFFER, Some(resolve));
+            gl.framebuffer_renderbuffer(
+                DRAW_FRAMEBUFFER,
+                COLOR_ATTACHMENT0,
+                RENDERBUFFER,
+                Some(color),
+            );
+            let status = gl.check_framebuffer_status(DRAW_FRAMEBUFFER);
+
+            if status == FRAMEBUFFER_COMPLETE {
+                // resolve the samples, then read from the single-sample copy
+                gl.blit_framebuffer(
+                    0,
+                    0,
+                    width,
+                    height,
+                    0,
+                    0,
+                    width,
+                    height,
+                    COLOR_BUFFER_BIT,
+                    NEAREST,
+                );
+                gl.bind_framebuffer(READ_FRAMEBUFFER, Some(resolve));
+                read_pixels(gl, width, height, &mut pixels);
+            }
+
+            gl.bind_framebuffer(READ_FRAMEBUFFER, None);
+            gl.bind_framebuffer(DRAW_FRAMEBUFFER, None);
+            gl.delete_framebuffer(resolve);
+            gl.delete_renderbuffer(color);
+
+            if status != FRAMEBUFFER_COMPLETE {
+                return Err(Error::Framebuffer(status));
+            }
+        } else {
+            read_pixels(gl, width, height, &mut pixels);
+            gl.bind_framebuffer(READ_FRAMEBUFFER, None);
+        }
+    }
+    Ok(pixels)
+}
+
+/// Encode the pixels of [`read_framebuffer`] as a binary PPM image (top-down, alpha dropped).
+pub fn encode_ppm(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut ppm = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    ppm.reserve(width * height * 3);
+
+    if width > 0 {
+        for row in pixels.chunks_exact(width * 4).take(height).rev() {
+            for rgba in row.chunks_exact(4) {
+                ppm.extend_from_slice(&rgba[..3])
+            }
+        }
+    }
+    ppm
+}
+
+unsafe fn read_pixels(gl: &Context, width: i32, height: i32, pixels: &mut [u8]) {
+    gl.read_pixels(
+        0,
+        0,
+        width,
+        height,
+        RGBA,
+        UNSIGNED_BYTE,
+        PixelPackData::Slice(Some(pixels)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ppm_is_flipped_and_opaque() {
+        // 1x2, bottom row first (GL order)
+        let pixels = [1, 2, 3, 255, 4, 5, 6, 128];
+        let ppm = encode_ppm(&pixels, 1, 2);
+
+        let header = b"P6\n1 2\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(&ppm[header.len()..], [4, 5, 6, 1, 2, 3]);
+    }
+}
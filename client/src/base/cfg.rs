@@ -8,6 +8,18 @@ pub struct Config {
     #[arg(long, default_value_t = 60)]
     fps: u16,
 
+    /// Specify how many input commands are sent to the server each second.
+    #[arg(long, default_value_t = 60)]
+    input_rate: u16,
+
+    /// Write logarithmic depth (precise far away, but without early depth testing).
+    #[arg(long, default_value_t)]
+    log_depth: bool,
+
+    /// Move the light like the sun over a day of this many seconds.
+    #[arg(long)]
+    day_length: Option<f32>,
+
     /// Do not attempt to connect to server.
     #[arg(long, default_value_t)]
     offline: bool,
@@ -30,6 +42,18 @@ impl Config {
         self.fps
     }
 
+    pub const fn input_rate(&self) -> u16 {
+        self.input_rate
+    }
+
+    pub const fn log_depth(&self) -> bool {
+        self.log_depth
+    }
+
+    pub const fn day_length(&self) -> Option<f32> {
+        self.day_length
+    }
+
     pub const fn is_online(&self) -> bool {
         !self.offline
     }
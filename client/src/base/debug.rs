@@ -0,0 +1,239 @@
+use crate::*;
+use bytemuck::cast_slice;
+use glow::{
+    Context, HasContext, NativeBuffer, NativeVertexArray, ALIASED_LINE_WIDTH_RANGE, ARRAY_BUFFER,
+    ELEMENT_ARRAY_BUFFER, FLOAT, LINES, STREAM_DRAW, TRIANGLES, UNSIGNED_INT,
+};
+
+/// A single segment of [`DebugLines`].
+#[derive(Clone, Copy, Debug)]
+pub struct DebugLine {
+    pub a: Vector,
+    pub b: Vector,
+    pub color: Color,
+}
+
+/// Expand every line into a quad (two triangles) of `width` facing `eye`.
+///
+/// Returns the vertices (4 per line) and indices (6 per line).
+pub fn thick_line_quads(lines: &[DebugLine], eye: &Vector, width: f32) -> (Vec<f32>, Vec<u32>) {
+    let mut vertices = Vec::with_capacity(lines.len() * 12);
+    let mut indices = Vec::with_capacity(lines.len() * 6);
+
+    for (i, line) in lines.iter().enumerate() {
+        let dir = line.b - line.a;
+        let to_eye = eye - (line.a + line.b) / 2.0;
+
+        // perpendicular to both the line and the view (counter-clockwise towards `eye`)
+        let side = dir
+            .cross(&to_eye)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default()
+            * (width / 2.0);
+
+        for v in [line.a - side, line.a + side, line.b - side, line.b + side] {
+            vertices.extend(v.iter());
+        }
+
+        let n = i as u32 * 4;
+        indices.extend([n, n + 1, n + 2, n + 2, n + 1, n + 3]);
+    }
+    (vertices, indices)
+}
+
+/// The 12 edges of `aabb`, as pairs of corners.
+pub fn aabb_edges(aabb: &Aabb) -> Vec<(Vector, Vector)> {
+    // the bits of `i` pick the max (instead of the min) along each axis
+    let corner = |i: usize| {
+        Vector::from_fn(|axis, _| {
+            if i & (1 << axis) == 0 {
+                aabb.min[axis]
+            } else {
+                aabb.max[axis]
+            }
+        })
+    };
+
+    // every pair of corners differing along a single axis
+    (0..8)
+        .flat_map(|i| (0..3).map(move |axis| (i, axis)))
+        .filter(|(i, axis)| i & (1 << axis) == 0)
+        .map(|(i, axis)| (corner(i), corner(i | 1 << axis)))
+        .collect()
+}
+
+/// Immediate-mode segments, rebuilt every frame, for visualizing anything.
+#[derive(Debug)]
+pub struct DebugLines {
+    lines: Vec<DebugLine>,
+    thick_lines: bool,
+    vao: NativeVertexArray,
+    vbo: NativeBuffer,
+    ebo: NativeBuffer,
+}
+
+impl DebugLines {
+    pub fn new(gl: &Context) -> Result<Self> {
+        unsafe {
+            let vao = gl.create_vertex_array()?;
+            let vbo = gl.create_buffer()?;
+            let ebo = gl.create_buffer()?;
+
+            gl.bind_vertex_array(Some(vao));
+            gl.bind_buffer(ARRAY_BUFFER, Some(vbo));
+            gl.bind_buffer(ELEMENT_ARRAY_BUFFER, Some(ebo));
+
+            // enable `pos` attribute
+            gl.enable_vertex_attrib_array(0);
+            gl.vertex_attrib_pointer_f32(0, 3, FLOAT, false, 3 * size_of::<f32>() as i32, 0);
+
+            // unbind buffers
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(ARRAY_BUFFER, None);
+            gl.bind_buffer(ELEMENT_ARRAY_BUFFER, None);
+
+            Ok(Self {
+                lines: Vec::new(),
+                thick_lines: false,
+                vao,
+                vbo,
+                ebo,
+            })
+        }
+    }
+
+    pub fn line(&mut self, a: Vector, b: Vector, color: Color) {
+        self.lines.push(DebugLine { a, b, color })
+    }
+
+    /// Outline the edges of `aabb`.
+    pub fn aabb(&mut self, aabb: &Aabb, color: Color) {
+        for (a, b) in aabb_edges(aabb) {
+            self.line(a, b, color)
+        }
+    }
+
+    pub fn lines(&self) -> &[DebugLine] {
+        &self.lines
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear()
+    }
+
+    /// Draw camera-facing quads instead of GL lines (wide lines are poorly supported).
+    pub fn set_thick_lines(&mut self, enabled: bool) {
+        self.thick_lines = enabled
+    }
+
+    /// Render every line with the 'simple' `program`.
+    ///
+    /// `line_width` is in pixels (clamped to what the driver supports),
+    /// or world units with thick lines.
+    pub fn draw(
+        &self,
+        gl: &Context,
+        cam: &RawCamera,
+        color: ColorManagement,
+        program: Program,
+        line_width: f32,
+    ) {
+        if self.lines.is_empty() {
+            return;
+        }
+
+        debug_group(gl, "debug", || unsafe {
+            let native = program.native();
+            gl.use_program(Some(native));
+            setup_program(gl, program, cam, color, None, &[], &[]);
+
+            // lines are in world space
+            gl.uniform_matrix_4_f32_slice(
+                gl.get_uniform_location(native, "model").as_ref(),
+                false,
+                Matrix::identity().as_slice(),
+            );
+
+            gl.bind_vertex_array(Some(self.vao));
+            gl.bind_buffer(ARRAY_BUFFER, Some(self.vbo));
+
+            let obj_col = gl.get_uniform_location(native, "obj_col");
+
+            if self.thick_lines {
+                let (vertices, indices) = thick_line_quads(&self.lines, cam.pos(), line_width);
+
+                gl.buffer_data_u8_slice(ARRAY_BUFFER, cast_slice(&vertices), STREAM_DRAW);
+                gl.buffer_data_u8_slice(ELEMENT_ARRAY_BUFFER, cast_slice(&indices), STREAM_DRAW);
+
+                for (i, line) in self.lines.iter().enumerate() {
+                    gl.uniform_4_f32_slice(obj_col.as_ref(), &line.color);
+                    gl.draw_elements(
+                        TRIANGLES,
+                        6,
+                        UNSIGNED_INT,
+                        (i * 6 * size_of::<u32>()) as i32,
+                    );
+                }
+            } else {
+                let mut range = [1.0; 2];
+                gl.get_parameter_f32_slice(ALIASED_LINE_WIDTH_RANGE, &mut range);
+                gl.line_width(line_width.clamp(range[0], range[1]));
+
+                let vertices = self
+                    .lines
+                    .iter()
+                    .flat_map(|line| line.a.iter().chain(line.b.iter()).copied())
+                    .collect::<Vec<_>>();
+
+                gl.buffer_data_u8_slice(ARRAY_BUFFER, cast_slice(&vertices), STREAM_DRAW);
+
+                for (i, line) in self.lines.iter().enumerate() {
+                    gl.uniform_4_f32_slice(obj_col.as_ref(), &line.color);
+                    gl.draw_arrays(LINES, i as i32 * 2, 2);
+                }
+
+                gl.line_width(1.0);
+            }
+
+            // clean up
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(ARRAY_BUFFER, None);
+            gl.use_program(None);
+        });
+        gl_report(gl, "debug pass");
+    }
+
+    pub fn delete(&self, gl: &Context) {
+        unsafe {
+            gl.delete_vertex_array(self.vao);
+            gl.delete_buffer(self.vbo);
+            gl.delete_buffer(self.ebo);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aabb_edges_outline_the_box() {
+        let aabb = Aabb::new(Vector::new(-1.0, 0.0, 2.0), Vector::new(3.0, 1.0, 4.0));
+        let edges = aabb_edges(&aabb);
+        assert_eq!(edges.len(), 12);
+
+        // 4 edges along each axis, each as long as the box along it
+        for axis in 0..3 {
+            let along = edges
+                .iter()
+                .filter(|(a, b)| (0..3).all(|i| (a[i] == b[i]) != (i == axis)))
+                .collect::<Vec<_>>();
+            assert_eq!(along.len(), 4);
+
+            for (a, b) in along {
+                assert_eq!(b[axis] - a[axis], aabb.max[axis] - aabb.min[axis]);
+                assert!(aabb.contains(a) && aabb.contains(b));
+            }
+        }
+    }
+}
@@ -3,10 +3,26 @@ use crate::*;
 pub type Result<T = (), E = Error> = std::result::Result<T, E>;
 
 #[derive(thiserror::Error, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     #[error("Window: {0}")]
     Window(sdl2::video::WindowBuildError),
 
+    #[error("Framebuffer: incomplete (status: {0:#x})")]
+    Framebuffer(u32),
+
+    #[error("Attribute mismatch: {program:?} program (normals: {has_norms})")]
+    AttributeMismatch {
+        program: ProgramUnit,
+        has_norms: bool,
+    },
+
+    #[error("Missing references (child, parent): {0:?}")]
+    MissingReferences(Vec<(Id, Id)>),
+
+    #[error("OpenGL: {code:#x} ({context})")]
+    GlError { code: u32, context: String },
+
     #[error(transparent)]
     Blazed(BlazedError),
 }
@@ -6,7 +6,7 @@ use std::{
         Arc,
     },
     thread::{sleep, spawn, JoinHandle},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 pub struct Counter(u64);
@@ -93,3 +93,74 @@ impl FPSCounter {
         Duration::from_secs_f32((1000.0 / fps as f32) * 0.001)
     }
 }
+
+/// Limits frames to a target rate, reporting the actual delta between frames.
+#[derive(Clone, Copy)]
+pub struct FramePacer {
+    spin: SpinSleeper,
+    budget: Duration,
+    last: Instant,
+}
+
+impl FramePacer {
+    /// Pace at `fps` frames per second (zero is unlimited).
+    pub fn new(fps: u16) -> Self {
+        let mut pacer = Self {
+            spin: SpinSleeper::default(),
+            budget: Duration::ZERO,
+            last: Instant::now(),
+        };
+        pacer.set_fps(fps);
+        pacer
+    }
+
+    /// The time each frame is given.
+    pub const fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    pub fn set_fps(&mut self, fps: u16) {
+        self.budget = if fps == 0 {
+            Duration::ZERO
+        } else {
+            FPSCounter::limit_dur(fps)
+        }
+    }
+
+    /// How long to wait after `elapsed` of work (nothing if the frame overran its budget).
+    pub fn remaining(&self, elapsed: Duration) -> Duration {
+        self.budget.saturating_sub(elapsed)
+    }
+
+    /// Finish a frame, waiting out the rest of its budget (spinning for the last stretch),
+    /// then return the time since the previous frame for use as `dt`.
+    pub fn pace(&mut self) -> Duration {
+        let wait = self.remaining(self.last.elapsed());
+
+        if !wait.is_zero() {
+            self.spin.sleep(wait)
+        }
+
+        let now = Instant::now();
+        let dt = now - self.last;
+        self.last = now;
+        dt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pacer_waits_out_the_rest_of_the_budget() {
+        let mut pacer = FramePacer::new(60);
+        pacer.budget = Duration::from_micros(16_600);
+
+        let wait = pacer.remaining(Duration::from_millis(10));
+        assert_eq!(wait, Duration::from_micros(6_600));
+
+        // an overrun frame doesn't wait at all
+        assert!(pacer.remaining(Duration::from_millis(20)).is_zero());
+    }
+}
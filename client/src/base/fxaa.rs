@@ -0,0 +1,206 @@
+use crate::*;
+use glow::{
+    Context, HasContext, NativeFramebuffer, NativeProgram, NativeRenderbuffer, NativeTexture,
+    NativeVertexArray, PixelUnpackData, CLAMP_TO_EDGE, COLOR_ATTACHMENT0, DEPTH_ATTACHMENT,
+    DEPTH_COMPONENT24, DEPTH_TEST, FRAGMENT_SHADER, FRAMEBUFFER, FRAMEBUFFER_COMPLETE, LINEAR,
+    RENDERBUFFER, RGBA, RGBA8, TEXTURE0, TEXTURE_2D, TEXTURE_MAG_FILTER, TEXTURE_MIN_FILTER,
+    TEXTURE_WRAP_S, TEXTURE_WRAP_T, TRIANGLES, UNSIGNED_BYTE, VERTEX_SHADER,
+};
+
+/// The vertices of the fullscreen pass (a single oversized triangle; no diagonal seam).
+pub const FULLSCREEN_VERTICES: i32 = 3;
+
+/// Trade-off between the cost and smoothness of [`FxaaPass`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FxaaQuality {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl FxaaQuality {
+    /// (edge threshold, minimum edge threshold, sub-pixel blending, edge search steps)
+    pub const fn params(&self) -> (f32, f32, f32, i32) {
+        match self {
+            Self::Low => (0.250, 0.0833, 0.50, 4),
+            Self::Medium => (0.166, 0.0625, 0.75, 8),
+            Self::High => (0.125, 0.0312, 1.00, 12),
+        }
+    }
+}
+
+/// Fast approximate anti-aliasing (cheaper than multisampling) as a post-process.
+///
+/// The scene is rendered into a texture between [`FxaaPass::begin`] and
+/// [`FxaaPass::end_and_resolve`], which then filters it into the default framebuffer.
+#[derive(Debug)]
+pub struct FxaaPass {
+    fbo: NativeFramebuffer,
+    color: NativeTexture,
+    depth: NativeRenderbuffer,
+    vao: NativeVertexArray,
+    program: NativeProgram,
+    size: (i32, i32),
+    quality: FxaaQuality,
+}
+
+impl FxaaPass {
+    pub fn new(gl: &Context, width: i32, height: i32) -> Result<Self> {
+        let program = process_shaders(
+            gl,
+            [
+                (
+                    VERTEX_SHADER,
+                    include_str!("../../shaders/fxaa/shader.vert"),
+                ),
+                (
+                    FRAGMENT_SHADER,
+                    include_str!("../../shaders/fxaa/shader.frag"),
+                ),
+            ],
+        )?;
+
+        unsafe {
+            // vertices are generated from `gl_VertexID`, but a VAO must be bound
+            let vao = gl.create_vertex_array()?;
+
+            let fbo = gl.create_framebuffer()?;
+            let color = gl.create_texture()?;
+            let depth = gl.create_renderbuffer()?;
+
+            let pass = Self {
+                fbo,
+                color,
+                depth,
+                vao,
+                program,
+                size: (width, height),
+                quality: Default::default(),
+            };
+
+            pass.allocate(gl);
+
+            gl.bind_framebuffer(FRAMEBUFFER, Some(fbo));
+            gl.framebuffer_texture_2d(FRAMEBUFFER, COLOR_ATTACHMENT0, TEXTURE_2D, Some(color), 0);
+            gl.framebuffer_renderbuffer(FRAMEBUFFER, DEPTH_ATTACHMENT, RENDERBUFFER, Some(depth));
+
+            let status = gl.check_framebuffer_status(FRAMEBUFFER);
+            gl.bind_framebuffer(FRAMEBUFFER, None);
+
+            if status != FRAMEBUFFER_COMPLETE {
+                pass.delete(gl);
+                return Err(Error::Framebuffer(status));
+            }
+            Ok(pass)
+        }
+    }
+
+    pub const fn size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    pub const fn quality(&self) -> FxaaQuality {
+        self.quality
+    }
+
+    pub fn set_quality(&mut self, quality: FxaaQuality) {
+        self.quality = quality
+    }
+
+    /// Reallocate the scene texture if the resolution has changed.
+    pub fn resize(&mut self, gl: &Context, width: i32, height: i32) {
+        if self.size != (width, height) {
+            self.size = (width, height);
+            unsafe { self.allocate(gl) }
+        }
+    }
+
+    /// The framebuffer of the scene texture.
+    pub const fn framebuffer(&self) -> NativeFramebuffer {
+        self.fbo
+    }
+
+    /// Direct all rendering into the scene texture.
+    pub fn begin(&self, gl: &Context) {
+        unsafe { gl.bind_framebuffer(FRAMEBUFFER, Some(self.fbo)) }
+    }
+
+    /// Filter the scene texture into the default framebuffer.
+    pub fn end_and_resolve(&self, gl: &Context) {
+        let (width, height) = self.size;
+        let (edge_threshold, edge_threshold_min, subpix, search_steps) = self.quality.params();
+
+        unsafe {
+            gl.bind_framebuffer(FRAMEBUFFER, None);
+            gl.disable(DEPTH_TEST);
+
+            gl.use_program(Some(self.program));
+
+            gl.active_texture(TEXTURE0);
+            gl.bind_texture(TEXTURE_2D, Some(self.color));
+
+            let uniform = |name| gl.get_uniform_location(self.program, name);
+
+            gl.uniform_1_i32(uniform("scene").as_ref(), 0);
+            gl.uniform_2_f32(
+                uniform("inv_resolution").as_ref(),
+                1.0 / width as f32,
+                1.0 / height as f32,
+            );
+            gl.uniform_1_f32(uniform("edge_threshold").as_ref(), edge_threshold);
+            gl.uniform_1_f32(uniform("edge_threshold_min").as_ref(), edge_threshold_min);
+            gl.uniform_1_f32(uniform("subpix").as_ref(), subpix);
+            gl.uniform_1_i32(uniform("search_steps").as_ref(), search_steps);
+
+            gl.bind_vertex_array(Some(self.vao));
+            gl.draw_arrays(TRIANGLES, 0, FULLSCREEN_VERTICES);
+
+            // clean up
+            gl.bind_vertex_array(None);
+            gl.bind_texture(TEXTURE_2D, None);
+            gl.use_program(None);
+            gl.enable(DEPTH_TEST);
+        }
+        gl_report(gl, "fxaa pass");
+    }
+
+    pub fn delete(&self, gl: &Context) {
+        unsafe {
+            gl.delete_framebuffer(self.fbo);
+            gl.delete_texture(self.color);
+            gl.delete_renderbuffer(self.depth);
+            gl.delete_vertex_array(self.vao);
+            gl.delete_program(self.program);
+        }
+    }
+
+    /// (Re)allocate the storage of the attachments for the current size.
+    unsafe fn allocate(&self, gl: &Context) {
+        let (width, height) = self.size;
+
+        gl.bind_texture(TEXTURE_2D, Some(self.color));
+        gl.tex_image_2d(
+            TEXTURE_2D,
+            0,
+            RGBA8 as i32,
+            width,
+            height,
+            0,
+            RGBA,
+            UNSIGNED_BYTE,
+            PixelUnpackData::Slice(None),
+        );
+
+        // FXAA relies on bilinear filtering between texels
+        gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MIN_FILTER, LINEAR as i32);
+        gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MAG_FILTER, LINEAR as i32);
+        gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_WRAP_S, CLAMP_TO_EDGE as i32);
+        gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_WRAP_T, CLAMP_TO_EDGE as i32);
+        gl.bind_texture(TEXTURE_2D, None);
+
+        gl.bind_renderbuffer(RENDERBUFFER, Some(self.depth));
+        gl.renderbuffer_storage(RENDERBUFFER, DEPTH_COMPONENT24, width, height);
+        gl.bind_renderbuffer(RENDERBUFFER, None);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::*;
+use glow::{
+    Context, HasContext, NativeFramebuffer, NativeRenderbuffer, NativeTexture, PixelUnpackData,
+    COLOR_ATTACHMENT0, COLOR_ATTACHMENT1, COLOR_ATTACHMENT2, DEPTH_ATTACHMENT, DEPTH_COMPONENT24,
+    FLOAT, FRAMEBUFFER, FRAMEBUFFER_COMPLETE, NEAREST, RENDERBUFFER, RGBA, RGBA16F, RGBA8,
+    TEXTURE_2D, TEXTURE_MAG_FILTER, TEXTURE_MIN_FILTER, UNSIGNED_BYTE,
+};
+
+/// The color attachments of a [`GBuffer`] (albedo, position, normal).
+///
+/// These match the output locations of the 'normal' fragment shader.
+const ATTACHMENTS: [u32; 3] = [COLOR_ATTACHMENT0, COLOR_ATTACHMENT1, COLOR_ATTACHMENT2];
+
+/// An offscreen framebuffer with multiple render targets, used for deferred shading.
+#[derive(Clone, Copy, Debug)]
+pub struct GBuffer {
+    fbo: NativeFramebuffer,
+    albedo: NativeTexture,
+    position: NativeTexture,
+    normal: NativeTexture,
+    depth: NativeRenderbuffer,
+}
+
+impl GBuffer {
+    pub fn new(gl: &Context, width: i32, height: i32) -> Result<Self> {
+        unsafe {
+            let fbo = gl.create_framebuffer()?;
+            gl.bind_framebuffer(FRAMEBUFFER, Some(fbo));
+
+            // color attachments
+            let albedo = Self::create_target(gl, RGBA8, UNSIGNED_BYTE, width, height)?;
+            let position = Self::create_target(gl, RGBA16F, FLOAT, width, height)?;
+            let normal = Self::create_target(gl, RGBA16F, FLOAT, width, height)?;
+
+            for (attachment, texture) in ATTACHMENTS.into_iter().zip([albedo, position, normal]) {
+                gl.framebuffer_texture_2d(FRAMEBUFFER, attachment, TEXTURE_2D, Some(texture), 0);
+            }
+
+            // fragment outputs -> color attachments
+            gl.draw_buffers(&ATTACHMENTS);
+
+            // depth attachment
+            let depth = gl.create_renderbuffer()?;
+            gl.bind_renderbuffer(RENDERBUFFER, Some(depth));
+            gl.renderbuffer_storage(RENDERBUFFER, DEPTH_COMPONENT24, width, height);
+            gl.framebuffer_renderbuffer(FRAMEBUFFER, DEPTH_ATTACHMENT, RENDERBUFFER, Some(depth));
+            gl.bind_renderbuffer(RENDERBUFFER, None);
+
+            let status = gl.check_framebuffer_status(FRAMEBUFFER);
+            gl.bind_framebuffer(FRAMEBUFFER, None);
+
+            let gbuf = Self {
+                fbo,
+                albedo,
+                position,
+                normal,
+                depth,
+            };
+
+            if status != FRAMEBUFFER_COMPLETE {
+                gbuf.delete(gl);
+                return Err(Error::Framebuffer(status));
+            }
+            Ok(gbuf)
+        }
+    }
+
+    pub const fn albedo(&self) -> NativeTexture {
+        self.albedo
+    }
+
+    pub const fn position(&self) -> NativeTexture {
+        self.position
+    }
+
+    pub const fn normal(&self) -> NativeTexture {
+        self.normal
+    }
+
+    pub const fn attachments(&self) -> [NativeTexture; 3] {
+        [self.albedo, self.position, self.normal]
+    }
+
+    /// Direct all rendering into this buffer.
+    pub fn bind(&self, gl: &Context) {
+        unsafe { gl.bind_framebuffer(FRAMEBUFFER, Some(self.fbo)) }
+    }
+
+    /// Restore the default framebuffer.
+    pub fn unbind(&self, gl: &Context) {
+        unsafe { gl.bind_framebuffer(FRAMEBUFFER, None) }
+    }
+
+    /// Recreate every attachment with the new dimensions.
+    pub fn resize(&mut self, gl: &Context, width: i32, height: i32) -> Result {
+        let gbuf = Self::new(gl, width, height)?;
+        self.delete(gl);
+        *self = gbuf;
+        Ok(())
+    }
+
+    pub fn delete(&self, gl: &Context) {
+        unsafe {
+            gl.delete_framebuffer(self.fbo);
+            self.attachments()
+                .into_iter()
+                .for_each(|texture| gl.delete_texture(texture));
+            gl.delete_renderbuffer(self.depth);
+        }
+    }
+
+    unsafe fn create_target(
+        gl: &Context,
+        internal_format: u32,
+        ty: u32,
+        width: i32,
+        height: i32,
+    ) -> Result<NativeTexture> {
+        let texture = gl.create_texture()?;
+        gl.bind_texture(TEXTURE_2D, Some(texture));
+        gl.tex_image_2d(
+            TEXTURE_2D,
+            0,
+            internal_format as i32,
+            width,
+            height,
+            0,
+            RGBA,
+            ty,
+            PixelUnpackData::Slice(None),
+        );
+        gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MIN_FILTER, NEAREST as i32);
+        gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MAG_FILTER, NEAREST as i32);
+        gl.bind_texture(TEXTURE_2D, None);
+        Ok(texture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_color_attachment_per_fragment_output() {
+        let frag = include_str!("../../shaders/normal/shader.frag");
+        let outputs = frag
+            .lines()
+            .filter(|line| {
+                line.trim_start().starts_with("layout(location") && line.contains(" out ")
+            })
+            .count();
+
+        assert_eq!(ATTACHMENTS.len(), 3);
+        assert_eq!(outputs, ATTACHMENTS.len());
+        assert!(ATTACHMENTS.windows(2).all(|pair| pair[0] + 1 == pair[1]));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::*;
+use glow::Context;
+
+/// A single sample of a [`Clip`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe {
+    /// Seconds since the start of the clip.
+    pub time: f32,
+    pub pos: Vector,
+    pub rotation: UnitQuaternion,
+}
+
+/// A recorded path (e.g., a lap), sampled by time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Clip {
+    keyframes: Vec<Keyframe>,
+}
+
+impl Clip {
+    /// Append a keyframe, returning `false` (dropping it) unless it's later than the last one.
+    pub fn record(&mut self, time: f32, pos: Vector, rotation: UnitQuaternion) -> bool {
+        if !time.is_finite() || self.keyframes.last().is_some_and(|last| time <= last.time) {
+            return false;
+        }
+        self.keyframes.push(Keyframe {
+            time,
+            pos,
+            rotation,
+        });
+        true
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    /// The time of the last keyframe.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |last| last.time)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// The transform at `time`, interpolated between the surrounding keyframes (and held
+    /// before the first and after the last).
+    pub fn sample(&self, time: f32) -> Option<(Vector, UnitQuaternion)> {
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+
+        let (a, b) = match (
+            self.keyframes.get(next.wrapping_sub(1)),
+            self.keyframes.get(next),
+        ) {
+            (Some(a), Some(b)) => (a, b),
+            (Some(k), None) | (None, Some(k)) => return Some((k.pos, k.rotation)),
+            (None, None) => return None,
+        };
+
+        let t = (time - a.time) / (b.time - a.time);
+        Some((a.pos.lerp(&b.pos, t), a.rotation.slerp(&b.rotation, t)))
+    }
+}
+
+/// A translucent object replaying a [`Clip`] (e.g., a race ghost).
+///
+/// It's kept out of [`RawObjects`], so it never collides, casts shadows, or shows up in
+/// queries; draw it with [`RawObjects::draw_ghosts`].
+#[derive(Debug)]
+pub struct GhostObject {
+    obj: Object,
+    clip: Clip,
+    time: f32,
+    looping: bool,
+}
+
+impl GhostObject {
+    /// The transparency of ghosts by default.
+    pub const ALPHA: f32 = 0.35;
+
+    /// Replay `clip` with `obj` (which is owned by the ghost from now on) at `alpha`.
+    pub fn new(obj: Object, clip: Clip, alpha: f32) -> Self {
+        let mut ghost = Self {
+            obj,
+            clip,
+            time: 0.0,
+            looping: false,
+        };
+
+        let data = ghost.obj.data_mut();
+        data.set_cast_shadow(false);
+        data.set_two_pass_transparency(true);
+
+        ghost.set_alpha(alpha);
+        ghost.set_time(0.0);
+        ghost
+    }
+
+    pub const fn object(&self) -> &Object {
+        &self.obj
+    }
+
+    pub const fn clip(&self) -> &Clip {
+        &self.clip
+    }
+
+    /// The playback time (in seconds).
+    pub const fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Jump to `time`, moving the ghost to where the clip was then.
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time.max(0.0);
+
+        if let Some((pos, rotation)) = self.clip.sample(self.time) {
+            let data = self.obj.data_mut();
+            *data.pos_mut() = pos;
+            data.translation_upt();
+            data.set_rotation(rotation);
+        }
+    }
+
+    /// Play `dt` seconds on (restarting at the end if looping).
+    pub fn advance(&mut self, dt: f32) {
+        let mut time = self.time + dt;
+        let duration = self.clip.duration();
+
+        if self.looping && duration > 0.0 {
+            time %= duration
+        }
+        self.set_time(time)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.time >= self.clip.duration()
+    }
+
+    pub const fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.obj.data().alpha()
+    }
+
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.obj.data_mut().set_alpha(alpha.clamp(0.0, 1.0))
+    }
+
+    pub fn delete(&self, gl: &Context) {
+        free_buffers(gl, self.obj.buffers())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base::obj::tests::{basic, object};
+
+    #[test]
+    fn ghost_follows_the_clip_interpolation() {
+        let turn = UnitQuaternion::from_axis_angle(&Y_AXIS_UNIT, 1.0);
+
+        let mut clip = Clip::default();
+        assert!(clip.record(0.0, Vector::zeros(), UnitQuaternion::identity()));
+        assert!(clip.record(2.0, Vector::new(4.0, 0.0, -2.0), turn));
+        assert!(!clip.record(1.0, Vector::zeros(), turn));
+
+        let obj = object(basic(LOCAL_IDS.start, Vector::repeat(9.0)));
+        let mut ghost = GhostObject::new(obj, clip, GhostObject::ALPHA);
+        assert_eq!(*ghost.object().data().pos(), Vector::zeros());
+
+        ghost.advance(0.5);
+        let (pos, rotation) = ghost.clip().sample(0.5).unwrap();
+        assert_eq!(pos, Vector::new(1.0, 0.0, -0.5));
+        assert_eq!(*ghost.object().data().pos(), pos);
+        assert_eq!(ghost.object().data().rotation(), &rotation);
+        assert_eq!(ghost.object().data().translation().vector, pos);
+
+        // held at the end
+        ghost.advance(5.0);
+        assert!(ghost.is_finished());
+        assert_eq!(*ghost.object().data().pos(), Vector::new(4.0, 0.0, -2.0));
+        assert_eq!(ghost.alpha(), GhostObject::ALPHA);
+    }
+}
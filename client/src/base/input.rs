@@ -0,0 +1,153 @@
+use crate::*;
+use std::{collections::BTreeMap, mem::take, time::Duration};
+
+/// One slot per bit of [`Flags`].
+const KEY_BITS: usize = u16::BITS as usize;
+
+/// Everything sampled during a single interval of an [`InputSampler`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InputCommand {
+    keys: Flags,
+    held: [Duration; KEY_BITS],
+    motion: (i32, i32),
+    wheel: f32,
+}
+
+impl InputCommand {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.motion == (0, 0) && self.wheel == 0.0
+    }
+
+    /// The packets describing this command (keys held for the same number of ticks share one).
+    pub fn inputs(&self) -> Vec<Input> {
+        let mut groups = BTreeMap::<u16, Flags>::new();
+
+        for bit in (0..KEY_BITS).filter(|bit| self.keys.bits() & (1 << bit) != 0) {
+            let ticks = (self.held[bit].as_secs_f32() / TICK_RATE.as_secs_f32()).round() as u16;
+            *groups.entry(ticks.clamp(1, MAX_HELD_TICKS)).or_default() |=
+                Flags::from_bits_retain(1 << bit);
+        }
+
+        let mut inputs = groups
+            .into_iter()
+            .map(|(ticks, keys)| Input::Held { keys, ticks })
+            .collect::<Vec<_>>();
+
+        if self.motion != (0, 0) {
+            let (xrel, yrel) = self.motion;
+            inputs.push(Input::Mouse(Mouse::Motion { xrel, yrel }))
+        }
+        if self.wheel != 0.0 {
+            inputs.push(Input::Mouse(Mouse::Wheel {
+                precise_y: self.wheel,
+            }))
+        }
+        inputs
+    }
+}
+
+/// Consolidates inputs into one [`InputCommand`] per interval, regardless of the frame rate.
+#[derive(Clone, Copy, Debug)]
+pub struct InputSampler {
+    interval: Duration,
+    elapsed: Duration,
+    current: Flags,
+    pending: InputCommand,
+}
+
+impl InputSampler {
+    /// Sample at `rate` commands per second.
+    pub fn new(rate: u16) -> Self {
+        Self {
+            interval: SECOND / rate.max(1) as u32,
+            elapsed: Duration::ZERO,
+            current: Flags::empty(),
+            pending: Default::default(),
+        }
+    }
+
+    /// The time left until the next command is due.
+    pub fn remaining(&self) -> Duration {
+        self.interval.saturating_sub(self.elapsed)
+    }
+
+    /// Record an input of the current frame.
+    ///
+    /// Keys only count as held for the frame they're reported in.
+    pub fn record(&mut self, input: Input) {
+        match input {
+            Input::Mouse(Mouse::Motion { xrel, yrel }) => {
+                self.pending.motion.0 += xrel;
+                self.pending.motion.1 += yrel;
+            }
+            Input::Mouse(Mouse::Wheel { precise_y }) => self.pending.wheel += precise_y,
+            Input::Keyboard(keys) => self.current |= keys,
+            Input::Held { keys, ticks } => self.hold(keys, TICK_RATE * ticks as u32),
+        }
+    }
+
+    /// Finish a frame of `dt`, returning the consolidated command once an interval has passed.
+    ///
+    /// Intervals without any input produce nothing.
+    pub fn advance(&mut self, dt: Duration) -> Option<InputCommand> {
+        let keys = take(&mut self.current);
+        self.hold(keys, dt);
+        self.elapsed += dt;
+
+        if self.elapsed < self.interval {
+            return None;
+        }
+        self.elapsed -= self.interval;
+
+        // don't catch up on missed intervals
+        if self.elapsed >= self.interval {
+            self.elapsed = Duration::ZERO
+        }
+
+        let cmd = take(&mut self.pending);
+        (!cmd.is_empty()).then_some(cmd)
+    }
+
+    fn hold(&mut self, keys: Flags, dt: Duration) {
+        self.pending.keys |= keys;
+
+        for bit in (0..KEY_BITS).filter(|bit| keys.bits() & (1 << bit) != 0) {
+            self.pending.held[bit] += dt
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_within_an_interval_make_one_command() {
+        // 4 frames of 240hz within one interval of 60hz
+        let mut sampler = InputSampler::new(60);
+        let frame = SECOND / 240;
+
+        let mut commands = Vec::new();
+        for i in 0..4 {
+            sampler.record(Input::Keyboard(Flags::W));
+            sampler.record(Input::Mouse(Mouse::Motion { xrel: 1, yrel: -2 }));
+
+            if i % 2 == 0 {
+                sampler.record(Input::Mouse(Mouse::Wheel { precise_y: 0.5 }));
+            }
+            commands.extend(sampler.advance(frame + Duration::from_micros(1)));
+        }
+        assert_eq!(commands.len(), 1);
+
+        let cmd = commands[0];
+        assert_eq!(cmd.keys, Flags::W);
+        assert_eq!(cmd.motion, (4, -8));
+        assert_eq!(cmd.wheel, 1.0);
+
+        let held = cmd.held[Flags::W.bits().trailing_zeros() as usize];
+        assert_eq!(held, (frame + Duration::from_micros(1)) * 4);
+
+        // nothing is left for the next interval
+        assert_eq!(sampler.advance(SECOND / 60), None);
+    }
+}
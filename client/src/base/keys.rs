@@ -1,5 +1,6 @@
 use crate::*;
 use sdl2::keyboard::Scancode;
+use std::collections::HashMap;
 
 pub const fn from_scancode(key: Scancode) -> Flags {
     match key {
@@ -30,3 +31,175 @@ pub const fn try_from_scancode(key: Scancode) -> Option<Flags> {
         Some(flags)
     }
 }
+
+/// An analog stick of a gamepad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+/// A two-dimensional control (e.g., movement).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Move,
+    Look,
+}
+
+/// The sources of an [`Action`]: one key per direction and (optionally) a stick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AxisBinding {
+    /// -x, +x, -y, +y
+    pub keys: [Flags; 4],
+    pub stick: Option<Stick>,
+}
+
+/// Scale `(x, y)` so the edge of the (radial) dead zone maps to zero and the edge of
+/// the stick to one, zeroing anything within the dead zone.
+pub fn apply_dead_zone((x, y): (f32, f32), dead_zone: f32) -> (f32, f32) {
+    let dead_zone = dead_zone.clamp(0.0, 0.99);
+    let magnitude = x.hypot(y);
+
+    if magnitude <= dead_zone || !magnitude.is_finite() {
+        return (0.0, 0.0);
+    }
+
+    let scale = (magnitude.min(1.0) - dead_zone) / (1.0 - dead_zone) / magnitude;
+    (x * scale, y * scale)
+}
+
+/// Maps keyboard and gamepad state onto [`Action`]s.
+#[derive(Clone, Debug)]
+pub struct InputMap {
+    bindings: HashMap<Action, AxisBinding>,
+    dead_zone: f32,
+    keys: Flags,
+    sticks: [(f32, f32); 2],
+}
+
+impl InputMap {
+    pub const DEFAULT_DEAD_ZONE: f32 = 0.15;
+
+    pub fn new(dead_zone: f32) -> Self {
+        let bindings = HashMap::from([
+            (
+                Action::Move,
+                AxisBinding {
+                    keys: [Flags::A, Flags::D, Flags::S, Flags::W],
+                    stick: Some(Stick::Left),
+                },
+            ),
+            (
+                Action::Look,
+                AxisBinding {
+                    keys: [Flags::LEFT, Flags::RIGHT, Flags::DOWN, Flags::UP],
+                    stick: Some(Stick::Right),
+                },
+            ),
+        ]);
+
+        Self {
+            bindings,
+            dead_zone,
+            keys: Flags::empty(),
+            sticks: Default::default(),
+        }
+    }
+
+    pub fn bind(&mut self, action: Action, binding: AxisBinding) {
+        self.bindings.insert(action, binding);
+    }
+
+    pub const fn dead_zone(&self) -> f32 {
+        self.dead_zone
+    }
+
+    pub fn set_dead_zone(&mut self, dead_zone: f32) {
+        self.dead_zone = dead_zone
+    }
+
+    /// The keys currently held.
+    pub fn set_keys(&mut self, keys: Flags) {
+        self.keys = keys
+    }
+
+    /// The position of `stick` (each component within [-1, 1]).
+    pub fn set_stick(&mut self, stick: Stick, (x, y): (f32, f32)) {
+        self.sticks[stick as usize] = (x.clamp(-1.0, 1.0), y.clamp(-1.0, 1.0))
+    }
+
+    /// The position of `stick` from raw SDL axis values (y pointing down).
+    pub fn set_stick_raw(&mut self, stick: Stick, x: i16, y: i16) {
+        let norm = |v: i16| v as f32 / i16::MAX as f32;
+        self.set_stick(stick, (norm(x), -norm(y)))
+    }
+
+    /// The direction of `action`, at most unit length (keys and stick combined).
+    pub fn axis(&self, action: Action) -> (f32, f32) {
+        let Some(binding) = self.bindings.get(&action) else {
+            return (0.0, 0.0);
+        };
+
+        let held = |key: Flags| self.keys.intersects(key) as i8 as f32;
+        let [neg_x, pos_x, neg_y, pos_y] = binding.keys;
+
+        let (sx, sy) = binding
+            .stick
+            .map(|stick| apply_dead_zone(self.sticks[stick as usize], self.dead_zone))
+            .unwrap_or_default();
+
+        let (x, y) = (
+            held(pos_x) - held(neg_x) + sx,
+            held(pos_y) - held(neg_y) + sy,
+        );
+
+        let magnitude = x.hypot(y);
+        if magnitude > 1.0 {
+            (x / magnitude, y / magnitude)
+        } else {
+            (x, y)
+        }
+    }
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DEAD_ZONE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stick_within_dead_zone_is_zero() {
+        let mut input = InputMap::new(0.2);
+        input.set_stick(Stick::Left, (0.1, 0.1));
+
+        assert_eq!(input.axis(Action::Move), (0.0, 0.0));
+    }
+
+    #[test]
+    fn dead_zone_edge_maps_to_zero_magnitude() {
+        let (x, y) = apply_dead_zone((0.2 + 1e-4, 0.0), 0.2);
+        assert!(x.hypot(y) < 1e-3);
+
+        let (x, y) = apply_dead_zone((0.6, 0.0), 0.2);
+        assert!((x - 0.5).abs() < 1e-6 && y == 0.0);
+
+        let (x, y) = apply_dead_zone((0.0, -1.0), 0.2);
+        assert!((y + 1.0).abs() < 1e-6 && x == 0.0);
+    }
+
+    #[test]
+    fn keys_and_stick_combine_within_unit_length() {
+        let mut input = InputMap::default();
+        input.set_keys(Flags::W);
+        input.set_stick(Stick::Left, (1.0, 0.0));
+
+        let (x, y) = input.axis(Action::Move);
+        assert!((x.hypot(y) - 1.0).abs() < 1e-6);
+        assert!((x - y).abs() < 1e-6);
+    }
+}
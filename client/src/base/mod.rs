@@ -1,19 +1,44 @@
+mod capture;
 mod cfg;
+mod debug;
 mod err;
 mod fps;
+mod fxaa;
+mod gbuffer;
+mod ghost;
+mod input;
 mod keys;
 mod net;
 mod obj;
 mod render;
+mod scale;
+mod scene;
+mod screen;
+mod shadow;
+mod step;
+mod texture;
+mod timer;
 mod util;
 
+pub use capture::*;
 pub use cfg::*;
+pub use debug::*;
 pub use err::*;
 pub use fps::*;
+pub use fxaa::*;
+pub use gbuffer::*;
+pub use ghost::*;
+pub use input::*;
 pub use keys::*;
 pub use net::*;
 pub use obj::*;
 pub use render::*;
+pub use scale::*;
+pub use screen::*;
+pub use shadow::*;
+pub use step::*;
+pub use texture::*;
+pub use timer::*;
 pub use util::*;
 
 pub use blazed_demo::*;
@@ -101,12 +126,14 @@ pub enum UserAction {
 }
 
 /// Event wrappers related to the game.
-#[derive(Clone, Copy, Debug)]
+///
+/// Object actions are boxed since [`ObjectData`] dwarfs every other event.
+#[derive(Clone, Debug)]
 pub enum GameEvent {
     Quit,
     Reset,
     Render(RenderAction),
-    Object(ObjectAction),
+    Object(Box<ObjectAction>),
     User(UserAction),
 }
 
@@ -36,7 +36,13 @@ pub fn handle_tcp(
             // wait for response
             match tcp.recv(
                 &mut buf,
-                PacketKind::AddObj | PacketKind::RemObj | PacketKind::Ping,
+                PacketKind::AddObj
+                    | PacketKind::RemObj
+                    | PacketKind::SpawnConfirm
+                    | PacketKind::SpawnReject
+                    | PacketKind::Hit
+                    | PacketKind::Disconnect
+                    | PacketKind::Ping,
             )? {
                 Packet::AddObj { data } => {
                     handle_obj(id, ObjectAction::Add { data }, &event_sender)?;
@@ -48,6 +54,24 @@ pub fn handle_tcp(
                     _ = render_sender.try_send(());
                 }
 
+                // the object itself arrives with everyone else's copy (see `AddObj`)
+                Packet::SpawnConfirm { request, data } => {
+                    debug!("[spawn] Request {} was assigned id {}", request, data.id())
+                }
+                Packet::SpawnReject { request, reason } => {
+                    warn!("[spawn] Request {} was rejected ({:?})", request, reason)
+                }
+
+                Packet::Hit { shooter, target } => {
+                    info!("[fire] ({}) hit ({})", shooter, target)
+                }
+
+                // the connection is closed right after (ending this thread ends the client)
+                Packet::Disconnect { reason } => {
+                    error!("[TCP] Disconnected by the server ({:?})", reason);
+                    return Ok(());
+                }
+
                 Packet::Ping => (),
                 _ => unreachable!(),
             }
@@ -27,11 +27,20 @@ pub fn handle_udp(
 
         loop {
             // wait for server to send player update
-            let packet: Packet = udp.recv(&mut buf, PacketKind::UptObj)?;
+            let packet: Packet = udp.recv(&mut buf, PacketKind::UptObj | PacketKind::TickRate)?;
 
-            if let Packet::UptObj { data } = packet {
-                handle_obj(id, ObjectAction::Upt { data }, &event_sender)?;
-                _ = render_sender.try_send(());
+            match packet {
+                Packet::UptObj { data, .. } => {
+                    handle_obj(id, ObjectAction::Upt { data }, &event_sender)?;
+                    _ = render_sender.try_send(());
+                }
+
+                // updates arrive (and are interpolated) at the new rate from now on
+                Packet::TickRate { tick_rate } => {
+                    warn!("[UDP] Server tick rate changed to {:?}", tick_rate);
+                    continue;
+                }
+                _ => unreachable!(),
             }
             rate.fetch_add(1, Ordering::Relaxed);
         }
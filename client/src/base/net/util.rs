@@ -1,8 +1,8 @@
 use crate::*;
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
 use pfrs::*;
 
-use std::{net::Ipv4Addr, thread::sleep};
+use std::{net::Ipv4Addr, thread::sleep, time::Instant};
 
 pub fn handshake(
     tcp: &TcpClient,
@@ -18,10 +18,18 @@ pub fn handshake(
     let mut buf = [0; PACKET_SIZE];
 
     debug!("[TCP] [][2] Receiving server handshake");
-    let id = tcp
+    let server = tcp
         .recv::<PacketKind, Packet, PACKET_SIZE>(&mut buf, PacketKind::Handshake)?
-        .into_server_handshake()?
-        .id();
+        .into_server_handshake()?;
+    let id = server.id();
+
+    if server.tick_rate() != TICK_RATE {
+        warn!(
+            "[TCP] Server tick rate ({:?}) differs from the client's ({:?})",
+            server.tick_rate(),
+            TICK_RATE
+        )
+    }
 
     debug!("[UDP] [][3] Sending client handshake");
     udp.send(&Packet::Handshake {
@@ -49,13 +57,13 @@ fn map_obj_event(user_id: Id, action: ObjectAction) -> Result<GameEvent> {
         ObjectAction::Add { data } | ObjectAction::Upt { data } => (data.id() == user_id)
             .then_some({
                 let data = data.player().ok_or("Expected 'Player' object type")?.data();
-                GameEvent::Object(ObjectAction::User { data })
+                GameEvent::Object(Box::new(ObjectAction::User { data }))
             }),
 
         ObjectAction::Rem { id } => (id == user_id).then_some(GameEvent::Reset),
         _ => unreachable!(),
     }
-    .unwrap_or(GameEvent::Object(action));
+    .unwrap_or_else(|| GameEvent::Object(Box::new(action)));
     Ok(game_event)
 }
 
@@ -101,7 +109,7 @@ pub fn handle_conn(
     handle_udp(&s, udp_clone, render_sender.clone(), event_sender, tps, id);
 
     // handle mouse and keyboard input
-    handle_input(&s, udp, input_receiver);
+    handle_input(&s, udp, input_receiver, cfg.input_rate());
 
     Ok(())
 }
@@ -139,13 +147,33 @@ pub fn init_conn(
     });
 }
 
-pub fn handle_input(s: &SyncSelect, mut udp: UdpClient, input_receiver: Receiver<Input>) {
+pub fn handle_input(
+    s: &SyncSelect,
+    mut udp: UdpClient,
+    input_receiver: Receiver<Input>,
+    rate: u16,
+) {
     s.spawn(move || -> Result<()> {
         udp.socket().set_write_timeout(Some(TICK_RATE))?;
 
-        // repeatedly send user input to server
-        while let Ok(input) = input_receiver.recv() {
-            udp.send(&Packet::Input { input })?;
+        let mut sampler = InputSampler::new(rate);
+        let mut last = Instant::now();
+
+        // send consolidated user input to server at a fixed rate
+        loop {
+            match input_receiver.recv_timeout(sampler.remaining()) {
+                Ok(input) => sampler.record(input),
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            let now = Instant::now();
+            if let Some(cmd) = sampler.advance(now - last) {
+                for input in cmd.inputs() {
+                    udp.send(&Packet::Input { input })?;
+                }
+            }
+            last = now;
         }
         Err(BlazedError::Infallible.into())
     });
@@ -1,13 +1,21 @@
 use crate::*;
 use bytemuck::{cast_slice, NoUninit};
 use glow::{
-    Context, HasContext, NativeBuffer, NativeVertexArray, ARRAY_BUFFER, ELEMENT_ARRAY_BUFFER,
-    FLOAT, STATIC_DRAW, TRIANGLES, TRIANGLE_STRIP, UNSIGNED_BYTE,
+    Context, HasContext, NativeBuffer, NativeVertexArray, ARRAY_BUFFER, BUFFER, BUFFER_SIZE,
+    CLIP_DISTANCE0, DEPTH_BUFFER_BIT, ELEMENT_ARRAY_BUFFER, FLOAT, MAP_INVALIDATE_BUFFER_BIT,
+    MAP_WRITE_BIT, SCISSOR_TEST, STATIC_DRAW, STREAM_DRAW, TRIANGLES, TRIANGLE_FAN, TRIANGLE_STRIP,
+    UNSIGNED_BYTE, UNSIGNED_INT, UNSIGNED_SHORT, VERTEX_ARRAY,
 };
 use std::{
-    collections::HashMap,
+    any::Any,
+    cell::Cell,
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    f32::consts::TAU,
     fmt::Debug,
+    fs::read_to_string,
     ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
 #[derive(Clone, Copy, Debug)]
@@ -15,6 +23,7 @@ pub struct Buffers {
     vao: NativeVertexArray,
     vbo: NativeBuffer,
     ebo: NativeBuffer,
+    shared: bool,
 }
 
 impl Buffers {
@@ -29,6 +38,476 @@ impl Buffers {
     pub const fn ebo(&self) -> NativeBuffer {
         self.ebo
     }
+
+    /// Shared buffers (VBO and EBO) are owned by a [`MeshCache`], not the object.
+    pub const fn is_shared(&self) -> bool {
+        self.shared
+    }
+}
+
+/// Count the triangles drawn by `indices` with the specified primitive `mode`.
+///
+/// Runs separated by the fixed primitive restart index (all bits set) are counted separately.
+pub fn triangle_count<I: NoUninit>(mode: u32, indices: &[I]) -> usize {
+    let per_run = |n: usize| match mode {
+        TRIANGLES => n / 3,
+        TRIANGLE_STRIP | TRIANGLE_FAN => n.saturating_sub(2),
+        _ => 0,
+    };
+
+    let bytes: &[u8] = cast_slice(indices);
+    let (mut total, mut run) = (0, 0);
+
+    for index in bytes.chunks(size_of::<I>()) {
+        if index.iter().all(|&b| b == u8::MAX) {
+            total += per_run(run);
+            run = 0;
+        } else {
+            run += 1;
+        }
+    }
+    total + per_run(run)
+}
+
+/// The size (in bytes) of a single index of the specified GL type.
+pub const fn element_type_size(element_type: u32) -> Option<usize> {
+    match element_type {
+        UNSIGNED_BYTE => Some(1),
+        UNSIGNED_SHORT => Some(2),
+        UNSIGNED_INT => Some(4),
+        _ => None,
+    }
+}
+
+/// How the index buffer of an [`Object`] is drawn.
+#[derive(Clone, Copy, Debug)]
+pub struct Elements {
+    mode: u32,
+    element_type: u32,
+    len: i32,
+    index_size: usize,
+    triangles: usize,
+    /// Whether the vertices interleave normals.
+    has_norms: bool,
+}
+
+impl Elements {
+    fn new<I: NoUninit>(mode: u32, element_type: u32, indices: &[I], has_norms: bool) -> Self {
+        Self {
+            mode,
+            element_type,
+            len: indices.len() as i32,
+            index_size: size_of::<I>(),
+            triangles: triangle_count(mode, indices),
+            has_norms,
+        }
+    }
+}
+
+/// An interleaved vertex attribute (all of them being floats).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub name: &'static str,
+    /// The offset (in bytes) from the start of a vertex.
+    pub offset: i32,
+    /// The number of components.
+    pub size: i32,
+}
+
+/// The layout of the vertex buffer of an [`Object`], for diagnosing stride/offset bugs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VertexLayout {
+    /// The size (in bytes) of a vertex.
+    pub stride: i32,
+    pub attributes: Vec<VertexAttribute>,
+}
+
+impl VertexLayout {
+    /// `pos`, then `norm` if present.
+    pub fn new(has_norms: bool) -> Self {
+        let float = size_of::<f32>() as i32;
+
+        let mut attributes = vec![VertexAttribute {
+            name: "pos",
+            offset: 0,
+            size: 3,
+        }];
+        if has_norms {
+            attributes.push(VertexAttribute {
+                name: "norm",
+                offset: 3 * float,
+                size: 3,
+            });
+        }
+
+        Self {
+            stride: attributes.iter().map(|a| a.size).sum::<i32>() * float,
+            attributes,
+        }
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&VertexAttribute> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+}
+
+/// The built-in mesh an [`Object`] was constructed from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Geometry {
+    FlatCube,
+    Cube,
+    Cone {
+        segments: u16,
+    },
+    SmoothCube,
+
+    /// Arbitrary vertices (see [`Object::from_raw`]), which can't be reconstructed.
+    Custom,
+}
+
+/// Pop and handle items off the front of `queue` until `budget` has passed since the
+/// first call of `now`, returning how many were handled.
+///
+/// At least one item is handled per call (if any), so a tiny budget still makes progress.
+fn drain_within<T>(
+    queue: &mut VecDeque<T>,
+    budget: Duration,
+    mut now: impl FnMut() -> Instant,
+    mut f: impl FnMut(T) -> Result,
+) -> Result<usize> {
+    let start = now();
+    let mut handled = 0;
+
+    while handled == 0 || now() - start < budget {
+        let Some(item) = queue.pop_front() else {
+            break;
+        };
+        f(item)?;
+        handled += 1;
+    }
+    Ok(handled)
+}
+
+/// Pop and handle at most `max` items off the front of `queue`, returning how many were
+/// handled.
+fn drain_batch<T>(
+    queue: &mut VecDeque<T>,
+    max: usize,
+    mut f: impl FnMut(T) -> Result,
+) -> Result<usize> {
+    let mut handled = 0;
+
+    while handled < max {
+        let Some(item) = queue.pop_front() else {
+            break;
+        };
+        f(item)?;
+        handled += 1;
+    }
+    Ok(handled)
+}
+
+/// Whether vertices with(out) normals can be drawn by a program of `kind`.
+///
+/// Only the 'simple' program has no `norm` attribute to bind normals to (the 'normal'
+/// program falls back to a constant normal without them, see [`Object::lacks_normals`]).
+fn check_attributes(kind: ProgramUnit, has_norms: bool) -> Result {
+    if has_norms && kind == ProgramUnit::Simple {
+        return Err(Error::AttributeMismatch {
+            program: kind,
+            has_norms,
+        });
+    }
+    Ok(())
+}
+
+/// Whether a program of `kind` reads normals that vertices with(out) normals don't have.
+fn reads_missing_normals(kind: ProgramUnit, has_norms: bool) -> bool {
+    kind == ProgramUnit::Normal && !has_norms
+}
+
+/// The shape of a cone (see [`Object::create_cone`]).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConeShape {
+    /// The center of the cone (halfway up).
+    pub pos: Vector,
+    /// The radius of the base.
+    pub radius: f32,
+    pub height: f32,
+    /// The number of sides.
+    pub segments: u16,
+}
+
+impl ConeShape {
+    /// The scale of the unit cone, which spans [-1, 1] along every axis (just like the cubes).
+    pub fn dim(&self) -> Vector {
+        Vector::new(self.radius, self.height / 2.0, self.radius)
+    }
+}
+
+/// The vertices (position, then normal if `has_norms`) and indices of a unit cone of
+/// `segments` sides, drawn as two triangle fans (the side, then the cap).
+fn cone_mesh(segments: u16, has_norms: bool) -> Result<(Vec<f32>, Vec<u16>)> {
+    if !(3..u16::MAX / 2).contains(&segments) {
+        return Err(format!("Invalid cone segment count: {}", segments).into());
+    }
+
+    let ring = (0..segments)
+        .map(|i| {
+            let theta = i as f32 * TAU / segments as f32;
+            (theta.cos(), theta.sin())
+        })
+        .collect::<Vec<_>>();
+
+    let mut vertices = Vec::with_capacity((segments as usize * 2 + 2) * 6);
+    let mut push = |pos: [f32; 3], norm: Vector| {
+        vertices.extend(pos);
+        if has_norms {
+            vertices.extend(norm.iter())
+        }
+    };
+
+    // apex + side ring [0..=segments]
+    push([0.0, 1.0, 0.0], Y_AXIS);
+    for &(x, z) in &ring {
+        push([x, -1.0, z], Vector::new(2.0 * x, 1.0, 2.0 * z).normalize());
+    }
+
+    // cap center + cap ring [segments + 1..=segments * 2 + 1]
+    push([0.0, -1.0, 0.0], -Y_AXIS);
+    for &(x, z) in &ring {
+        push([x, -1.0, z], -Y_AXIS);
+    }
+
+    // side (clockwise ring from above => counter-clockwise from outside)
+    let side = [0, 1].into_iter().chain((2..=segments).rev()).chain([1]);
+
+    // cap (counter-clockwise ring from below)
+    let center = segments + 1;
+    let cap = [center]
+        .into_iter()
+        .chain(center + 1..=center + segments)
+        .chain([center + 1]);
+
+    let indices = side.chain([u16::MAX]).chain(cap).collect::<Vec<_>>();
+    Ok((vertices, indices))
+}
+
+/// The vertices (position, then normal) and indices of a normal cube spanning [-1, 1].
+///
+/// Explanation: https://stackoverflow.com/a/79337030/13449866
+fn cube_mesh() -> ([f32; 144], [u8; 36]) {
+    let x = -1.0;
+    let y = -1.0;
+    let z = -1.0;
+
+    let xw = 1.0;
+    let yh = 1.0;
+    let zd = 1.0;
+
+    #[rustfmt::skip]
+    let vertices = [
+         // BACK
+         x,   y,   z,  /* [0, 0, 0] */   0.0,  0.0, -1.0,  //  [00]
+         x,  yh,   z,  /* [0, 1, 0] */   0.0,  0.0, -1.0,  //  [01]
+        xw,   y,   z,  /* [1, 0, 0] */   0.0,  0.0, -1.0,  //  [02]
+        xw,  yh,   z,  /* [1, 1, 0] */   0.0,  0.0, -1.0,  //  [03]
+
+         // FRONT
+         x,   y,  zd,  /* [0, 0, 1] */   0.0,  0.0,  1.0,  //  [04]
+         x,  yh,  zd,  /* [0, 1, 1] */   0.0,  0.0,  1.0,  //  [05]
+        xw,   y,  zd,  /* [1, 0, 1] */   0.0,  0.0,  1.0,  //  [06]
+        xw,  yh,  zd,  /* [1, 1, 1] */   0.0,  0.0,  1.0,  //  [07]
+
+         // LEFT
+         x,   y,  zd,  /* [0, 0, 1] */  -1.0,  0.0,  0.0,  //  [08]
+         x,  yh,  zd,  /* [0, 1, 1] */  -1.0,  0.0,  0.0,  //  [09]
+         x,   y,   z,  /* [0, 0, 0] */  -1.0,  0.0,  0.0,  //  [10]
+         x,  yh,   z,  /* [0, 1, 0] */  -1.0,  0.0,  0.0,  //  [11]
+
+         // RIGHT
+         xw,   y,  zd,  /* [1, 0, 1] */  1.0,  0.0,  0.0,  //  [12]
+         xw,  yh,  zd,  /* [1, 1, 1] */  1.0,  0.0,  0.0,  //  [13]
+         xw,   y,   z,  /* [1, 0, 0] */  1.0,  0.0,  0.0,  //  [14]
+         xw,  yh,   z,  /* [1, 1, 0] */  1.0,  0.0,  0.0,  //  [15]
+
+         // TOP
+          x,  yh,   z,  /* [0, 1, 0] */  0.0,  1.0,  0.0,  //  [16]
+          x,  yh,  zd,  /* [0, 1, 1] */  0.0,  1.0,  0.0,  //  [17]
+         xw,  yh,   z,  /* [1, 1, 0] */  0.0,  1.0,  0.0,  //  [18]
+         xw,  yh,  zd,  /* [1, 1, 1] */  0.0,  1.0,  0.0,  //  [19]
+
+         // BOTTOM
+          x,   y,   z,  /* [0, 0, 0] */  0.0, -1.0,  0.0,  //  [20]
+          x,   y,  zd,  /* [0, 0, 1] */  0.0, -1.0,  0.0,  //  [21]
+         xw,   y,   z,  /* [1, 0, 0] */  0.0, -1.0,  0.0,  //  [22]
+         xw,   y,  zd,  /* [1, 0, 1] */  0.0, -1.0,  0.0,  //  [23]
+    ];
+
+    #[rustfmt::skip]
+    let indices = [
+        // FRONT
+         0,  3,  2,    1,  3,  0,
+
+        // BACK
+         6,  7,  4,    4,  7,  5,
+
+        // LEFT
+         8, 11, 10,    9, 11,  8,
+
+        // RIGHT
+        14, 15, 12,   12, 15, 13,
+
+        // TOP
+        16, 19, 18,   17, 19, 16,
+
+        // BOTTOM
+        22, 23, 20,   20, 23, 21,
+    ];
+
+    (vertices, indices)
+}
+
+/// The vertices (position, then normal) and indices of a smooth cube spanning [-1, 1].
+///
+/// Every corner is shared by its three faces, so its normal points away from the center.
+fn smooth_cube_mesh() -> ([f32; 48], [u8; 36]) {
+    let (cube_vertices, cube_indices) = cube_mesh();
+
+    // corners are indexed by the signs of their position (x, y, z as bits)
+    let corner = |v: &[f32]| (0..3).fold(0, |i, axis| i << 1 | (v[axis] > 0.0) as u8);
+
+    let mut vertices = [0.0; 48];
+    for (i, v) in vertices.chunks_exact_mut(6).enumerate() {
+        let p = Vector::from_fn(|axis, _| if i >> (2 - axis) & 1 == 1 { 1.0 } else { -1.0 });
+        let n = p.normalize();
+        v.copy_from_slice(&[p.x, p.y, p.z, n.x, n.y, n.z]);
+    }
+
+    let corners = cube_vertices
+        .chunks_exact(6)
+        .map(corner)
+        .collect::<Vec<_>>();
+    let indices = cube_indices.map(|index| corners[index as usize]);
+
+    (vertices, indices)
+}
+
+/// How the normals of a cube are laid out (see [`Object::rebuild_normals`]).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Shading {
+    /// A normal per face (24 vertices), so edges are hard.
+    #[default]
+    Flat,
+    /// A normal per corner (8 vertices), so edges are blended.
+    Smooth,
+}
+
+impl Shading {
+    pub const fn geometry(&self) -> Geometry {
+        match self {
+            Self::Flat => Geometry::Cube,
+            Self::Smooth => Geometry::SmoothCube,
+        }
+    }
+
+    /// The vertices (position, then normal) and triangle indices of a cube shaded this way.
+    pub fn cube_mesh(&self) -> (Vec<f32>, Vec<u8>) {
+        match self {
+            Self::Flat => {
+                let (vertices, indices) = cube_mesh();
+                (vertices.to_vec(), indices.to_vec())
+            }
+            Self::Smooth => {
+                let (vertices, indices) = smooth_cube_mesh();
+                (vertices.to_vec(), indices.to_vec())
+            }
+        }
+    }
+}
+
+/// How [`Object::update_vertices`] uploads into an existing vertex buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UpdateStrategy {
+    /// Write into the current storage. Cheapest for occasional updates, but the driver
+    /// may stall until the GPU is done reading the previous contents.
+    #[default]
+    SubData,
+    /// Re-specify (orphan) the storage at the same size before writing, letting the
+    /// driver hand out fresh memory while the GPU keeps drawing from the old one. Suits
+    /// meshes rewritten every frame, at the cost of a reallocation per update.
+    Orphan,
+}
+
+/// Merge a normal cube per `model` into a single world-space mesh (position, then normal).
+///
+/// Indices are offset per cube, and mirrored models have their winding reversed.
+pub fn bake_cubes<'a>(models: impl IntoIterator<Item = &'a Matrix>) -> (Vec<f32>, Vec<u32>) {
+    let (cube_vertices, cube_indices) = cube_mesh();
+    let per_cube = (cube_vertices.len() / 6) as u32;
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for (i, model) in models.into_iter().enumerate() {
+        let linear = model.fixed_view::<3, 3>(0, 0).into_owned();
+        let normal_matrix = normal_matrix(model);
+
+        for v in cube_vertices.chunks_exact(6) {
+            let p = model.transform_point(&Point::new(v[0], v[1], v[2]));
+            let n = normal_matrix * Vector::new(v[3], v[4], v[5]);
+            let n = n.try_normalize(f32::EPSILON).unwrap_or(n);
+            vertices.extend([p.x, p.y, p.z, n.x, n.y, n.z]);
+        }
+
+        let offset = i as u32 * per_cube;
+        let flipped = linear.determinant() < 0.0;
+
+        for tri in cube_indices.chunks_exact(3) {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|index| index as u32 + offset);
+            indices.extend(if flipped { [a, c, b] } else { [a, b, c] });
+        }
+    }
+    (vertices, indices)
+}
+
+/// The bits of everything an object is drawn with besides its geometry (its uniforms).
+fn appearance(data: &ObjectData) -> Vec<u32> {
+    let (color, material) = (data.raw_color(), data.material());
+
+    [
+        &color[..],
+        &[material.shininess()],
+        material.specular(),
+        &data.emissive()[..],
+        &data.tint()[..],
+    ]
+    .concat()
+    .into_iter()
+    .map(f32::to_bits)
+    .chain([
+        color.emits() as u32,
+        data.palette_index().map_or(u32::MAX, u32::from),
+    ])
+    .collect()
+}
+
+/// Split `objects` into batches of identical appearance (color, material, ...), which can
+/// each be baked into a single object (see [`RawObjects::bake_static`]).
+///
+/// Batches are ordered by their first object, and keep the order of `objects`.
+pub fn batch_by_appearance<'a>(objects: impl IntoIterator<Item = &'a ObjectData>) -> Vec<Vec<Id>> {
+    let mut batches: Vec<(Vec<u32>, Vec<Id>)> = Vec::new();
+
+    for data in objects {
+        let key = appearance(data);
+
+        match batches.iter_mut().find(|(other, _)| *other == key) {
+            Some((_, ids)) => ids.push(data.id()),
+            None => batches.push((key, vec![data.id()])),
+        }
+    }
+    batches.into_iter().map(|(_, ids)| ids).collect()
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -36,30 +515,26 @@ pub struct Object {
     program: Program,
     buffers: Buffers,
     data: ObjectData,
-    mode: u32,
-    element_type: u32,
-    len: i32,
+    elements: Elements,
+    geometry: Geometry,
 }
 
 impl Object {
-    const fn new(
-        program: Program,
-        buffers: Buffers,
-        mode: u32,
-        element_type: u32,
-        len: i32,
-        data: ObjectData,
-    ) -> Self {
+    const fn new(program: Program, buffers: Buffers, elements: Elements, data: ObjectData) -> Self {
         Self {
             program,
             buffers,
             data,
-            mode,
-            element_type,
-            len,
+            elements,
+            geometry: Geometry::Custom,
         }
     }
 
+    const fn with_geometry(mut self, geometry: Geometry) -> Self {
+        self.geometry = geometry;
+        self
+    }
+
     /// Construct a simple cube (8 vertices; 14 indices).
     ///
     /// Explanation: https://stackoverflow.com/a/79336923/13449866
@@ -120,6 +595,7 @@ impl Object {
             data,
             false,
         )
+        .map(|obj| obj.with_geometry(Geometry::FlatCube))
     }
 
     /// Construct a normal cube (24 vertices; 36 indices).
@@ -140,93 +616,173 @@ impl Object {
         };
         let data = ObjectData::new(id, color, raw_data);
 
+        Self::create_with(gl, program, data)
+    }
+
+    /// Construct a cube with specified [`ObjectData`], using the geometry the program expects.
+    pub fn create_with(gl: &Context, program: Program, data: ObjectData) -> Result<Self> {
         match program.kind() {
             ProgramUnit::Simple => Self::create_flat_cube_with(gl, program, data),
             ProgramUnit::Normal => Self::create_cube_with(gl, program, data),
         }
     }
 
+    /// Construct an object of built-in `geometry` with specified [`ObjectData`].
+    pub fn create_geometry_with(
+        gl: &Context,
+        program: Program,
+        geometry: Geometry,
+        data: ObjectData,
+    ) -> Result<Self> {
+        match geometry {
+            Geometry::FlatCube => Self::create_flat_cube_with(gl, program, data),
+            Geometry::Cube => Self::create_cube_with(gl, program, data),
+            Geometry::SmoothCube => Self::create_smooth_cube_with(gl, program, data),
+            Geometry::Cone { segments } => Self::create_cone_with(gl, program, segments, data),
+            Geometry::Custom => Err("Custom geometry can't be rebuilt".into()),
+        }
+    }
+
     /// Construct a normal cube (24 vertices; 36 indices) with specified [`ObjectData`].
     ///
     /// Explanation: https://stackoverflow.com/a/79337030/13449866
     pub fn create_cube_with(gl: &Context, program: Program, data: ObjectData) -> Result<Self> {
-        let x = -1.0;
-        let y = -1.0;
-        let z = -1.0;
+        let (vertices, indices) = cube_mesh();
 
-        let xw = 1.0;
-        let yh = 1.0;
-        let zd = 1.0;
-
-        #[rustfmt::skip]
-        let vertices = [
-             // BACK
-             x,   y,   z,  /* [0, 0, 0] */   0.0,  0.0, -1.0,  //  [00]
-             x,  yh,   z,  /* [0, 1, 0] */   0.0,  0.0, -1.0,  //  [01]
-            xw,   y,   z,  /* [1, 0, 0] */   0.0,  0.0, -1.0,  //  [02]
-            xw,  yh,   z,  /* [1, 1, 0] */   0.0,  0.0, -1.0,  //  [03]
-
-             // FRONT
-             x,   y,  zd,  /* [0, 0, 1] */   0.0,  0.0,  1.0,  //  [04]
-             x,  yh,  zd,  /* [0, 1, 1] */   0.0,  0.0,  1.0,  //  [05]
-            xw,   y,  zd,  /* [1, 0, 1] */   0.0,  0.0,  1.0,  //  [06]
-            xw,  yh,  zd,  /* [1, 1, 1] */   0.0,  0.0,  1.0,  //  [07]
-
-             // LEFT
-             x,   y,  zd,  /* [0, 0, 1] */  -1.0,  0.0,  0.0,  //  [08]
-             x,  yh,  zd,  /* [0, 1, 1] */  -1.0,  0.0,  0.0,  //  [09]
-             x,   y,   z,  /* [0, 0, 0] */  -1.0,  0.0,  0.0,  //  [10]
-             x,  yh,   z,  /* [0, 1, 0] */  -1.0,  0.0,  0.0,  //  [11]
-
-             // RIGHT
-             xw,   y,  zd,  /* [1, 0, 1] */  1.0,  0.0,  0.0,  //  [12]
-             xw,  yh,  zd,  /* [1, 1, 1] */  1.0,  0.0,  0.0,  //  [13]
-             xw,   y,   z,  /* [1, 0, 0] */  1.0,  0.0,  0.0,  //  [14]
-             xw,  yh,   z,  /* [1, 1, 0] */  1.0,  0.0,  0.0,  //  [15]
-
-             // TOP
-              x,  yh,   z,  /* [0, 1, 0] */  0.0,  1.0,  0.0,  //  [16]
-              x,  yh,  zd,  /* [0, 1, 1] */  0.0,  1.0,  0.0,  //  [17]
-             xw,  yh,   z,  /* [1, 1, 0] */  0.0,  1.0,  0.0,  //  [18]
-             xw,  yh,  zd,  /* [1, 1, 1] */  0.0,  1.0,  0.0,  //  [19]
-
-             // BOTTOM
-              x,   y,   z,  /* [0, 0, 0] */  0.0, -1.0,  0.0,  //  [20]
-              x,   y,  zd,  /* [0, 0, 1] */  0.0, -1.0,  0.0,  //  [21]
-             xw,   y,   z,  /* [1, 0, 0] */  0.0, -1.0,  0.0,  //  [22]
-             xw,   y,  zd,  /* [1, 0, 1] */  0.0, -1.0,  0.0,  //  [23]
-        ];
-
-        #[rustfmt::skip]
-        let indices = [
-            // FRONT
-             0,  3,  2,    1,  3,  0,
+        Self::from_raw::<f32, u8>(
+            gl,
+            program,
+            vertices.as_slice(),
+            indices.as_slice(),
+            TRIANGLES,
+            UNSIGNED_BYTE,
+            data,
+            true,
+        )
+        .map(|obj| obj.with_geometry(Geometry::Cube))
+    }
 
-            // BACK
-             6,  7,  4,    4,  7,  5,
+    /// Construct a smooth cube (8 vertices; 36 indices) with specified [`ObjectData`].
+    pub fn create_smooth_cube_with(
+        gl: &Context,
+        program: Program,
+        data: ObjectData,
+    ) -> Result<Self> {
+        let (vertices, indices) = smooth_cube_mesh();
 
-            // LEFT
-             8, 11, 10,    9, 11,  8,
+        Self::from_raw::<f32, u8>(
+            gl,
+            program,
+            vertices.as_slice(),
+            indices.as_slice(),
+            TRIANGLES,
+            UNSIGNED_BYTE,
+            data,
+            true,
+        )
+        .map(|obj| obj.with_geometry(Geometry::SmoothCube))
+    }
 
-            // RIGHT
-            14, 15, 12,   12, 15, 13,
+    /// Construct a cone (pointing up) of `shape`.
+    ///
+    /// The side and the cap are both triangle fans, separated by a primitive restart.
+    pub fn create_cone(
+        gl: &Context,
+        program: Program,
+        shape: ConeShape,
+        color: Color,
+        id: Id,
+        kind: RawObjectDataUnit,
+    ) -> Result<Self> {
+        let raw_data = match kind {
+            RawObjectDataUnit::Player => RawObjectData::Player(PlayerData::new(shape.pos)),
+            RawObjectDataUnit::Basic => {
+                RawObjectData::Basic(BasicData::new(shape.pos, shape.dim()))
+            }
+        };
+        let data = ObjectData::new(id, color, raw_data);
 
-            // TOP
-            16, 19, 18,   17, 19, 16,
+        Self::create_cone_with(gl, program, shape.segments, data)
+    }
 
-            // BOTTOM
-            22, 23, 20,   20, 23, 21,
-        ];
+    /// Construct a cone (`segments` sides; pointing up) with specified [`ObjectData`].
+    pub fn create_cone_with(
+        gl: &Context,
+        program: Program,
+        segments: u16,
+        data: ObjectData,
+    ) -> Result<Self> {
+        let has_norms = program.kind() == ProgramUnit::Normal;
+        let (vertices, indices) = cone_mesh(segments, has_norms)?;
 
-        Self::from_raw::<f32, u8>(
+        Self::from_raw::<f32, u16>(
             gl,
             program,
             vertices.as_slice(),
             indices.as_slice(),
+            TRIANGLE_FAN,
+            UNSIGNED_SHORT,
+            data,
+            has_norms,
+        )
+        .map(|obj| obj.with_geometry(Geometry::Cone { segments }))
+    }
+
+    /// Load a Wavefront OBJ file (see [`parse_obj`]), converting it as of `options`.
+    pub fn import_obj(
+        gl: &Context,
+        program: Program,
+        path: impl AsRef<Path>,
+        options: impl Into<ImportOptions>,
+        data: ObjectData,
+    ) -> Result<Self> {
+        let options = options.into();
+        let mut mesh = parse_obj(&read_to_string(path)?, options.system)?;
+
+        if let Some(epsilon) = options.weld() {
+            mesh.weld(epsilon)
+        }
+        Self::from_mesh(gl, program, &mesh, data)
+    }
+
+    /// Rebuild the buffers of this object (for `program`) from its geometry, keeping its data.
+    ///
+    /// Custom geometry is rebuilt from `mesh`, returning `None` without it.
+    fn recreate(
+        &self,
+        gl: &Context,
+        program: Program,
+        mesh: Option<&Mesh>,
+    ) -> Option<Result<Self>> {
+        let data = self.data;
+
+        let obj = match self.geometry {
+            Geometry::Custom => Self::from_mesh(gl, program, mesh?, data),
+            geometry => Self::create_geometry_with(gl, program, geometry, data),
+        };
+
+        // exactly as it was (the constructors refresh the model matrix)
+        Some(obj.map(|obj| Self { data, ..obj }))
+    }
+
+    /// Construct an object from triangulated geometry (normals are only kept if the program uses them).
+    pub fn from_mesh(
+        gl: &Context,
+        program: Program,
+        mesh: &Mesh,
+        data: ObjectData,
+    ) -> Result<Self> {
+        let has_norms = program.kind() == ProgramUnit::Normal;
+
+        Self::from_raw::<f32, u32>(
+            gl,
+            program,
+            &mesh.vertices(has_norms),
+            &mesh.indices,
             TRIANGLES,
-            UNSIGNED_BYTE,
+            UNSIGNED_INT,
             data,
-            true,
+            has_norms,
         )
     }
 
@@ -240,51 +796,53 @@ impl Object {
         mut data: ObjectData,
         has_norms: bool,
     ) -> Result<Self> {
+        check_attributes(program.kind(), has_norms)?;
+
         unsafe {
             // creates and bind Vertex Array Object (VAO)
             let vao = gl.create_vertex_array()?;
-            let vbo = gl.create_buffer()?;
-            let ebo = gl.create_buffer()?;
-
-            let mut stride = 3;
-
-            if has_norms {
-                stride += 3
-            }
+            let vbo = gl
+                .create_buffer()
+                .inspect_err(|_| gl.delete_vertex_array(vao))?;
+            let ebo = gl.create_buffer().inspect_err(|_| {
+                gl.delete_vertex_array(vao);
+                gl.delete_buffer(vbo)
+            })?;
+
+            let buf = Buffers {
+                vao,
+                vbo,
+                ebo,
+                shared: false,
+            };
 
             gl.bind_vertex_array(Some(vao));
 
-            // create and bind Vertex Buffer Object (VBO)
-            gl.bind_buffer(ARRAY_BUFFER, Some(vbo));
-            gl.buffer_data_u8_slice(ARRAY_BUFFER, cast_slice(vertices), STATIC_DRAW);
-
-            // create and bind Elements Buffer Object (EBO)
-            gl.bind_buffer(ELEMENT_ARRAY_BUFFER, Some(ebo));
-            gl.buffer_data_u8_slice(ELEMENT_ARRAY_BUFFER, cast_slice(indices), STATIC_DRAW);
+            let uploaded = (|| {
+                // create and bind Vertex Buffer Object (VBO)
+                gl.bind_buffer(ARRAY_BUFFER, Some(vbo));
+                gl.buffer_data_u8_slice(ARRAY_BUFFER, cast_slice(vertices), STATIC_DRAW);
 
-            // enable `pos` attribute
-            gl.enable_vertex_attrib_array(0);
-            gl.vertex_attrib_pointer_f32(0, 3, FLOAT, false, stride * size_of::<f32>() as i32, 0);
+                // create and bind Elements Buffer Object (EBO)
+                gl.bind_buffer(ELEMENT_ARRAY_BUFFER, Some(ebo));
+                gl.buffer_data_u8_slice(ELEMENT_ARRAY_BUFFER, cast_slice(indices), STATIC_DRAW);
+                gl_check(gl, "from_raw: buffer upload")?;
 
-            if has_norms {
-                // enable `norm` attribute
-                gl.enable_vertex_attrib_array(1);
-                gl.vertex_attrib_pointer_f32(
-                    1,
-                    3,
-                    FLOAT,
-                    false,
-                    stride * size_of::<f32>() as i32,
-                    3 * size_of::<f32>() as i32,
-                );
-            }
+                Self::attrib_pointers(gl, program, has_norms);
+                gl_check(gl, "from_raw: vertex attributes")
+            })();
 
             // unbind buffers
             gl.bind_vertex_array(None);
             gl.bind_buffer(ARRAY_BUFFER, None);
             gl.bind_buffer(ELEMENT_ARRAY_BUFFER, None);
 
-            let buf = Buffers { vao, vbo, ebo };
+            // nothing is left behind on failure
+            if let Err(e) = uploaded {
+                free_buffers(gl, buf);
+                return Err(e);
+            }
+            label_buffers(gl, buf, || format!("{:?} {}", data.kind(), data.id()));
 
             // initial transformation update
             data.model_upt();
@@ -292,35 +850,169 @@ impl Object {
             Ok(Self::new(
                 program,
                 buf,
-                mode,
-                element_type,
-                indices.len() as i32,
+                Elements::new(mode, element_type, indices, has_norms),
                 data,
             ))
         }
     }
 
-    pub const fn program(&self) -> Program {
-        self.program
-    }
+    /// Construct an object from lazily generated vertices, writing them straight into the
+    /// (mapped) vertex buffer instead of collecting them first.
+    pub fn from_vertex_iter<I: NoUninit>(
+        gl: &Context,
+        program: Program,
+        vertices: impl ExactSizeIterator<Item = f32>,
+        indices: &[I],
+        (mode, element_type): (u32, u32),
+        data: ObjectData,
+        has_norms: bool,
+    ) -> Result<Self> {
+        let obj = Self::from_raw::<f32, I>(
+            gl,
+            program,
+            &[],
+            indices,
+            mode,
+            element_type,
+            data,
+            has_norms,
+        )?;
 
-    pub const fn buffers(&self) -> Buffers {
-        self.buffers
-    }
+        let len = vertices.len();
+        let size = (len * size_of::<f32>()) as i32;
 
-    pub const fn data(&self) -> &ObjectData {
-        &self.data
-    }
+        unsafe {
+            // (re)allocate the storage the VAO already points at
+            gl.bind_buffer(ARRAY_BUFFER, Some(obj.vbo()));
+            gl.buffer_data_size(ARRAY_BUFFER, size, STATIC_DRAW);
 
-    pub fn data_mut(&mut self) -> &mut ObjectData {
-        &mut self.data
-    }
+            let written = if len == 0 {
+                0
+            } else {
+                let ptr = gl.map_buffer_range(
+                    ARRAY_BUFFER,
+                    0,
+                    size,
+                    MAP_WRITE_BIT | MAP_INVALIDATE_BUFFER_BIT,
+                ) as *mut f32;
+
+                if ptr.is_null() {
+                    0
+                } else {
+                    let mut written = 0;
+                    for (i, v) in vertices.take(len).enumerate() {
+                        ptr.add(i).write_unaligned(v);
+                        written += 1;
+                    }
+                    gl.unmap_buffer(ARRAY_BUFFER);
+                    written
+                }
+            };
+            gl.bind_buffer(ARRAY_BUFFER, None);
 
-    pub const fn vao(&self) -> NativeVertexArray {
-        self.buffers.vao()
+            if written != len {
+                free_buffers(gl, obj.buffers());
+                return Err(format!("Mapped {} of {} vertex components", written, len).into());
+            }
+        }
+
+        if let Err(e) = gl_check(gl, "from_vertex_iter: buffer upload") {
+            free_buffers(gl, obj.buffers());
+            return Err(e);
+        }
+        Ok(obj)
     }
 
-    pub const fn vbo(&self) -> NativeBuffer {
+    /// Construct an object with its own VAO over the buffers of `mesh`.
+    fn from_shared(
+        gl: &Context,
+        program: Program,
+        mesh: &SharedMesh,
+        mut data: ObjectData,
+    ) -> Result<Self> {
+        unsafe {
+            let vao = gl.create_vertex_array()?;
+            gl.bind_vertex_array(Some(vao));
+
+            gl.bind_buffer(ARRAY_BUFFER, Some(mesh.vbo));
+            gl.bind_buffer(ELEMENT_ARRAY_BUFFER, Some(mesh.ebo));
+
+            Self::attrib_pointers(gl, program, program.kind() == ProgramUnit::Normal);
+            gl_check(gl, "from_shared: vertex attributes")?;
+
+            // unbind buffers
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(ARRAY_BUFFER, None);
+            gl.bind_buffer(ELEMENT_ARRAY_BUFFER, None);
+
+            let buf = Buffers {
+                vao,
+                vbo: mesh.vbo,
+                ebo: mesh.ebo,
+                shared: true,
+            };
+            gl_label(gl, VERTEX_ARRAY, vao.0.get(), || {
+                format!("{:?} {} VAO", data.kind(), data.id())
+            });
+
+            // initial transformation update
+            data.model_upt();
+
+            Ok(Self::new(program, buf, mesh.elements, data).with_geometry(mesh.geometry))
+        }
+    }
+
+    /// Describe the vertex layout (`pos`, then `norm` if present) of the bound VAO.
+    ///
+    /// Attributes are looked up by name, skipping any the program doesn't consume
+    /// (e.g., stripped by the shader compiler).
+    unsafe fn attrib_pointers(gl: &Context, program: Program, has_norms: bool) {
+        let native = program.native();
+        let layout = VertexLayout::new(has_norms);
+
+        for attrib in layout.attributes {
+            let Some(location) = gl.get_attrib_location(native, attrib.name) else {
+                warn!(
+                    "[{:?}] Attribute `{}` is missing",
+                    program.kind(),
+                    attrib.name
+                );
+                continue;
+            };
+
+            gl.enable_vertex_attrib_array(location);
+            gl.vertex_attrib_pointer_f32(
+                location,
+                attrib.size,
+                FLOAT,
+                false,
+                layout.stride,
+                attrib.offset,
+            );
+        }
+    }
+
+    pub const fn program(&self) -> Program {
+        self.program
+    }
+
+    pub const fn buffers(&self) -> Buffers {
+        self.buffers
+    }
+
+    pub const fn data(&self) -> &ObjectData {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut ObjectData {
+        &mut self.data
+    }
+
+    pub const fn vao(&self) -> NativeVertexArray {
+        self.buffers.vao()
+    }
+
+    pub const fn vbo(&self) -> NativeBuffer {
         self.buffers.vbo()
     }
 
@@ -337,15 +1029,208 @@ impl Object {
     }
 
     pub const fn mode(&self) -> u32 {
-        self.mode
+        self.elements.mode
     }
 
     pub const fn element_type(&self) -> u32 {
-        self.element_type
+        self.elements.element_type
     }
 
     pub const fn len(&self) -> i32 {
-        self.len
+        self.elements.len
+    }
+
+    pub const fn triangles(&self) -> usize {
+        self.elements.triangles
+    }
+
+    pub const fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    pub const fn has_norms(&self) -> bool {
+        self.elements.has_norms
+    }
+
+    /// Whether the vertices provide every attribute `program` consumes.
+    pub fn supports(&self, program: Program) -> Result {
+        if program.kind() == ProgramUnit::Normal && !self.has_norms() {
+            return Err(Error::AttributeMismatch {
+                program: program.kind(),
+                has_norms: self.has_norms(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Draw this object with `program` from now on, pointing its VAO at the attributes
+    /// of `program` (the buffers are kept).
+    pub fn set_program(&mut self, gl: &Context, program: Program) -> Result {
+        self.supports(program)?;
+
+        let layout = self.describe_layout();
+        let (old, new) = (self.program.native(), program.native());
+
+        unsafe {
+            gl.bind_vertex_array(Some(self.vao()));
+
+            for attrib in &layout.attributes {
+                if let Some(location) = gl.get_attrib_location(old, attrib.name) {
+                    gl.disable_vertex_attrib_array(location)
+                }
+            }
+
+            gl.bind_buffer(ARRAY_BUFFER, Some(self.vbo()));
+
+            // attributes the program doesn't consume (e.g., normals when unlit) are skipped
+            for attrib in &layout.attributes {
+                let Some(location) = gl.get_attrib_location(new, attrib.name) else {
+                    continue;
+                };
+
+                gl.enable_vertex_attrib_array(location);
+                gl.vertex_attrib_pointer_f32(
+                    location,
+                    attrib.size,
+                    FLOAT,
+                    false,
+                    layout.stride,
+                    attrib.offset,
+                );
+            }
+
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(ARRAY_BUFFER, None);
+        }
+
+        self.program = program;
+        gl_check(gl, "set_program: vertex attributes")
+    }
+
+    /// The layout the vertex buffer was created with.
+    pub fn describe_layout(&self) -> VertexLayout {
+        VertexLayout::new(self.has_norms())
+    }
+
+    /// Switch a cube between flat and smooth normals, keeping everything else.
+    ///
+    /// The vertices are rewritten in place if the layout stays the same, otherwise the
+    /// buffers are reallocated (e.g., 24 -> 8 vertices). Shared meshes must be detached
+    /// first (see [`RawObjects::rebuild_normals`]).
+    pub fn rebuild_normals(&mut self, gl: &Context, shading: Shading) -> Result {
+        if !matches!(
+            self.geometry(),
+            Geometry::FlatCube | Geometry::Cube | Geometry::SmoothCube
+        ) {
+            return Err("Only cubes can be reshaded".into());
+        }
+        if self.buffers().is_shared() {
+            return Err("Shared meshes can't be reshaded in place".into());
+        }
+
+        let (vertices, indices) = shading.cube_mesh();
+
+        if self.geometry() == shading.geometry() {
+            return self.update_vertices(gl, &vertices, UpdateStrategy::SubData);
+        }
+
+        let obj = Self::from_raw::<f32, u8>(
+            gl,
+            self.program(),
+            &vertices,
+            &indices,
+            TRIANGLES,
+            UNSIGNED_BYTE,
+            self.data,
+            true,
+        )?;
+        free_buffers(gl, self.buffers());
+
+        self.buffers = obj.buffers;
+        self.elements = obj.elements;
+        self.geometry = shading.geometry();
+        Ok(())
+    }
+
+    /// Overwrite the start of the vertex buffer with `vertices` (same layout), keeping its size.
+    ///
+    /// Shared meshes can't be updated in place, and `vertices` must fit the buffer.
+    pub fn update_vertices<V: NoUninit>(
+        &self,
+        gl: &Context,
+        vertices: &[V],
+        strategy: UpdateStrategy,
+    ) -> Result {
+        if self.buffers().is_shared() {
+            return Err("Shared meshes can't be updated in place".into());
+        }
+        let bytes = cast_slice::<V, u8>(vertices);
+
+        unsafe {
+            gl.bind_buffer(ARRAY_BUFFER, Some(self.vbo()));
+            let size = gl.get_buffer_parameter_i32(ARRAY_BUFFER, BUFFER_SIZE);
+
+            if bytes.len() > size as usize {
+                gl.bind_buffer(ARRAY_BUFFER, None);
+                return Err(format!(
+                    "{} bytes of vertices exceed the buffer ({} bytes)",
+                    bytes.len(),
+                    size
+                )
+                .into());
+            }
+
+            if strategy == UpdateStrategy::Orphan {
+                gl.buffer_data_size(ARRAY_BUFFER, size, STREAM_DRAW);
+            }
+            gl.buffer_sub_data_u8_slice(ARRAY_BUFFER, 0, bytes);
+            gl.bind_buffer(ARRAY_BUFFER, None);
+        }
+        gl_check(gl, "update_vertices: buffer upload")
+    }
+
+    /// Whether the program of this object reads normals that the mesh doesn't have
+    /// (e.g., a flat cube paired with the 'normal' program), which renders it black.
+    pub fn lacks_normals(&self) -> bool {
+        reads_missing_normals(self.program().kind(), self.has_norms())
+    }
+
+    /// return every inconsistency between the program, buffers, and data of this object.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.len() <= 0 {
+            problems.push("Empty index buffer".to_string());
+        }
+
+        match element_type_size(self.element_type()) {
+            Some(size) if size != self.elements.index_size => problems.push(format!(
+                "Element type ({:#x}) expects {}-byte indices, but the index buffer has {}-byte indices",
+                self.element_type(),
+                size,
+                self.elements.index_size
+            )),
+            None => problems.push(format!("Invalid element type ({:#x})", self.element_type())),
+            _ => (),
+        }
+
+        if self.triangles() == 0 {
+            problems.push(format!("Mode ({:#x}) draws no triangles", self.mode()));
+        }
+
+        if self.lacks_normals() {
+            problems.push("The normal program expects normals, but the mesh has none".to_string());
+        }
+
+        if !self
+            .pos()
+            .iter()
+            .chain(self.dim().iter())
+            .all(|v| v.is_finite())
+        {
+            problems.push("Non-finite position or dimensions".to_string());
+        }
+        problems
     }
 }
 
@@ -363,90 +1248,2732 @@ impl DerefMut for Object {
     }
 }
 
-#[derive(Clone, Debug, Default)]
-pub struct RawObjects {
-    opaque: HashMap<Id, Object>,
+/// The buffers of a mesh, referenced by multiple objects.
+#[derive(Clone, Copy, Debug)]
+struct SharedMesh {
+    vbo: NativeBuffer,
+    ebo: NativeBuffer,
+    elements: Elements,
+    geometry: Geometry,
+    refs: usize,
 }
 
-impl RawObjects {
-    /// create and add a new cube with specified attributes.
-    pub fn new_cube(
+/// The key of a file in a [`MeshCache`] (canonical if possible, so aliases share a mesh).
+fn cache_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Where the vertices of a [`SharedMesh`] come from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum MeshSource {
+    Builtin(Geometry),
+    File(PathBuf, ImportOptions),
+}
+
+/// Reference-counted VBOs and EBOs of built-in meshes and imported files.
+///
+/// Lives inside [`RawObjects`], so it's shared between threads by the same lock.
+#[derive(Debug, Default)]
+pub struct MeshCache {
+    // by EBO
+    meshes: HashMap<NativeBuffer, SharedMesh>,
+    lookup: HashMap<(MeshSource, bool), NativeBuffer>,
+}
+
+impl MeshCache {
+    /// reuse the buffers of `source` (for `program`), only calling `create` for the first object.
+    fn create(
         &mut self,
         gl: &Context,
-        id: Id,
         program: Program,
-        pos: Vector,
-        dim: Vector,
-        color: Color,
-        kind: RawObjectDataUnit,
-    ) -> Result {
-        let raw_data = match kind {
-            RawObjectDataUnit::Player => RawObjectData::Player(PlayerData::new(pos)),
-            RawObjectDataUnit::Basic => RawObjectData::Basic(BasicData::new(pos, dim)),
+        data: ObjectData,
+        source: MeshSource,
+        create: impl FnOnce(&Context, Program, ObjectData) -> Result<Object>,
+    ) -> Result<Object> {
+        let key = (source, program.kind() == ProgramUnit::Normal);
+
+        if let Some(mesh) = self
+            .lookup
+            .get(&key)
+            .and_then(|ebo| self.meshes.get_mut(ebo))
+        {
+            let obj = Object::from_shared(gl, program, mesh, data)?;
+            mesh.refs += 1;
+            return Ok(obj);
+        }
+
+        let mut obj = create(gl, program, data)?;
+        obj.buffers.shared = true;
+
+        // the buffers outlive the object which created them
+        gl_label(gl, BUFFER, obj.vbo().0.get(), || format!("{:?} VBO", key.0));
+        gl_label(gl, BUFFER, obj.ebo().0.get(), || format!("{:?} EBO", key.0));
+
+        let mesh = SharedMesh {
+            vbo: obj.vbo(),
+            ebo: obj.ebo(),
+            elements: obj.elements,
+            geometry: obj.geometry(),
+            refs: 1,
         };
-        let data = ObjectData::new(id, color, raw_data);
+        self.meshes.insert(mesh.ebo, mesh);
+        self.lookup.insert(key, mesh.ebo);
+        Ok(obj)
+    }
 
-        self.new_cube_with(gl, program, data)
+    /// free the buffers of `obj`, deleting shared buffers once the last reference is gone.
+    fn free(&mut self, gl: &Context, obj: &Object) {
+        free_buffers(gl, obj.buffers());
+
+        if !obj.buffers().is_shared() {
+            return;
+        }
+
+        let Some(mesh) = self.meshes.get_mut(&obj.ebo()) else {
+            return;
+        };
+
+        mesh.refs -= 1;
+
+        if mesh.refs == 0 {
+            unsafe {
+                gl.delete_buffer(mesh.vbo);
+                gl.delete_buffer(mesh.ebo);
+            }
+            let ebo = mesh.ebo;
+            self.meshes.remove(&ebo);
+            self.lookup.retain(|_, v| *v != ebo);
+        }
     }
 
-    /// create and add a new cube with specified [`ObjectData`].
-    pub fn new_cube_with(&mut self, gl: &Context, program: Program, data: ObjectData) -> Result {
-        let obj = Object::create_cube_with(gl, program, data)?;
-        self.opaque.insert(data.id(), obj);
-        Ok(())
+    /// return the number of objects referencing the mesh of `ebo`.
+    pub fn refs(&self, ebo: NativeBuffer) -> usize {
+        self.meshes.get(&ebo).map_or(0, |mesh| mesh.refs)
     }
 
-    /// return a mutable reference of the specified object.
-    pub fn get_mut(&mut self, id: Id) -> Option<&mut ObjectData> {
-        self.opaque.get_mut(&id).map(Object::data_mut)
+    /// whether the file at `path` has been imported by a (still existing) object.
+    pub fn is_cached(&self, path: impl AsRef<Path>) -> bool {
+        let path = cache_path(path.as_ref());
+        self.lookup
+            .keys()
+            .any(|(source, _)| matches!(source, MeshSource::File(p, _) if *p == path))
     }
 
-    /// insert a new object.
-    pub fn insert(&mut self, obj: Object) {
-        self.opaque.insert(obj.id(), obj);
+    /// take over every mesh of `other`, preferring the existing meshes for reuse.
+    fn absorb(&mut self, other: MeshCache) {
+        self.meshes.extend(other.meshes);
+        for (key, ebo) in other.lookup {
+            self.lookup.entry(key).or_insert(ebo);
+        }
     }
+}
 
-    /// create and add a new light (simple shading with color as light color) object.
-    pub fn new_light(
-        &mut self,
-        gl: &Context,
-        id: Id,
-        program: Program,
-        pos: Vector,
-        dim: Vector,
-        color: Color,
-    ) -> Result {
-        let obj =
-            Object::create_flat_cube(gl, program, pos, dim, color, id, RawObjectDataUnit::Basic)?;
-        self.opaque.insert(id, obj);
-        Ok(())
+/// Where a ray hit an object.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PickHit {
+    pub id: Id,
+    pub face: CubeFace,
+
+    /// The hit point in the space of the unit geometry ([-1, 1] along every axis).
+    pub local_point: Vector,
+
+    /// The distance along the ray (in units of `ray_dir`).
+    pub distance: f32,
+}
+
+/// Scale `offset` (from the center of an object of `dim`) into the space of the unit geometry.
+///
+/// Flat axes (zero extent) map to 0 instead of dividing by zero.
+fn to_unit_space(offset: &Vector, dim: &Vector) -> Vector {
+    offset.zip_map(
+        &dim.abs(),
+        |o, d| if d > f32::EPSILON { o / d } else { 0.0 },
+    )
+}
+
+/// How an object leaves the scene (see [`RawObjects::despawn`]).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DespawnEffect {
+    /// Removed immediately.
+    #[default]
+    None,
+
+    /// Replaced by `fragments` projectiles flying outwards at `speed` for `lifetime` seconds.
+    Explode {
+        fragments: u8,
+        speed: f32,
+        lifetime: f32,
+    },
+
+    /// Faded out (alpha) over `duration` seconds, then removed.
+    Fade { duration: f32 },
+}
+
+/// Pre-built meshes swapped in for an object once it's far enough from the camera.
+///
+/// Each level replaces only the geometry; the object's program, transform, and
+/// appearance are kept, so every mesh needs the vertex layout of that program.
+#[derive(Clone, Debug, Default)]
+pub struct LodMeshes {
+    levels: Vec<(f32, Object)>,
+    /// The level last selected (see [`LodMeshes::select`]).
+    current: Cell<Option<usize>>,
+}
+
+impl LodMeshes {
+    /// How much closer (as a fraction of its threshold) than a level the camera must come
+    /// to leave it for a finer one, so objects straddling a threshold don't flicker.
+    pub const HYSTERESIS: f32 = 0.1;
+
+    /// Map each distance threshold to the mesh used from that distance onwards.
+    pub fn new(mut levels: Vec<(f32, Object)>) -> Self {
+        levels.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Self {
+            levels,
+            current: Cell::default(),
+        }
+    }
+
+    /// The index of the level used at `distance` (`None` for the object's own mesh).
+    ///
+    /// Coarser levels are taken as soon as their threshold is reached, but only left once
+    /// within [`LodMeshes::HYSTERESIS`] of it.
+    pub fn select(&self, distance: f32) -> Option<usize> {
+        let level = |scale: f32| {
+            self.levels
+                .partition_point(|(threshold, _)| *threshold * scale <= distance)
+                .checked_sub(1)
+        };
+
+        let coarse = level(1.0);
+        let kept = level(1.0 - Self::HYSTERESIS).min(self.current.get());
+
+        let selected = coarse.max(kept);
+        self.current.set(selected);
+        selected
     }
 
-    /// remove the object specified object.
-    pub fn remove(&mut self, id: Id) -> Option<Object> {
-        self.opaque.remove(&id)
+    /// The mesh used at `distance`, if any level applies.
+    pub fn mesh(&self, distance: f32) -> Option<&Object> {
+        self.select(distance).map(|i| &self.levels[i].1)
     }
 
-    /// retain only the objects specified by object type.
-    pub fn retain(&mut self, gl: &Context, kind: RawObjectDataUnit) {
-        self.opaque.retain(|_, obj| {
-            if kind == obj.kind() {
-                free_buffers(gl, obj.buffers());
-                false
-            } else {
-                true
+    pub fn meshes(&self) -> impl Iterator<Item = &Object> {
+        self.levels.iter().map(|(_, mesh)| mesh)
+    }
+
+    fn meshes_mut(&mut self) -> impl Iterator<Item = &mut Object> {
+        self.levels.iter_mut().map(|(_, mesh)| mesh)
+    }
+}
+
+/// The progress of a [`DespawnEffect::Fade`].
+#[derive(Clone, Copy, Debug)]
+struct Fade {
+    elapsed: f32,
+    duration: f32,
+    alpha: f32,
+}
+
+/// How a light tween (see [`RawObjects::tween_light`]) progresses over its duration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Waveform {
+    /// Once from the start to the target, which is then kept.
+    #[default]
+    Linear,
+    /// Forever back and forth, reaching the target halfway through every period.
+    Sine,
+    /// Forever alternating, the start for the first half of every period and the target
+    /// for the second.
+    Square,
+}
+
+impl Waveform {
+    /// The weight of the target after `elapsed` of a `duration` (period) long tween.
+    pub fn weight(&self, elapsed: f32, duration: f32) -> f32 {
+        if duration <= 0.0 {
+            return 1.0;
+        }
+        let t = elapsed / duration;
+
+        match self {
+            Self::Linear => t.clamp(0.0, 1.0),
+            Self::Sine => (1.0 - (t * TAU).cos()) / 2.0,
+            Self::Square => {
+                if t.fract() < 0.5 {
+                    0.0
+                } else {
+                    1.0
+                }
             }
-        });
+        }
     }
 
-    /// return an iterator of every light object
-    pub fn lights(&self) -> impl Iterator<Item = &Object> {
-        self.opaque.values().filter(|o| o.is_light())
+    /// Whether a tween of this shape ends (after its duration).
+    pub const fn is_finite(&self) -> bool {
+        matches!(self, Self::Linear)
+    }
+}
+
+/// The progress of a light animated by [`RawObjects::tween_light`].
+#[derive(Clone, Copy, Debug)]
+struct LightTween {
+    from: (f32, [f32; 3]),
+    to: (f32, [f32; 3]),
+    elapsed: f32,
+    duration: f32,
+    waveform: Waveform,
+}
+
+impl LightTween {
+    /// The intensity and color after `elapsed`.
+    fn sample(&self) -> (f32, [f32; 3]) {
+        let w = self.waveform.weight(self.elapsed, self.duration);
+        let lerp = |a: f32, b: f32| a + (b - a) * w;
+
+        let (from_intensity, from_color) = self.from;
+        let (to_intensity, to_color) = self.to;
+
+        (
+            lerp(from_intensity, to_intensity),
+            std::array::from_fn(|i| lerp(from_color[i], to_color[i])),
+        )
     }
 
-    /// return an iterator of every object in descending order,
-    /// based on the alpha value color of each object.
-    pub fn iter(&self) -> impl Iterator<Item = &Object> {
-        self.opaque.values()
+    fn is_done(&self) -> bool {
+        self.waveform.is_finite() && self.elapsed >= self.duration
+    }
+}
+
+/// Arbitrary gameplay data attached to an object.
+pub type UserData = Box<dyn Any + Send + Sync>;
+
+type UpdateCallback = dyn FnMut(&mut ObjectData, f32) + Send + Sync;
+
+/// A callback invoked every tick with the data of its object and the time step (in seconds).
+pub struct UpdateFn(Box<UpdateCallback>);
+
+impl Debug for UpdateFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UpdateFn")
+    }
+}
+
+/// Why an object left the scene.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemoveReason {
+    /// Explicitly removed (or deleted/despawned).
+    Removed,
+    /// A moving object reached the end of its lifetime.
+    Expired,
+    /// A fading despawn finished.
+    Faded,
+    /// Rejected by [`RawObjects::retain_by`].
+    Filtered,
+    /// Every object was cleared.
+    Cleared,
+    /// Another object was inserted with its id.
+    Replaced,
+}
+
+/// Something that happened to an object of a [`RawObjects`] (see [`RawObjects::set_lifecycle_logger`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    Spawned {
+        id: Id,
+        kind: RawObjectDataUnit,
+    },
+    Removed {
+        id: Id,
+        kind: RawObjectDataUnit,
+        reason: RemoveReason,
+    },
+    /// The GPU buffers of the object were released (shared meshes may outlive it).
+    BuffersFreed {
+        id: Id,
+    },
+}
+
+type LifecycleCallback = dyn FnMut(LifecycleEvent) + Send + Sync;
+
+/// A callback invoked on every [`LifecycleEvent`].
+pub struct LifecycleLogger(Box<LifecycleCallback>);
+
+impl LifecycleLogger {
+    fn log(logger: &mut Option<Self>, event: LifecycleEvent) {
+        if let Some(Self(f)) = logger {
+            f(event)
+        }
+    }
+}
+
+impl Debug for LifecycleLogger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LifecycleLogger")
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RawObjects {
+    opaque: DenseMap<Object>,
+    user_data: HashMap<Id, UserData>,
+    update_fns: HashMap<Id, UpdateFn>,
+    despawn_effects: HashMap<Id, DespawnEffect>,
+    fades: HashMap<Id, Fade>,
+    light_tweens: HashMap<Id, LightTween>,
+    lods: HashMap<Id, LodMeshes>,
+    sockets: HashMap<Id, HashMap<String, Vector>>,
+    custom_meshes: HashMap<Id, Mesh>,
+    attached: HashMap<Id, String>,
+    spawn_queue: VecDeque<(Program, Geometry, ObjectData)>,
+    pending_links: Vec<(Id, Id)>,
+    sky: SkyCycle,
+    sun: Option<Id>,
+    time_of_day: f32,
+    meshes: MeshCache,
+    share_meshes: bool,
+    order: Option<BTreeSet<Id>>,
+    color: ColorManagement,
+    clip: Option<ClipPlane>,
+    bvh: Bvh,
+    bvh_ids: HashSet<Id>,
+    bvh_dirty: bool,
+    max_dt: Option<f32>,
+    gravity: Option<Vector>,
+    hovered: Option<Id>,
+    palette: Palette,
+    cvd: CvdMode,
+    cvd_palette: Palette,
+    shadows: Option<Shadows>,
+    logger: Option<LifecycleLogger>,
+    sort_key: Option<DrawSortKey>,
+    gpu_timer: Option<GpuTimer>,
+}
+
+impl RawObjects {
+    /// The dimensions of every projectile.
+    const PROJECTILE_DIM: Vector = Vector::new(0.1, 0.1, 0.1);
+
+    /// The time spent creating queued objects each frame (see [`RawObjects::process_spawn_queue`]).
+    pub const SPAWN_BUDGET: Duration = Duration::from_millis(2);
+
+    /// How far the sun is from the origin of the scene.
+    const SUN_DISTANCE: f32 = 8.0;
+
+    /// Brightens the hovered object (see [`RawObjects::update_hover`]).
+    pub const HOVER_HIGHLIGHT: Color = Color::new([1.4, 1.4, 1.4, 1.0], false);
+
+    /// construct an empty scene that's always iterated in ascending order of id.
+    ///
+    /// Keeps replays and snapshots deterministic (at the cost of an extra index).
+    pub fn ordered() -> Self {
+        Self {
+            order: Some(BTreeSet::new()),
+            ..Default::default()
+        }
+    }
+
+    pub const fn is_ordered(&self) -> bool {
+        self.order.is_some()
+    }
+
+    /// The most seconds a single update advances objects by ([`MAX_DT`] by default).
+    pub fn max_dt(&self) -> f32 {
+        self.max_dt.unwrap_or(MAX_DT)
+    }
+
+    pub fn set_max_dt(&mut self, max_dt: f32) {
+        self.max_dt = Some(max_dt.max(0.0))
+    }
+
+    /// The acceleration of [`RawObjects::step_physics`] (none by default).
+    pub const fn gravity(&self) -> Option<Vector> {
+        self.gravity
+    }
+
+    /// Accelerate every dynamic object by `gravity` from now on.
+    ///
+    /// Anything meant to stay put (e.g., the ground) must be kinematic first.
+    pub fn set_gravity(&mut self, gravity: Option<Vector>) {
+        self.gravity = gravity
+    }
+
+    /// clamp the `dt` of a frame, so a stall (e.g., dragging the window) can't launch objects.
+    fn step_dt(&self, dt: f32) -> f32 {
+        dt.clamp(0.0, self.max_dt())
+    }
+
+    /// return the first local id (see [`LOCAL_IDS`]) that isn't occupied by an object.
+    ///
+    /// Every other id is the server's to hand out.
+    pub fn next_id(&self) -> Option<Id> {
+        LOCAL_IDS.clone().find(|id| !self.opaque.contains_key(id))
+    }
+
+    /// create and add a new cube with specified attributes.
+    pub fn new_cube(
+        &mut self,
+        gl: &Context,
+        id: Id,
+        program: Program,
+        pos: Vector,
+        dim: Vector,
+        color: Color,
+        kind: RawObjectDataUnit,
+    ) -> Result {
+        let raw_data = match kind {
+            RawObjectDataUnit::Player => RawObjectData::Player(PlayerData::new(pos)),
+            RawObjectDataUnit::Basic => RawObjectData::Basic(BasicData::new(pos, dim)),
+        };
+        let data = ObjectData::new(id, color, raw_data);
+
+        self.new_cube_with(gl, program, data)
+    }
+
+    /// create and add a new cube with specified [`ObjectData`].
+    pub fn new_cube_with(&mut self, gl: &Context, program: Program, data: ObjectData) -> Result {
+        let obj = self.create_cube(gl, program, data)?;
+        self.insert(gl, obj);
+        Ok(())
+    }
+
+    /// reuse a single VBO/EBO for every cube of the same geometry (from now on).
+    pub fn set_mesh_sharing(&mut self, enabled: bool) {
+        self.share_meshes = enabled
+    }
+
+    pub fn meshes(&self) -> &MeshCache {
+        &self.meshes
+    }
+
+    /// return the cube geometry `program` expects.
+    fn cube_geometry(program: Program) -> Geometry {
+        match program.kind() {
+            ProgramUnit::Simple => Geometry::FlatCube,
+            ProgramUnit::Normal => Geometry::Cube,
+        }
+    }
+
+    /// create a cube with the geometry `program` expects, sharing its mesh if enabled.
+    fn create_cube(&mut self, gl: &Context, program: Program, data: ObjectData) -> Result<Object> {
+        self.create_geometry(gl, program, Self::cube_geometry(program), data)
+    }
+
+    /// create an object of built-in `geometry`, sharing its mesh if enabled.
+    fn create_geometry(
+        &mut self,
+        gl: &Context,
+        program: Program,
+        geometry: Geometry,
+        data: ObjectData,
+    ) -> Result<Object> {
+        if !self.share_meshes {
+            return Object::create_geometry_with(gl, program, geometry, data);
+        }
+
+        self.meshes.create(
+            gl,
+            program,
+            data,
+            MeshSource::Builtin(geometry),
+            |gl, program, data| Object::create_geometry_with(gl, program, geometry, data),
+        )
+    }
+
+    /// import and add a Wavefront OBJ file (see [`Object::import_obj`]).
+    ///
+    /// Each file is only parsed and uploaded once; later imports of the same path (and
+    /// `options`) share its buffers until the last of its objects is freed.
+    pub fn import_obj(
+        &mut self,
+        gl: &Context,
+        program: Program,
+        path: impl AsRef<Path>,
+        options: impl Into<ImportOptions>,
+        data: ObjectData,
+    ) -> Result {
+        let path = path.as_ref();
+        let options = options.into();
+
+        let obj = self.meshes.create(
+            gl,
+            program,
+            data,
+            MeshSource::File(cache_path(path), options),
+            |gl, program, data| Object::import_obj(gl, program, path, options, data),
+        )?;
+        self.insert(gl, obj);
+        Ok(())
+    }
+
+    /// create and add an object of custom geometry (see [`Object::from_mesh`]).
+    ///
+    /// Unlike objects inserted directly, `mesh` is retained, so the object survives
+    /// [`RawObjects::recreate_gl_resources`].
+    pub fn insert_mesh(
+        &mut self,
+        gl: &Context,
+        program: Program,
+        mesh: Mesh,
+        data: ObjectData,
+    ) -> Result {
+        let obj = Object::from_mesh(gl, program, &mesh, data)?;
+        let id = obj.id();
+        self.insert(gl, obj);
+        self.custom_meshes.insert(id, mesh);
+        Ok(())
+    }
+
+    /// rebuild every buffer after the GL context was lost, given a fresh context and the
+    /// programs recreated in it.
+    ///
+    /// The old handles are dropped without being deleted (they died with the context).
+    /// Shared meshes are shared again, imported files are read again, and the data of
+    /// every object is kept as is. Objects whose geometry can't be rebuilt (custom
+    /// geometry not added through [`RawObjects::insert_mesh`], or files which can no
+    /// longer be imported) are removed, and their ids returned. Shadows are disabled
+    /// until a new [`ShadowMap`] is set.
+    pub fn recreate_gl_resources(&mut self, gl: &Context, shaders: &Shaders) -> Vec<Id> {
+        // the source of every shared mesh, by its (dead) EBO
+        let sources = std::mem::take(&mut self.meshes)
+            .lookup
+            .into_iter()
+            .map(|((source, _), ebo)| (ebo, source))
+            .collect::<HashMap<_, _>>();
+
+        let mut lost = Vec::new();
+
+        for id in self.opaque.keys().copied().collect::<Vec<_>>() {
+            let old = self.opaque[&id];
+            let program = shaders.get(old.program().kind());
+
+            let source = old
+                .buffers()
+                .is_shared()
+                .then(|| sources.get(&old.ebo()).cloned())
+                .flatten();
+
+            match self.recreate(gl, program, &old, source) {
+                Some(Ok(obj)) => _ = self.opaque.insert(id, obj),
+                Some(Err(e)) => {
+                    warn!("[recreate_gl_resources] ({}) {}", id, e);
+                    lost.push(id)
+                }
+                None => lost.push(id),
+            }
+        }
+
+        // levels of detail are all rebuilt or dropped together
+        for (id, lods) in std::mem::take(&mut self.lods) {
+            let levels = lods
+                .levels
+                .iter()
+                .map(|(threshold, mesh)| {
+                    let program = shaders.get(mesh.program().kind());
+                    let mesh = mesh.recreate(gl, program, None)?.ok()?;
+                    Some((*threshold, mesh))
+                })
+                .collect::<Option<Vec<_>>>();
+
+            match levels {
+                Some(levels) => _ = self.lods.insert(id, LodMeshes::new(levels)),
+                None => warn!("[recreate_gl_resources] ({}) Dropped its LOD meshes", id),
+            }
+        }
+
+        // the buffers of lost objects went along with the context, unlike their rebuilt LODs
+        for &id in &lost {
+            if let Some((_, Some(lods))) = self.remove(id) {
+                self.free_lods(gl, lods)
+            }
+        }
+
+        // state of the context which the scene enables
+        if self.clip.is_some() {
+            unsafe { gl.enable(CLIP_DISTANCE0) }
+        }
+        self.shadows = None;
+
+        // the queries died with the context too
+        if self.gpu_timer.take().is_some() {
+            self.gpu_timer = GpuTimer::new(gl)
+                .inspect_err(|e| error!("[gpu timer] {}", e))
+                .ok();
+        }
+
+        lost
+    }
+
+    /// rebuild `old`, sharing buffers again if they came from `source`.
+    fn recreate(
+        &mut self,
+        gl: &Context,
+        program: Program,
+        old: &Object,
+        source: Option<MeshSource>,
+    ) -> Option<Result<Object>> {
+        let mesh = self.custom_meshes.get(&old.id());
+
+        match source {
+            Some(MeshSource::File(path, options)) => Some(self.meshes.create(
+                gl,
+                program,
+                old.data,
+                MeshSource::File(path.clone(), options),
+                |gl, program, data| Object::import_obj(gl, program, &path, options, data),
+            )),
+            Some(source @ MeshSource::Builtin(_)) => {
+                Some(
+                    self.meshes
+                        .create(gl, program, old.data, source, |gl, program, _| {
+                            old.recreate(gl, program, None)
+                                .unwrap_or_else(|| Err("Custom geometry can't be shared".into()))
+                        }),
+                )
+            }
+            None => old.recreate(gl, program, mesh),
+        }
+    }
+
+    /// queue a cube to be created by [`RawObjects::process_spawn_queue`].
+    pub fn spawn_deferred(&mut self, program: Program, data: ObjectData) {
+        self.spawn_queue
+            .push_back((program, Self::cube_geometry(program), data));
+    }
+
+    /// create queued cubes until `budget` (as measured by `now`, e.g., [`Instant::now`])
+    /// is exhausted, returning how many were created.
+    ///
+    /// Spreads large buffer uploads across multiple frames.
+    pub fn process_spawn_queue(
+        &mut self,
+        gl: &Context,
+        budget: Duration,
+        now: impl FnMut() -> Instant,
+    ) -> Result<usize> {
+        let mut queue = std::mem::take(&mut self.spawn_queue);
+        let spawned = drain_within(&mut queue, budget, now, |(program, geometry, data)| {
+            let obj = self.create_geometry(gl, program, geometry, data)?;
+            self.insert(gl, obj);
+            Ok(())
+        });
+
+        // anything queued meanwhile goes after what's left
+        queue.append(&mut self.spawn_queue);
+        self.spawn_queue = queue;
+
+        let spawned = spawned?;
+        self.link_spawned()?;
+        Ok(spawned)
+    }
+
+    /// create at most `max` queued cubes, returning how many were created.
+    ///
+    /// Unlike [`RawObjects::process_spawn_queue`], the number of objects appearing per
+    /// frame is fixed (e.g., materializing a snapshot progressively).
+    pub fn process_spawn_batch(&mut self, gl: &Context, max: usize) -> Result<usize> {
+        let mut queue = std::mem::take(&mut self.spawn_queue);
+        let spawned = drain_batch(&mut queue, max, |(program, geometry, data)| {
+            let obj = self.create_geometry(gl, program, geometry, data)?;
+            self.insert(gl, obj);
+            Ok(())
+        });
+
+        // anything queued meanwhile goes after what's left
+        queue.append(&mut self.spawn_queue);
+        self.spawn_queue = queue;
+
+        let spawned = spawned?;
+        self.link_spawned()?;
+        Ok(spawned)
+    }
+
+    /// link the objects of a batched resync once every one of them exists.
+    fn link_spawned(&mut self) -> Result {
+        if !self.spawn_queue.is_empty() || self.pending_links.is_empty() {
+            return Ok(());
+        }
+        let links = std::mem::take(&mut self.pending_links);
+        self.link(&links)
+    }
+
+    /// return the number of queued cubes.
+    pub fn pending_spawns(&self) -> usize {
+        self.spawn_queue.len()
+    }
+
+    /// return a mutable reference of the specified object.
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut ObjectData> {
+        // static objects may be moved (or hidden) through it
+        self.bvh_dirty |= self.bvh_ids.contains(&id);
+        self.opaque.get_mut(&id).map(Object::data_mut)
+    }
+
+    /// draw the specified object (and its levels of detail) with `program`, keeping its buffers.
+    ///
+    /// Fails without changing anything if any of its meshes lacks an attribute `program` reads.
+    pub fn set_program(&mut self, gl: &Context, id: Id, program: Program) -> Result {
+        let obj = self
+            .opaque
+            .get_mut(&id)
+            .ok_or_else(|| format!("Object {} doesn't exist", id))?;
+        let mut lods = self.lods.get_mut(&id);
+
+        obj.supports(program)?;
+        for mesh in lods.iter_mut().flat_map(|lods| lods.meshes_mut()) {
+            mesh.supports(program)?
+        }
+
+        obj.set_program(gl, program)?;
+        for mesh in lods.into_iter().flat_map(LodMeshes::meshes_mut) {
+            mesh.set_program(gl, program)?
+        }
+        Ok(())
+    }
+
+    /// insert a new object, freeing the one it replaces (if any).
+    pub fn insert(&mut self, gl: &Context, obj: Object) {
+        if self.opaque.contains_key(&obj.id()) {
+            self.delete_with(gl, obj.id(), RemoveReason::Replaced);
+        }
+        self.add(obj)
+    }
+
+    /// add `obj`, its id being unoccupied.
+    fn add(&mut self, obj: Object) {
+        if let Some(order) = &mut self.order {
+            order.insert(obj.id());
+        }
+        LifecycleLogger::log(
+            &mut self.logger,
+            LifecycleEvent::Spawned {
+                id: obj.id(),
+                kind: obj.data().kind(),
+            },
+        );
+        // it might be (or replace) static geometry
+        self.bvh_dirty |= obj.basic_ref().is_some() || self.bvh_ids.contains(&obj.id());
+        self.opaque.insert(obj.id(), obj);
+    }
+
+    /// invoke `logger` on every spawn, removal and release of buffers from now on.
+    pub fn set_lifecycle_logger(&mut self, logger: Box<dyn FnMut(LifecycleEvent) + Send + Sync>) {
+        self.logger = Some(LifecycleLogger(logger))
+    }
+
+    /// stop logging lifecycle events.
+    pub fn clear_lifecycle_logger(&mut self) {
+        self.logger = None
+    }
+
+    /// create and add a new light (simple shading with color as light color) object.
+    pub fn new_light(
+        &mut self,
+        gl: &Context,
+        id: Id,
+        program: Program,
+        pos: Vector,
+        dim: Vector,
+        color: Color,
+    ) -> Result {
+        let data = ObjectData::new(id, color, RawObjectData::Basic(BasicData::new(pos, dim)));
+
+        let obj = self.create_cube(gl, program, data)?;
+        self.insert(gl, obj);
+        Ok(())
+    }
+
+    /// create and add a cube that moves with `vel` and despawns after `lifetime` seconds.
+    pub fn spawn_projectile(
+        &mut self,
+        gl: &Context,
+        program: Program,
+        pos: Vector,
+        vel: Vector,
+        lifetime: f32,
+        color: Color,
+    ) -> Result<Id> {
+        let id = self.next_id().ok_or("No available object ids")?;
+
+        let raw_data = RawObjectData::Basic(BasicData::with_motion(
+            pos,
+            Self::PROJECTILE_DIM,
+            vel,
+            Some(lifetime),
+        ));
+        let data = ObjectData::new(id, color, raw_data);
+
+        let obj = self.create_cube(gl, program, data)?;
+        self.insert(gl, obj);
+        Ok(id)
+    }
+
+    /// advance every moving object by `dt` seconds (at most [`RawObjects::max_dt`]),
+    /// freeing the expired ones.
+    pub fn update_projectiles(&mut self, gl: &Context, dt: f32) {
+        for obj in self.advance(dt) {
+            Self::free_logged(
+                gl,
+                &mut self.meshes,
+                &mut self.logger,
+                &obj,
+                RemoveReason::Expired,
+            );
+        }
+        self.prune(gl);
+    }
+
+    /// advance every moving object by `dt` seconds (at most [`RawObjects::max_dt`]),
+    /// removing and returning the expired ones (for the caller to free).
+    fn advance(&mut self, dt: f32) -> Vec<Object> {
+        let dt = self.step_dt(dt);
+
+        let mut expired = Vec::new();
+        self.opaque.retain(|_, obj| {
+            let Some(mut basic) = obj.basic_mut() else {
+                return true;
+            };
+
+            // sleeping (or still) objects haven't moved
+            let moves = !basic.is_asleep() && *basic.vel() != Vector::zeros();
+
+            if basic.integrate(dt) {
+                if moves {
+                    obj.translation_upt();
+                    obj.model_upt();
+                }
+                true
+            } else {
+                expired.push(*obj);
+                false
+            }
+        });
+        expired
+    }
+
+    /// push colliding objects apart and let those at rest fall asleep, then accelerate every
+    /// dynamic object by the gravity (if any) for `dt` seconds (at most [`RawObjects::max_dt`]),
+    /// returning every pair that collided (see [`RawObjects::collisions`]).
+    ///
+    /// Kinematic objects ignore both, but others still collide with (and resolve against) them.
+    /// Positions are advanced by [`RawObjects::update_projectiles`] (called before), so the
+    /// contacts of a resting object cancel the gravity it was integrated with.
+    pub fn step_physics(&mut self, dt: f32) -> Vec<(Id, Id)> {
+        let dt = self.step_dt(dt);
+
+        let basic = |objects: &Self, id: Id| Some(*objects.opaque.get(&id)?.basic_ref()?);
+
+        let mut collided = Vec::new();
+        for (a, b) in self.collisions() {
+            let (Some(mut a_data), Some(mut b_data)) = (basic(self, a), basic(self, b)) else {
+                continue;
+            };
+            if !resolve_basics(&mut a_data, &mut b_data) {
+                continue;
+            }
+
+            for (id, data) in [(a, a_data), (b, b_data)] {
+                self.bvh_dirty |= self.bvh_ids.contains(&id);
+
+                if let Some(obj) = self.opaque.get_mut(&id) {
+                    if let Some(mut basic) = obj.basic_mut() {
+                        *basic = data
+                    }
+                    obj.translation_upt();
+                    obj.model_upt();
+                }
+            }
+            collided.push((a, b));
+        }
+
+        for obj in self.opaque.values_mut().filter(|obj| !obj.is_light()) {
+            let id = obj.id();
+
+            if let Some(mut basic) = obj.basic_mut() {
+                basic.settle(dt);
+                if let Some(gravity) = &self.gravity {
+                    basic.apply_gravity(gravity, dt)
+                }
+
+                // static objects start moving
+                self.bvh_dirty |= *basic.vel() != Vector::zeros() && self.bvh_ids.contains(&id);
+            }
+        }
+        collided
+    }
+
+    /// wake every sleeping object within `radius` of `pos` (e.g., after an explosion).
+    pub fn wake_near(&mut self, pos: Vector, radius: f32) {
+        for obj in self.opaque.values_mut() {
+            if let Some(mut basic) = obj.basic_mut() {
+                if (basic.pos() - pos).norm() <= radius {
+                    basic.wake()
+                }
+            }
+        }
+    }
+
+    /// move every object (and its user data) of `other` into this scene.
+    ///
+    /// Colliding ids are remapped to unoccupied ones (parents included). The returned
+    /// old -> new mapping covers every merged object, so other references between objects
+    /// can be fixed up. Objects that don't fit (no ids left) are freed, and so are unlinked
+    /// from their children. Queued spawns are appended as is.
+    pub fn merge(&mut self, gl: &Context, other: RawObjects) -> HashMap<Id, Id> {
+        let (mapping, unfit) = self.merge_with(other);
+
+        for (obj, lods) in unfit {
+            self.meshes.free(gl, &obj);
+            if let Some(lods) = lods {
+                self.free_lods(gl, lods)
+            }
+        }
+        mapping
+    }
+
+    /// [`RawObjects::merge`], returning the objects that don't fit (and their LOD meshes)
+    /// rather than freeing them.
+    fn merge_with(
+        &mut self,
+        mut other: RawObjects,
+    ) -> (HashMap<Id, Id>, Vec<(Object, Option<LodMeshes>)>) {
+        let mut mapping = HashMap::with_capacity(other.len());
+        let mut unfit = Vec::new();
+
+        // shared buffers of `other` are now owned by this scene
+        self.meshes.absorb(std::mem::take(&mut other.meshes));
+
+        // deterministic remapping order
+        let mut ids = other.opaque.keys().copied().collect::<Vec<_>>();
+        ids.sort_unstable();
+
+        for &old in &ids {
+            let Some(mut obj) = other.opaque.remove(&old) else {
+                continue;
+            };
+
+            let new = if self.opaque.contains_key(&old) {
+                match self.next_id() {
+                    Some(id) => id,
+                    None => {
+                        warn!("[merge] No available id for object ({})", old);
+                        unfit.push((obj, other.lods.remove(&old)));
+                        continue;
+                    }
+                }
+            } else {
+                old
+            };
+
+            obj.set_id(new);
+            self.add(obj);
+
+            if let Some(data) = other.user_data.remove(&old) {
+                self.user_data.insert(new, data);
+            }
+            if let Some(f) = other.update_fns.remove(&old) {
+                self.update_fns.insert(new, f);
+            }
+            if let Some(effect) = other.despawn_effects.remove(&old) {
+                self.despawn_effects.insert(new, effect);
+            }
+            if let Some(fade) = other.fades.remove(&old) {
+                self.fades.insert(new, fade);
+            }
+            if let Some(tween) = other.light_tweens.remove(&old) {
+                self.light_tweens.insert(new, tween);
+            }
+            if let Some(lods) = other.lods.remove(&old) {
+                self.lods.insert(new, lods);
+            }
+            if let Some(sockets) = other.sockets.remove(&old) {
+                self.sockets.insert(new, sockets);
+            }
+            if let Some(socket) = other.attached.remove(&old) {
+                self.attached.insert(new, socket);
+            }
+            if let Some(mesh) = other.custom_meshes.remove(&old) {
+                self.custom_meshes.insert(new, mesh);
+            }
+            mapping.insert(old, new);
+        }
+
+        // parents outside of `other` are kept
+        for &new in mapping.values() {
+            let Some(obj) = self.opaque.get_mut(&new) else {
+                continue;
+            };
+            let Some(parent) = obj.parent() else {
+                continue;
+            };
+
+            if let Some(&moved) = mapping.get(&parent) {
+                obj.set_parent(Some(moved))
+            } else if ids.contains(&parent) {
+                obj.set_parent(None)
+            }
+        }
+
+        self.spawn_queue.append(&mut other.spawn_queue);
+        (mapping, unfit)
+    }
+
+    /// replace every object with those of a full snapshot (e.g., after reconnecting).
+    ///
+    /// Objects are created before any parent is linked, so children may precede their
+    /// parents. Links to ids absent from the snapshot are dropped (the objects are kept)
+    /// and reported as [`Error::MissingReferences`].
+    ///
+    /// Every object is rebuilt from its [`Geometry`], so custom geometry is rejected.
+    pub fn resync(
+        &mut self,
+        gl: &Context,
+        program: Program,
+        snapshot: &[(Geometry, ObjectData)],
+    ) -> Result {
+        self.clear(gl);
+
+        // create every object unlinked
+        for (geometry, data) in Self::unlinked(snapshot) {
+            let obj = self.create_geometry(gl, program, geometry, data)?;
+            self.insert(gl, obj);
+        }
+
+        // then link them, now that forward references exist
+        self.link(&Self::links(snapshot))
+    }
+
+    /// replace every object with those of a full snapshot, created progressively by
+    /// [`RawObjects::process_spawn_batch`] (or [`RawObjects::process_spawn_queue`]).
+    ///
+    /// Parents are linked (as by [`RawObjects::resync`]) once the last object is created.
+    pub fn resync_batched(
+        &mut self,
+        gl: &Context,
+        program: Program,
+        snapshot: &[(Geometry, ObjectData)],
+    ) {
+        self.clear(gl);
+        self.spawn_queue.clear();
+
+        for (geometry, data) in Self::unlinked(snapshot) {
+            self.spawn_queue.push_back((program, geometry, data));
+        }
+        self.pending_links = Self::links(snapshot);
+    }
+
+    /// return every object of `snapshot` without its parent, ready to be created.
+    fn unlinked(
+        snapshot: &[(Geometry, ObjectData)],
+    ) -> impl Iterator<Item = (Geometry, ObjectData)> + '_ {
+        snapshot.iter().map(|&(geometry, mut data)| {
+            data.set_parent(None);
+            data.transform_upt();
+            (geometry, data)
+        })
+    }
+
+    /// return every (child, parent) link of `snapshot`.
+    fn links(snapshot: &[(Geometry, ObjectData)]) -> Vec<(Id, Id)> {
+        snapshot
+            .iter()
+            .filter_map(|(_, data)| Some((data.id(), data.parent()?)))
+            .collect()
+    }
+
+    /// link each (child, parent), reporting the links to absent parents.
+    fn link(&mut self, links: &[(Id, Id)]) -> Result {
+        let mut missing = Vec::new();
+
+        for &(child, parent) in links {
+            if self.opaque.contains_key(&parent) {
+                if let Some(obj) = self.get_mut(child) {
+                    obj.set_parent(Some(parent))
+                }
+            } else {
+                missing.push((child, parent))
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::MissingReferences(missing))
+        }
+    }
+
+    /// return an iterator of every object directly linked to `parent`.
+    pub fn children(&self, parent: Id) -> impl Iterator<Item = &Object> {
+        self.iter().filter(move |obj| obj.parent() == Some(parent))
+    }
+
+    /// add (or move) a named attachment point at `local` (in the unit space of the object).
+    ///
+    /// Returns `false` if the object doesn't exist.
+    pub fn add_socket(&mut self, id: Id, name: &str, local: Vector) -> bool {
+        if !self.opaque.contains_key(&id) {
+            return false;
+        }
+        self.sockets
+            .entry(id)
+            .or_default()
+            .insert(name.to_owned(), local);
+        true
+    }
+
+    /// remove the named socket of the specified object, returning its local offset.
+    pub fn remove_socket(&mut self, id: Id, name: &str) -> Option<Vector> {
+        self.sockets.get_mut(&id)?.remove(name)
+    }
+
+    /// return the world position of the named socket, as transformed by the model matrix.
+    pub fn socket_world(&self, id: Id, name: &str) -> Option<Vector> {
+        let local = self.sockets.get(&id)?.get(name)?;
+        let model = self.opaque.get(&id)?.model();
+        Some(model.transform_point(&Point::from(*local)).coords)
+    }
+
+    /// parent `child` to the named socket of `parent`, moving it onto the socket.
+    ///
+    /// The child follows the socket on every [`RawObjects::update_attachments`]. Returns
+    /// `false` if either object or the socket doesn't exist.
+    pub fn attach_to_socket(&mut self, child: Id, parent: Id, name: &str) -> bool {
+        if child == parent || self.socket_world(parent, name).is_none() {
+            return false;
+        }
+        let Some(obj) = self.opaque.get_mut(&child) else {
+            return false;
+        };
+
+        obj.set_parent(Some(parent));
+        self.attached.insert(child, name.to_owned());
+        self.update_attachment(child);
+        true
+    }
+
+    /// unlink `child` from its socket (it stays where it is), returning whether it was attached.
+    pub fn detach(&mut self, child: Id) -> bool {
+        if self.attached.remove(&child).is_none() {
+            return false;
+        }
+        if let Some(obj) = self.opaque.get_mut(&child) {
+            obj.set_parent(None)
+        }
+        true
+    }
+
+    /// move every attached object onto its socket (after its parent has moved).
+    ///
+    /// Attachments whose parent or socket no longer exist are left in place.
+    pub fn update_attachments(&mut self) {
+        let children = self.attached.keys().copied().collect::<Vec<_>>();
+
+        for child in children {
+            self.update_attachment(child);
+        }
+    }
+
+    fn update_attachment(&mut self, child: Id) {
+        let Some(parent) = self.opaque.get(&child).and_then(|obj| obj.parent()) else {
+            return;
+        };
+        let Some(world) = self
+            .attached
+            .get(&child)
+            .and_then(|name| self.socket_world(parent, name))
+        else {
+            return;
+        };
+
+        if let Some(obj) = self.opaque.get_mut(&child) {
+            *obj.pos_mut() = world;
+            obj.translation_upt();
+            obj.model_upt();
+        }
+    }
+
+    /// switch the specified cube between flat and smooth normals (see [`Object::rebuild_normals`]).
+    ///
+    /// Objects sharing a cached mesh are given buffers of their own. Returns `false` if
+    /// the object doesn't exist.
+    pub fn rebuild_normals(&mut self, gl: &Context, id: Id, shading: Shading) -> Result<bool> {
+        let Some(obj) = self.opaque.get_mut(&id) else {
+            return Ok(false);
+        };
+
+        if obj.buffers().is_shared() {
+            let reshaded = match shading {
+                Shading::Flat => Object::create_cube_with(gl, obj.program(), *obj.data()),
+                Shading::Smooth => Object::create_smooth_cube_with(gl, obj.program(), *obj.data()),
+            }?;
+            self.meshes.free(gl, obj);
+            *obj = reshaded;
+        } else {
+            obj.rebuild_normals(gl, shading)?;
+        }
+        Ok(true)
+    }
+
+    /// remove the specified object, returning it along with its LOD meshes (if any), all of
+    /// which the caller is left to free (see [`RawObjects::delete`]).
+    pub fn remove(&mut self, id: Id) -> Option<(Object, Option<LodMeshes>)> {
+        let obj = self.remove_with(id, RemoveReason::Removed)?;
+        Some((obj, self.lods.remove(&id)))
+    }
+
+    /// remove the specified object (its LOD meshes are left for the caller to take).
+    fn remove_with(&mut self, id: Id, reason: RemoveReason) -> Option<Object> {
+        self.spawn_queue.retain(|(_, _, data)| data.id() != id);
+        self.user_data.remove(&id);
+        self.update_fns.remove(&id);
+        self.despawn_effects.remove(&id);
+        self.fades.remove(&id);
+        self.light_tweens.remove(&id);
+        self.sockets.remove(&id);
+        self.attached.remove(&id);
+        self.custom_meshes.remove(&id);
+        if let Some(order) = &mut self.order {
+            order.remove(&id);
+        }
+        self.bvh_dirty |= self.bvh_ids.contains(&id);
+        let obj = self.opaque.remove(&id)?;
+        LifecycleLogger::log(
+            &mut self.logger,
+            LifecycleEvent::Removed {
+                id,
+                kind: obj.data().kind(),
+                reason,
+            },
+        );
+        Some(obj)
+    }
+
+    /// remove and free the specified object (and its LOD meshes), returning whether it existed.
+    pub fn delete(&mut self, gl: &Context, id: Id) -> bool {
+        self.delete_with(gl, id, RemoveReason::Removed)
+    }
+
+    fn delete_with(&mut self, gl: &Context, id: Id, reason: RemoveReason) -> bool {
+        if let Some(lods) = self.lods.remove(&id) {
+            self.free_lods(gl, lods)
+        }
+        let Some(obj) = self.remove_with(id, reason) else {
+            return false;
+        };
+        self.meshes.free(gl, &obj);
+        LifecycleLogger::log(&mut self.logger, LifecycleEvent::BuffersFreed { id });
+        true
+    }
+
+    /// log the removal of `obj` (already taken out of the scene) and free it.
+    fn free_logged(
+        gl: &Context,
+        meshes: &mut MeshCache,
+        logger: &mut Option<LifecycleLogger>,
+        obj: &Object,
+        reason: RemoveReason,
+    ) {
+        let id = obj.id();
+        let kind = obj.data().kind();
+
+        LifecycleLogger::log(logger, LifecycleEvent::Removed { id, kind, reason });
+        meshes.free(gl, obj);
+        LifecycleLogger::log(logger, LifecycleEvent::BuffersFreed { id });
+    }
+
+    /// remove and free every object.
+    pub fn clear(&mut self, gl: &Context) {
+        for (_, obj) in self.opaque.drain() {
+            Self::free_logged(
+                gl,
+                &mut self.meshes,
+                &mut self.logger,
+                &obj,
+                RemoveReason::Cleared,
+            );
+        }
+        self.user_data.clear();
+        self.update_fns.clear();
+        self.despawn_effects.clear();
+        self.fades.clear();
+        self.light_tweens.clear();
+        self.sockets.clear();
+        self.attached.clear();
+        self.custom_meshes.clear();
+        for (_, lods) in std::mem::take(&mut self.lods) {
+            self.free_lods(gl, lods)
+        }
+        if let Some(order) = &mut self.order {
+            order.clear();
+        }
+        self.bvh = Bvh::default();
+        self.bvh_ids.clear();
+        self.bvh_dirty = false;
+    }
+
+    /// return every static object (see [`RawObjects::bake_static`]).
+    fn statics(&self) -> impl Iterator<Item = &Object> {
+        self.visible()
+            .filter(|obj| !obj.is_light())
+            .filter(|obj| {
+                obj.basic_ref().is_some_and(|basic| {
+                    basic.lifetime().is_none()
+                        && *basic.vel() == Vector::zeros()
+                        && !basic.is_kinematic()
+                })
+            })
+            .filter(|obj| {
+                !self.update_fns.contains_key(&obj.id()) && !self.fades.contains_key(&obj.id())
+            })
+    }
+
+    /// bake every static cube drawn with `program` into as few objects as possible (one per
+    /// distinct appearance, see [`batch_by_appearance`]), removing the individuals.
+    ///
+    /// Static objects are visible basic objects that never move (not kinematic, and no
+    /// velocity, lifetime, update function, or fade). Each baked object takes the id and
+    /// appearance (color, material, ...) of the first (lowest id) object of its batch.
+    pub fn bake_static(&mut self, gl: &Context, program: Program) -> Result<Vec<Object>> {
+        let mut statics = self
+            .statics()
+            .filter(|obj| obj.program().native() == program.native())
+            .filter(|obj| matches!(obj.geometry(), Geometry::Cube | Geometry::FlatCube))
+            .map(Object::data)
+            .collect::<Vec<_>>();
+        statics.sort_unstable_by_key(|data| data.id());
+
+        let batches = batch_by_appearance(statics);
+        let mut baked = Vec::with_capacity(batches.len());
+
+        for batch in &batches {
+            match self.bake_batch(gl, program, batch) {
+                Ok(obj) => baked.push(obj),
+                Err(e) => {
+                    for obj in &baked {
+                        self.meshes.free(gl, obj)
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        for id in batches.into_iter().flatten() {
+            self.delete(gl, id);
+        }
+        Ok(baked)
+    }
+
+    /// bake the cubes of `ids` (sorted, of a single appearance) into a single object.
+    fn bake_batch(&self, gl: &Context, program: Program, ids: &[Id]) -> Result<Object> {
+        let first = ids[0];
+
+        let (mut vertices, indices) = bake_cubes(
+            ids.iter()
+                .filter_map(|id| self.opaque.get(id))
+                .map(|obj| obj.model()),
+        );
+
+        // bounds of the baked geometry (the baked object is placed and scaled to fit them)
+        let (min, max) = vertices.chunks_exact(6).fold(
+            (Vector::repeat(f32::MAX), Vector::repeat(f32::MIN)),
+            |(min, max), v| {
+                let p = Vector::new(v[0], v[1], v[2]);
+                (min.inf(&p), max.sup(&p))
+            },
+        );
+        let center = (min + max) / 2.0;
+        let half = ((max - min) / 2.0).map(|v| v.max(f32::EPSILON));
+
+        // world space -> the unit space of the baked object (normals scale inversely)
+        for v in vertices.chunks_exact_mut(6) {
+            let p = (Vector::new(v[0], v[1], v[2]) - center).component_div(&half);
+            let n = Vector::new(v[3], v[4], v[5]).component_mul(&half);
+            let n = n.try_normalize(f32::EPSILON).unwrap_or(n);
+            v.copy_from_slice(&[p.x, p.y, p.z, n.x, n.y, n.z]);
+        }
+
+        // the 'simple' program has no normals
+        let has_norms = program.kind() == ProgramUnit::Normal;
+        if !has_norms {
+            vertices = vertices
+                .chunks_exact(6)
+                .flat_map(|v| [v[0], v[1], v[2]])
+                .collect();
+        }
+
+        let template = *self.opaque[&first].data();
+        let mut data = ObjectData::new(
+            first,
+            template.raw_color(),
+            RawObjectData::Basic(BasicData::new(center, half)),
+        );
+        data.set_palette_index(template.palette_index());
+        data.set_material(*template.material());
+        data.set_emissive(template.emissive());
+        data.set_tint(template.tint());
+
+        Object::from_raw::<f32, u32>(
+            gl,
+            program,
+            &vertices,
+            &indices,
+            TRIANGLES,
+            UNSIGNED_INT,
+            data,
+            has_norms,
+        )
+    }
+
+    /// remove (and free) every object of the specified object type.
+    pub fn retain(&mut self, gl: &Context, kind: RawObjectDataUnit) {
+        self.retain_by(gl, |data| data.kind() != kind)
+    }
+
+    /// retain only the objects whose data satisfies `f`, freeing the others.
+    pub fn retain_by(&mut self, gl: &Context, mut f: impl FnMut(&ObjectData) -> bool) {
+        let meshes = &mut self.meshes;
+        let logger = &mut self.logger;
+
+        self.opaque.retain(|_, obj| {
+            if f(obj.data()) {
+                true
+            } else {
+                Self::free_logged(gl, meshes, logger, obj, RemoveReason::Filtered);
+                false
+            }
+        });
+        self.spawn_queue.retain(|(_, _, data)| f(data));
+        self.prune(gl);
+    }
+
+    /// attach arbitrary data to the specified object, replacing any previous data.
+    ///
+    /// Returns `false` if the object doesn't exist.
+    pub fn set_user_data<T: Any + Send + Sync>(&mut self, id: Id, data: T) -> bool {
+        if !self.opaque.contains_key(&id) {
+            return false;
+        }
+        self.user_data.insert(id, Box::new(data));
+        true
+    }
+
+    /// return the data attached to the specified object, if it is of type `T`.
+    pub fn user_data<T: Any>(&self, id: Id) -> Option<&T> {
+        self.user_data.get(&id)?.downcast_ref()
+    }
+
+    /// return a mutable reference of the data attached to the specified object, if it is of type `T`.
+    pub fn user_data_mut<T: Any>(&mut self, id: Id) -> Option<&mut T> {
+        self.user_data.get_mut(&id)?.downcast_mut()
+    }
+
+    /// detach and return the data of the specified object.
+    pub fn take_user_data(&mut self, id: Id) -> Option<UserData> {
+        self.user_data.remove(&id)
+    }
+
+    /// attach a callback to the specified object, invoked by [`RawObjects::update`].
+    ///
+    /// Returns `false` if the object doesn't exist.
+    pub fn set_update_fn(
+        &mut self,
+        id: Id,
+        f: impl FnMut(&mut ObjectData, f32) + Send + Sync + 'static,
+    ) -> bool {
+        if !self.opaque.contains_key(&id) {
+            return false;
+        }
+        self.update_fns.insert(id, UpdateFn(Box::new(f)));
+        true
+    }
+
+    /// detach the callback of the specified object.
+    pub fn clear_update_fn(&mut self, id: Id) -> bool {
+        self.update_fns.remove(&id).is_some()
+    }
+
+    /// invoke the callback of every object, advancing them by `dt` seconds
+    /// (at most [`RawObjects::max_dt`]).
+    pub fn update(&mut self, dt: f32) {
+        let dt = self.step_dt(dt);
+
+        for (id, UpdateFn(f)) in self.update_fns.iter_mut() {
+            if let Some(obj) = self.opaque.get_mut(id) {
+                f(obj.data_mut(), dt);
+                obj.transform_upt();
+            }
+        }
+    }
+
+    /// configure how the specified object despawns, returning `false` if it doesn't exist.
+    pub fn set_despawn_effect(&mut self, id: Id, effect: DespawnEffect) -> bool {
+        if !self.opaque.contains_key(&id) {
+            return false;
+        }
+        self.despawn_effects.insert(id, effect);
+        true
+    }
+
+    /// swap the mesh of the specified object for `levels` beyond their distance thresholds,
+    /// returning `false` if it doesn't exist.
+    ///
+    /// Replaced levels are freed.
+    pub fn set_lod_meshes(&mut self, gl: &Context, id: Id, levels: Vec<(f32, Object)>) -> bool {
+        if !self.opaque.contains_key(&id) {
+            return false;
+        }
+        if let Some(old) = self.lods.insert(id, LodMeshes::new(levels)) {
+            self.free_lods(gl, old)
+        }
+        true
+    }
+
+    pub fn lod_meshes(&self, id: Id) -> Option<&LodMeshes> {
+        self.lods.get(&id)
+    }
+
+    /// The mesh the specified object is drawn with at `distance` from the camera.
+    pub fn lod_mesh<'a>(&'a self, obj: &'a Object, distance: f32) -> &'a Object {
+        self.lods
+            .get(&obj.id())
+            .and_then(|lods| lods.mesh(distance))
+            .unwrap_or(obj)
+    }
+
+    fn free_lods(&mut self, gl: &Context, lods: LodMeshes) {
+        for mesh in lods.meshes() {
+            self.meshes.free(gl, mesh)
+        }
+    }
+
+    /// remove the specified object through its [`DespawnEffect`], returning whether it existed.
+    pub fn despawn(&mut self, gl: &Context, id: Id) -> Result<bool> {
+        let Some(obj) = self.opaque.get(&id).copied() else {
+            return Ok(false);
+        };
+
+        match self.despawn_effects.get(&id).copied().unwrap_or_default() {
+            DespawnEffect::None => {
+                self.delete(gl, id);
+            }
+            DespawnEffect::Explode {
+                fragments,
+                speed,
+                lifetime,
+            } => {
+                self.delete(gl, id);
+
+                let color = obj.raw_color();
+                let golden_angle = std::f32::consts::PI * (3.0 - 5f32.sqrt());
+
+                // evenly spread over a sphere (fibonacci spiral)
+                for i in 0..fragments {
+                    let y = 1.0 - 2.0 * (i as f32 + 0.5) / fragments as f32;
+                    let r = (1.0 - y * y).sqrt();
+                    let theta = i as f32 * golden_angle;
+
+                    let dir = Vector::new(r * theta.cos(), y, r * theta.sin());
+                    self.spawn_projectile(
+                        gl,
+                        obj.program(),
+                        *obj.pos(),
+                        dir * speed,
+                        lifetime,
+                        color,
+                    )?;
+                }
+            }
+            DespawnEffect::Fade { duration } => {
+                let fade = Fade {
+                    elapsed: 0.0,
+                    duration,
+                    alpha: obj.alpha(),
+                };
+                self.fades.entry(id).or_insert(fade);
+            }
+        }
+        Ok(true)
+    }
+
+    /// animate the intensity and color of the specified light towards the targets over
+    /// `duration` seconds (or periodically, see [`Waveform`]), replacing any running tween.
+    ///
+    /// Returns `false` if the object isn't a light.
+    pub fn tween_light(
+        &mut self,
+        id: Id,
+        target_intensity: f32,
+        target_color: Color,
+        duration: f32,
+        waveform: Waveform,
+    ) -> bool {
+        let Some(light) = self.opaque.get(&id).filter(|obj| obj.is_light()) else {
+            return false;
+        };
+
+        let tween = LightTween {
+            from: (light.light_intensity(), light.raw_color().rgb()),
+            to: (target_intensity, target_color.rgb()),
+            elapsed: 0.0,
+            duration,
+            waveform,
+        };
+        self.light_tweens.insert(id, tween);
+        true
+    }
+
+    /// stop animating the specified light (it keeps its current intensity and color).
+    pub fn stop_light_tween(&mut self, id: Id) -> bool {
+        self.light_tweens.remove(&id).is_some()
+    }
+
+    /// advance every light tween by `dt` seconds (at most [`RawObjects::max_dt`]),
+    /// dropping the finished ones.
+    pub fn update_light_tweens(&mut self, dt: f32) {
+        let dt = self.step_dt(dt);
+
+        self.light_tweens.retain(|id, tween| {
+            let Some(light) = self.opaque.get_mut(id) else {
+                return false;
+            };
+
+            tween.elapsed += dt;
+
+            let (intensity, [r, g, b]) = tween.sample();
+            let alpha = light.alpha();
+
+            light.set_light_intensity(intensity);
+            light.set_color(Color::new([r, g, b, alpha], true));
+
+            !tween.is_done()
+        });
+    }
+
+    /// advance every fading object by `dt` seconds (at most [`RawObjects::max_dt`]),
+    /// removing the faded ones.
+    pub fn update_despawns(&mut self, gl: &Context, dt: f32) {
+        let dt = self.step_dt(dt);
+        let mut faded = Vec::new();
+
+        for (&id, fade) in self.fades.iter_mut() {
+            let Some(obj) = self.opaque.get_mut(&id) else {
+                continue;
+            };
+
+            fade.elapsed += dt;
+
+            if fade.elapsed >= fade.duration {
+                faded.push(id);
+                continue;
+            }
+
+            obj.set_alpha(fade.alpha * (1.0 - fade.elapsed / fade.duration));
+        }
+
+        for id in faded {
+            self.delete_with(gl, id, RemoveReason::Faded);
+        }
+    }
+
+    /// drop the data (and ordering) of every object that no longer exists.
+    fn prune(&mut self, gl: &Context) {
+        self.user_data.retain(|id, _| self.opaque.contains_key(id));
+        self.update_fns.retain(|id, _| self.opaque.contains_key(id));
+        self.despawn_effects
+            .retain(|id, _| self.opaque.contains_key(id));
+        self.fades.retain(|id, _| self.opaque.contains_key(id));
+        self.light_tweens
+            .retain(|id, _| self.opaque.contains_key(id));
+        self.sockets.retain(|id, _| self.opaque.contains_key(id));
+        self.attached.retain(|id, _| self.opaque.contains_key(id));
+        self.custom_meshes
+            .retain(|id, _| self.opaque.contains_key(id));
+        let gone = self
+            .lods
+            .keys()
+            .filter(|id| !self.opaque.contains_key(id))
+            .copied()
+            .collect::<Vec<_>>();
+        for id in gone {
+            if let Some(lods) = self.lods.remove(&id) {
+                self.free_lods(gl, lods)
+            }
+        }
+        if let Some(order) = &mut self.order {
+            order.retain(|id| self.opaque.contains_key(id));
+        }
+    }
+
+    pub const fn color(&self) -> ColorManagement {
+        self.color
+    }
+
+    /// change (and apply) where colors are converted into sRGB.
+    pub fn set_color(&mut self, gl: &Context, color: ColorManagement) {
+        color.apply(gl);
+        self.color = color
+    }
+
+    pub const fn clip_plane(&self) -> Option<ClipPlane> {
+        self.clip
+    }
+
+    pub const fn gpu_timer(&self) -> Option<&GpuTimer> {
+        self.gpu_timer.as_ref()
+    }
+
+    /// measure the GPU time of every pass into [`RenderStats::pass_times`] from now on.
+    pub fn enable_gpu_timer(&mut self, gl: &Context) -> Result {
+        if self.gpu_timer.is_none() {
+            self.gpu_timer = Some(GpuTimer::new(gl)?)
+        }
+        Ok(())
+    }
+
+    pub fn disable_gpu_timer(&mut self, gl: &Context) {
+        if let Some(timer) = self.gpu_timer.take() {
+            timer.delete(gl)
+        }
+    }
+
+    pub const fn draw_sort_key(&self) -> Option<&DrawSortKey> {
+        self.sort_key.as_ref()
+    }
+
+    /// draw objects of the same priority in the order of `key` (batched by program without one).
+    pub fn set_draw_sort_key(&mut self, key: Option<DrawSortKey>) {
+        self.sort_key = key
+    }
+
+    pub const fn shadows(&self) -> Option<&Shadows> {
+        self.shadows.as_ref()
+    }
+
+    /// shade every object receiving shadows against `shadows` (e.g., from [`ShadowMap::shadows`]).
+    pub fn set_shadows(&mut self, shadows: Option<Shadows>) {
+        self.shadows = shadows
+    }
+
+    /// slice the scene, discarding everything behind `plane`.
+    pub fn set_clip_plane(&mut self, gl: &Context, plane: ClipPlane) {
+        unsafe { gl.enable(CLIP_DISTANCE0) }
+        self.clip = Some(plane)
+    }
+
+    pub fn clear_clip_plane(&mut self, gl: &Context) {
+        unsafe { gl.disable(CLIP_DISTANCE0) }
+        self.clip = None
+    }
+
+    /// The colors referenced by [`ObjectData::palette_index`].
+    pub const fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.cvd_palette = palette.apply_cvd(self.cvd);
+        self.palette = palette
+    }
+
+    pub const fn cvd_mode(&self) -> CvdMode {
+        self.cvd
+    }
+
+    /// draw palette colors corrected for `mode` (the palette itself is kept as is).
+    pub fn set_cvd_mode(&mut self, mode: CvdMode) {
+        self.cvd = mode;
+        self.cvd_palette = self.palette.apply_cvd(mode)
+    }
+
+    /// The colors palette indices resolve to while drawing (see [`RawObjects::set_cvd_mode`]).
+    pub fn draw_palette(&self) -> &Palette {
+        match self.cvd {
+            CvdMode::None => &self.palette,
+            _ => &self.cvd_palette,
+        }
+    }
+
+    pub fn set_sky(&mut self, sky: SkyCycle) {
+        self.sky = sky
+    }
+
+    /// The time of day the lights were last moved to (see [`SkyCycle`]).
+    pub const fn time_of_day(&self) -> f32 {
+        self.time_of_day
+    }
+
+    /// The light moved by [`RawObjects::set_time_of_day`] (none by default).
+    pub const fn sun(&self) -> Option<Id> {
+        self.sun
+    }
+
+    /// Let the light `id` be the sun, leaving every other light where it is.
+    pub fn set_sun(&mut self, id: Option<Id>) {
+        self.sun = id
+    }
+
+    /// move the sun (if any, see [`RawObjects::set_sun`]) along its arc, tinting it for the
+    /// time of day `t`.
+    pub fn set_time_of_day(&mut self, t: f32) {
+        let t = t.rem_euclid(1.0);
+        self.time_of_day = t;
+
+        let Some(sun) = self
+            .sun
+            .and_then(|id| self.opaque.get_mut(&id))
+            .filter(|obj| obj.is_light())
+        else {
+            return;
+        };
+
+        let pos = self.sky.sun_direction(t) * Self::SUN_DISTANCE;
+        let col = self.sky.light_color(t);
+
+        if let Some(mut basic) = sun.basic_mut() {
+            *basic.pos_mut() = pos;
+        }
+        let alpha = sun.alpha();
+        sun.set_color(Color::new([col.x, col.y, col.z, alpha], true));
+
+        sun.translation_upt();
+        sun.model_upt();
+    }
+
+    /// offset every object (players included) by `delta`, e.g., to shake the world or to
+    /// rebase the origin once the player wanders far from it (keeping positions precise).
+    pub fn transform_all(&mut self, delta: Vector) {
+        self.transform_all_by(&Translation::from(delta).to_homogeneous())
+    }
+
+    /// move the position of every object (players included) by the affine `transform`.
+    ///
+    /// Only positions are transformed (orientations and dimensions are kept).
+    pub fn transform_all_by(&mut self, transform: &Matrix) {
+        for obj in self.opaque.values_mut() {
+            let data = obj.data_mut();
+            let pos = data.pos_mut();
+            *pos = transform.transform_point(&Point::from(*pos)).coords;
+
+            data.translation_upt();
+            data.model_upt();
+        }
+        self.rebuild_bvh();
+    }
+
+    /// run `f` with every object of `snapshot` moved back to where it was, then restore them.
+    ///
+    /// The BVH is rebuilt for the past, so raycasts (e.g., hit-scans) within `f` see it,
+    /// then the present one is restored as it was.
+    pub fn rewind_to<R>(&mut self, snapshot: &Snapshot, f: impl FnOnce(&Self) -> R) -> R {
+        let present = self.move_to(snapshot.positions());
+        let bvh = (
+            std::mem::take(&mut self.bvh),
+            std::mem::take(&mut self.bvh_ids),
+            self.bvh_dirty,
+        );
+        self.rebuild_bvh();
+
+        let result = f(self);
+
+        self.move_to(&present);
+        (self.bvh, self.bvh_ids, self.bvh_dirty) = bvh;
+        result
+    }
+
+    /// move each object to its position, returning where they were.
+    fn move_to(&mut self, positions: &[(Id, Vector)]) -> Vec<(Id, Vector)> {
+        positions
+            .iter()
+            .filter_map(|(id, pos)| {
+                let data = self.opaque.get_mut(id)?.data_mut();
+                let prev = std::mem::replace(data.pos_mut(), *pos);
+
+                data.translation_upt();
+                data.model_upt();
+                Some((*id, prev))
+            })
+            .collect()
+    }
+
+    /// return the corners (min, max) of the box enclosing every object, if there are any.
+    pub fn world_bounds(&self) -> Option<(Vector, Vector)> {
+        self.iter()
+            .map(|obj| obj.bounds())
+            .reduce(|a, b| a.union(&b))
+            .map(|aabb| (aabb.min, aabb.max))
+    }
+
+    /// return the boxes of every solid (visible, basic, non-light) object.
+    pub fn colliders(&self) -> impl Iterator<Item = Aabb> + '_ {
+        self.solids().map(|(_, aabb)| aabb)
+    }
+
+    /// return the boxes of every solid object that collides with `filter`.
+    pub fn colliders_for(&self, filter: CollisionFilter) -> impl Iterator<Item = Aabb> + '_ {
+        self.solids()
+            .filter(move |(obj, _)| filter.collides_with(&obj.collision_filter()))
+            .map(|(_, aabb)| aabb)
+    }
+
+    /// return every pair of overlapping solid objects whose layers collide (lower id first).
+    pub fn collisions(&self) -> Vec<(Id, Id)> {
+        let solids = self.solids().collect::<Vec<_>>();
+        let mut pairs = Vec::new();
+
+        for (i, (a, a_box)) in solids.iter().enumerate() {
+            for (b, b_box) in &solids[i + 1..] {
+                // layers first, they're cheaper than the boxes
+                if a.collides_with(b) && a_box.intersects(b_box) {
+                    pairs.push((a.id().min(b.id()), a.id().max(b.id())));
+                }
+            }
+        }
+        pairs
+    }
+
+    fn solids(&self) -> impl Iterator<Item = (&Object, Aabb)> {
+        self.visible()
+            .filter(|obj| !obj.is_light())
+            .filter_map(|obj| {
+                let b = obj.basic_ref()?;
+                Some((obj, Aabb::from_center(b.pos(), b.dim())))
+            })
+    }
+
+    /// return the id of the nearest visible object hit by the ray.
+    pub fn pick(&self, ray_origin: Vector, ray_dir: Vector) -> Option<Id> {
+        self.pick_detailed(ray_origin, ray_dir).map(|hit| hit.id)
+    }
+
+    /// highlight the object under the ray (clearing the previous one), returning its id.
+    ///
+    /// Meant to be called every frame with the ray of the cursor (see [`RawCamera::screen_to_ray`]).
+    pub fn update_hover(&mut self, ray_origin: Vector, ray_dir: Vector) -> Option<Id> {
+        let hovered = self.pick(ray_origin, ray_dir);
+
+        // the highlight neither moves nor hides anything, so the BVH stays valid
+        if hovered != self.hovered {
+            if let Some(obj) = self.hovered.and_then(|id| self.opaque.get_mut(&id)) {
+                obj.data_mut().set_highlight(None)
+            }
+            if let Some(obj) = hovered.and_then(|id| self.opaque.get_mut(&id)) {
+                obj.data_mut().set_highlight(Some(Self::HOVER_HIGHLIGHT))
+            }
+            self.hovered = hovered;
+        }
+        hovered
+    }
+
+    /// The id of the object highlighted by the last [`RawObjects::update_hover`].
+    pub const fn hovered(&self) -> Option<Id> {
+        self.hovered
+    }
+
+    /// return the nearest visible object hit by the ray, including the face and point hit.
+    ///
+    /// Objects are treated as their bounding boxes. Static objects are found through the
+    /// BVH, unless it's out of date (see [`RawObjects::refresh_bvh`]).
+    pub fn pick_detailed(&self, ray_origin: Vector, ray_dir: Vector) -> Option<PickHit> {
+        let hit = |obj: &Object, distance: f32, face: CubeFace| {
+            let point = ray_origin + ray_dir * distance;
+
+            PickHit {
+                id: obj.id(),
+                face,
+                local_point: to_unit_space(&(point - obj.pos()), obj.dim()),
+                distance,
+            }
+        };
+
+        let static_hit = (!self.bvh_dirty)
+            .then(|| self.bvh.raycast_detailed(&ray_origin, &ray_dir))
+            .flatten()
+            .and_then(|(id, distance, face)| Some(hit(self.opaque.get(&id)?, distance, face)));
+
+        self.visible()
+            .filter(|obj| self.bvh_dirty || !self.bvh_ids.contains(&obj.id()))
+            .filter_map(|obj| {
+                let (distance, face) = Aabb::from_center(obj.pos(), obj.dim())
+                    .ray_intersection(&ray_origin, &ray_dir)?;
+                Some(hit(obj, distance, face))
+            })
+            .chain(static_hit)
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// rebuild the tree used by [`RawObjects::raycast`] over the static objects.
+    ///
+    /// Must be called after static geometry is added, moved, or removed.
+    pub fn rebuild_bvh(&mut self) {
+        let statics = self
+            .statics()
+            .map(|obj| (obj.id(), Aabb::from_center(obj.pos(), obj.dim())))
+            .collect::<Vec<_>>();
+
+        self.bvh_ids = statics.iter().map(|(id, _)| *id).collect();
+        self.bvh = Bvh::build(statics);
+        self.bvh_dirty = false;
+    }
+
+    /// rebuild the BVH if a static object has been changed (or removed) since the last rebuild.
+    ///
+    /// Cheap otherwise, so it's meant to be called every frame.
+    pub fn refresh_bvh(&mut self) {
+        if self.bvh_dirty {
+            self.rebuild_bvh()
+        }
+    }
+
+    /// return the id of the object closest to `point` among those satisfying `filter`.
+    ///
+    /// Ties go to the first object in iteration order.
+    pub fn find_nearest(&self, point: Vector, filter: impl Fn(&Object) -> bool) -> Option<Id> {
+        self.iter()
+            .filter(|obj| filter(obj))
+            .map(|obj| (obj.id(), obj.distance_sq_to(&point)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id)
+    }
+
+    /// return the id of the nearest static object hit by the ray (as of the last rebuild).
+    pub fn raycast(&self, ray_origin: Vector, ray_dir: Vector) -> Option<Id> {
+        self.bvh.raycast(&ray_origin, &ray_dir)
+    }
+
+    /// return the offset which pushes a player at `pos` (on the default layer) out of every
+    /// solid object it collides with.
+    pub fn resolve_player(&self, pos: Vector) -> Vector {
+        resolve_capsule(
+            &mut Capsule::player(pos),
+            self.colliders_for(CollisionFilter::DEFAULT),
+        )
+    }
+
+    /// return a stable hash of the synchronized state of every object (see [`state_hash`]).
+    pub fn state_hash(&self) -> u64 {
+        state_hash(self.iter().map(Object::data))
+    }
+
+    /// return an iterator of every light object
+    pub fn lights(&self) -> impl Iterator<Item = &Object> {
+        self.iter().filter(|o| o.is_light())
+    }
+
+    /// return the id of every object of the specified object type.
+    pub fn ids_of_kind(&self, kind: RawObjectDataUnit) -> Vec<Id> {
+        self.iter()
+            .filter(|o| o.data().kind() == kind)
+            .map(Object::id)
+            .collect()
+    }
+
+    /// return an iterator of every object (by ascending id if [`RawObjects::ordered`]).
+    pub fn iter(&self) -> impl Iterator<Item = &Object> {
+        let ordered = self
+            .order
+            .iter()
+            .flatten()
+            .filter_map(|id| self.opaque.get(id));
+        let unordered = self.order.is_none().then(|| self.opaque.values());
+
+        ordered.chain(unordered.into_iter().flatten())
+    }
+
+    /// return an iterator of every object that should be drawn.
+    pub fn visible(&self) -> impl Iterator<Item = &Object> {
+        self.iter().filter(|o| o.is_visible())
+    }
+
+    /// return an iterator of every object drawn into shadow depth passes.
+    pub fn shadow_casters(&self) -> impl Iterator<Item = &Object> {
+        self.visible().filter(|o| o.cast_shadow())
+    }
+
+    /// render every visible object from `cam` into a sub-region of the window.
+    ///
+    /// Only the depth of the region is cleared, so multiple viewports can share a frame.
+    pub fn draw_viewport(&self, gl: &Context, cam: &RawCamera, viewport: Viewport) {
+        let Viewport {
+            x,
+            y,
+            width,
+            height,
+        } = viewport;
+
+        unsafe {
+            gl.viewport(x, y, width, height);
+            gl.scissor(x, y, width, height);
+
+            gl.enable(SCISSOR_TEST);
+            gl.clear(DEPTH_BUFFER_BIT);
+        }
+
+        self.draw(gl, cam);
+
+        unsafe {
+            gl.disable(SCISSOR_TEST);
+        }
+    }
+
+    /// check that every object is consistent before the first draw.
+    pub fn validate(&self) -> std::result::Result<(), Vec<(Id, String)>> {
+        let problems = self
+            .iter()
+            .flat_map(|obj| obj.problems().into_iter().map(|p| (obj.id(), p)))
+            .collect::<Vec<_>>();
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// return the number of objects, including hidden ones.
+    pub fn len(&self) -> usize {
+        self.opaque.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.opaque.is_empty()
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    #[test]
+    fn cone_triangle_count() {
+        let (vertices, indices) = cone_mesh(8, true).unwrap();
+
+        // 8 sides + 8 cap triangles, in two fans
+        assert_eq!(triangle_count(TRIANGLE_FAN, &indices), 16);
+        assert_eq!(vertices.len(), (8 * 2 + 2) * 6);
+        assert!(cone_mesh(2, false).is_err());
+    }
+
+    #[test]
+    fn flat_axes_map_to_unit_center() {
+        let local = to_unit_space(&Vector::new(1.0, 0.0, -2.0), &Vector::new(2.0, 0.0, -4.0));
+
+        assert!(local.iter().all(|c| c.is_finite()));
+        assert_eq!(local, Vector::new(0.5, 0.0, -0.5));
+    }
+
+    #[test]
+    fn spawn_queue_respects_budget() {
+        // every reading of the clock is 1ms later than the previous one
+        let start = Instant::now();
+        let mut ticks = 0;
+        let now = || {
+            ticks += 1;
+            start + Duration::from_millis(ticks)
+        };
+
+        let mut queue = (0..10).collect::<VecDeque<_>>();
+        let mut spawned = Vec::new();
+
+        let handled = drain_within(&mut queue, Duration::from_millis(3), now, |i| {
+            spawned.push(i);
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(handled, 3);
+        assert_eq!(spawned, [0, 1, 2]);
+        assert_eq!(queue.len(), 7);
+        assert_eq!(queue.front(), Some(&3));
+    }
+
+    #[test]
+    fn snapshots_materialize_in_fixed_batches() {
+        let mut queue = (0..100).collect::<VecDeque<_>>();
+        let mut spawned = Vec::new();
+
+        let mut frames = Vec::new();
+        while !queue.is_empty() {
+            let handled = drain_batch(&mut queue, 20, |i| {
+                spawned.push(i);
+                Ok(())
+            });
+            frames.push(handled.unwrap());
+        }
+
+        assert_eq!(frames, [20; 5]);
+        assert_eq!(spawned, (0..100).collect::<Vec<_>>());
+        assert_eq!(drain_batch(&mut queue, 20, |_: i32| Ok(())).unwrap(), 0);
+    }
+
+    #[test]
+    fn normals_with_simple_program_are_rejected() {
+        assert!(matches!(
+            check_attributes(ProgramUnit::Simple, true),
+            Err(Error::AttributeMismatch {
+                program: ProgramUnit::Simple,
+                has_norms: true,
+            })
+        ));
+
+        assert!(check_attributes(ProgramUnit::Simple, false).is_ok());
+        assert!(check_attributes(ProgramUnit::Normal, true).is_ok());
+    }
+
+    #[test]
+    fn flat_cube_with_normal_program_is_flagged() {
+        // flat cubes have no normals, which the 'normal' program falls back from
+        assert!(check_attributes(ProgramUnit::Normal, false).is_ok());
+        assert!(reads_missing_normals(ProgramUnit::Normal, false));
+
+        assert!(!reads_missing_normals(ProgramUnit::Normal, true));
+        assert!(!reads_missing_normals(ProgramUnit::Simple, false));
+    }
+
+    #[test]
+    fn next_id_is_local() {
+        let objects = RawObjects::default();
+
+        assert_eq!(objects.next_id(), Some(LOCAL_IDS.start));
+    }
+
+    /// an object of `data` with made-up buffers, for what never reaches GL.
+    pub(crate) fn object(mut data: ObjectData) -> Object {
+        data.model_upt();
+
+        let handle = std::num::NonZeroU32::MIN;
+        let buffers = Buffers {
+            vao: NativeVertexArray(handle),
+            vbo: NativeBuffer(handle),
+            ebo: NativeBuffer(handle),
+            shared: false,
+        };
+        let elements = Elements {
+            mode: TRIANGLES,
+            element_type: UNSIGNED_BYTE,
+            len: 0,
+            index_size: 1,
+            triangles: 0,
+            has_norms: false,
+        };
+        Object::new(
+            Program::Simple(glow::NativeProgram(handle)),
+            buffers,
+            elements,
+            data,
+        )
+    }
+
+    pub(crate) fn basic(id: Id, pos: Vector) -> ObjectData {
+        let data = RawObjectData::Basic(BasicData::new(pos, Vector::repeat(1.0)));
+        ObjectData::new(id, Color::WHITE, data)
+    }
+
+    #[test]
+    fn position_and_normal_layout_is_interleaved() {
+        let mut obj = object(basic(LOCAL_IDS.start, Vector::zeros()));
+        obj.elements.has_norms = true;
+
+        let layout = obj.describe_layout();
+        assert_eq!(layout.stride, 24);
+        assert_eq!(
+            layout.attributes,
+            [
+                VertexAttribute {
+                    name: "pos",
+                    offset: 0,
+                    size: 3
+                },
+                VertexAttribute {
+                    name: "norm",
+                    offset: 12,
+                    size: 3
+                },
+            ]
+        );
+
+        obj.elements.has_norms = false;
+        let layout = obj.describe_layout();
+        assert_eq!(layout.stride, 12);
+        assert_eq!(layout.attribute("norm"), None);
+    }
+
+    #[test]
+    fn reshading_switches_vertex_counts() {
+        for (shading, expected) in [(Shading::Flat, 24), (Shading::Smooth, 8)] {
+            let (vertices, indices) = shading.cube_mesh();
+            let normals = vertices
+                .chunks_exact(6)
+                .map(|v| Vector::new(v[3], v[4], v[5]));
+
+            assert_eq!(vertices.len() / 6, expected);
+            assert_eq!(indices.len(), 36);
+            assert!(indices.iter().all(|&i| (i as usize) < expected));
+
+            // flat normals face an axis, smooth ones a corner
+            for n in normals {
+                assert!((n.norm() - 1.0).abs() < 1e-5);
+                let axes = n.iter().filter(|c| c.abs() > 1e-5).count();
+                assert_eq!(axes, if shading == Shading::Flat { 1 } else { 3 });
+            }
+        }
+        assert_ne!(Shading::Flat.geometry(), Shading::Smooth.geometry());
+    }
+
+    #[test]
+    fn baking_two_cubes_offsets_indices() {
+        let models = [Matrix::identity(), Matrix::new_translation(&Vector::x())];
+        let (vertices, indices) = bake_cubes(&models);
+
+        assert_eq!(vertices.len() / 6, 48);
+        assert_eq!(indices.len(), 72);
+        assert!(indices[..36].iter().all(|&i| i < 24));
+        assert!(indices[36..].iter().all(|&i| (24..48).contains(&i)));
+    }
+
+    #[test]
+    fn cubes_are_batched_by_appearance() {
+        let red = Color::new([1.0, 0.0, 0.0, 1.0], false);
+        let cubes = (0..48)
+            .map(|i| {
+                let mut data = basic(i, Vector::new(i as f32, 0.0, 0.0));
+                if i % 2 == 1 {
+                    data.set_color(red)
+                }
+                data
+            })
+            .collect::<Vec<_>>();
+
+        let batches = batch_by_appearance(&cubes);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0], (0..48).step_by(2).collect::<Vec<_>>());
+        assert_eq!(batches[1], (1..48).step_by(2).collect::<Vec<_>>());
+
+        // the material splits them just as well
+        let mut shiny = cubes[0];
+        shiny.set_id(48);
+        shiny.set_material(MaterialData::new(8.0, Color::WHITE));
+        assert_eq!(batch_by_appearance(cubes.iter().chain([&shiny])).len(), 3);
+    }
+
+    /// a scene of `objects`, none of which reach GL.
+    fn scene(objects: impl IntoIterator<Item = ObjectData>) -> RawObjects {
+        let mut scene = RawObjects::default();
+        for data in objects {
+            scene.add(object(data))
+        }
+        scene
+    }
+
+    #[test]
+    fn moving_the_hover_tints_only_the_hovered_object() {
+        let [a, b] = [LOCAL_IDS.start, LOCAL_IDS.start + 1];
+        let mut objects = scene([basic(a, Vector::zeros()), basic(b, Vector::x() * 4.0)]);
+        objects.rebuild_bvh();
+
+        let down = -Vector::z();
+        assert_eq!(objects.update_hover(Vector::z() * 8.0, down), Some(a));
+        assert_eq!(
+            objects.update_hover(Vector::new(4.0, 0.0, 8.0), down),
+            Some(b)
+        );
+
+        let highlight = |id| objects.opaque[&id].data().highlight().map(|c| c[0]);
+        assert_eq!(highlight(a), None);
+        assert_eq!(highlight(b), Some(RawObjects::HOVER_HIGHLIGHT[0]));
+        assert_eq!(objects.opaque[&b].data().raw_color()[..], Color::WHITE[..]);
+        assert!(!objects.bvh_dirty);
+    }
+
+    #[test]
+    fn hidden_objects_are_not_drawn_but_counted() {
+        let [a, b] = [LOCAL_IDS.start, LOCAL_IDS.start + 1];
+        let mut hidden = basic(b, Vector::x() * 4.0);
+        hidden.set_visible(false);
+        let objects = scene([basic(a, Vector::zeros()), hidden]);
+
+        assert_eq!(objects.visible().map(Object::id).collect::<Vec<_>>(), [a]);
+        assert_eq!(objects.len(), 2);
+    }
+
+    #[test]
+    fn projectiles_expire_after_their_lifetime() {
+        let id = LOCAL_IDS.start;
+        let raw_data = RawObjectData::Basic(BasicData::with_motion(
+            Vector::zeros(),
+            RawObjects::PROJECTILE_DIM,
+            Vector::x(),
+            Some(1.0),
+        ));
+        let mut objects = scene([ObjectData::new(id, Color::WHITE, raw_data)]);
+
+        // in steps no longer than the largest one
+        let steps = (1.1 / objects.max_dt()).ceil() as usize;
+        let dt = 1.1 / steps as f32;
+
+        let expired = (0..steps)
+            .flat_map(|_| objects.advance(dt))
+            .map(|obj| obj.id())
+            .collect::<Vec<_>>();
+        assert_eq!(expired, [id]);
+        assert!(objects.is_empty());
+    }
+
+    #[test]
+    fn resizing_keeps_the_buffers() {
+        let pos = Vector::new(1.0, 2.0, 3.0);
+        let mut obj = object(basic(LOCAL_IDS.start, pos));
+        let vao = obj.vao();
+
+        let size = |obj: &Object| {
+            let bounds = obj.data().bounds();
+            bounds.max - bounds.min
+        };
+        let before = size(&obj);
+
+        assert!(obj.data_mut().set_dim(Vector::repeat(2.0)));
+        assert_eq!(size(&obj), before * 2.0);
+        assert_eq!(obj.data().bounds().min + size(&obj) / 2.0, pos);
+        assert_eq!(obj.vao(), vao);
+    }
+
+    #[test]
+    fn resync_links_children_preceding_their_parents() {
+        let [child, parent, orphan] = [LOCAL_IDS.start, LOCAL_IDS.start + 1, LOCAL_IDS.start + 2];
+        let mut child_data = basic(child, Vector::zeros());
+        child_data.set_parent(Some(parent));
+        let mut orphan_data = basic(orphan, Vector::zeros());
+        orphan_data.set_parent(Some(Id::MAX));
+
+        let snapshot = [child_data, basic(parent, Vector::x()), orphan_data]
+            .map(|data| (Geometry::Cube, data));
+
+        // the two passes of `resync`, minus the buffers
+        let mut objects = scene(RawObjects::unlinked(&snapshot).map(|(_, data)| data));
+        assert!(objects.iter().all(|obj| obj.parent().is_none()));
+
+        let result = objects.link(&RawObjects::links(&snapshot));
+        assert!(matches!(
+            result,
+            Err(Error::MissingReferences(missing)) if missing == [(orphan, Id::MAX)]
+        ));
+        assert_eq!(
+            objects.children(parent).map(Object::id).collect::<Vec<_>>(),
+            [child]
+        );
+        assert_eq!(objects.len(), 3);
+    }
+
+    #[test]
+    fn stalls_advance_objects_by_max_dt_at_most() {
+        let id = LOCAL_IDS.start;
+        let raw_data = RawObjectData::Basic(BasicData::with_motion(
+            Vector::zeros(),
+            RawObjects::PROJECTILE_DIM,
+            Vector::x(),
+            Some(1.0),
+        ));
+        let mut objects = scene([ObjectData::new(id, Color::WHITE, raw_data)]);
+
+        // a 2 second hitch neither launches nor expires it
+        assert!(objects.advance(2.0).is_empty());
+        let basic = *objects.opaque[&id].basic_ref().unwrap();
+        assert_eq!(basic.pos().x, MAX_DT);
+        assert_eq!(basic.lifetime(), Some(1.0 - MAX_DT));
+    }
+
+    #[test]
+    fn spawning_then_removing_logs_both_in_order() {
+        let id = LOCAL_IDS.start;
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let mut objects = RawObjects::default();
+        let logged = events.clone();
+        objects.set_lifecycle_logger(Box::new(move |event| logged.lock().unwrap().push(event)));
+
+        objects.add(object(basic(id, Vector::zeros())));
+        objects.remove(id).unwrap();
+
+        let kind = RawObjectDataUnit::Basic;
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                LifecycleEvent::Spawned { id, kind },
+                LifecycleEvent::Removed {
+                    id,
+                    kind,
+                    reason: RemoveReason::Removed
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn find_nearest_respects_the_filter() {
+        let [a, b, c] = [LOCAL_IDS.start, LOCAL_IDS.start + 1, LOCAL_IDS.start + 2];
+        let objects = scene([
+            basic(a, Vector::new(1.0, 0.0, 0.0)),
+            basic(b, Vector::new(0.0, 3.0, 0.0)),
+            basic(c, Vector::new(0.0, 0.0, -6.0)),
+        ]);
+
+        assert_eq!(objects.find_nearest(Vector::zeros(), |_| true), Some(a));
+        assert_eq!(
+            objects.find_nearest(Vector::zeros(), |obj| obj.id() != a),
+            Some(b)
+        );
+        assert_eq!(
+            objects.find_nearest(Vector::new(0.0, 0.0, -4.0), |_| true),
+            Some(c)
+        );
+        assert_eq!(objects.find_nearest(Vector::zeros(), |_| false), None);
+    }
+
+    #[test]
+    fn masked_layers_never_collide() {
+        let ids = [0, 1, 2, 3].map(|i| LOCAL_IDS.start + i);
+        let [a, b, _, _] = ids;
+
+        // every object overlaps every other, so only the layers matter
+        let filters = [
+            (0b001, 0b010),
+            (0b010, 0b001),
+            (0b100, 0b100),
+            (0b001, 0b100),
+        ];
+        let objects = scene(ids.into_iter().zip(filters).map(|(id, (layer, mask))| {
+            let mut data = basic(id, Vector::zeros());
+            data.set_collision_layer(layer);
+            data.set_collision_mask(mask);
+            data
+        }));
+
+        // the last one only accepts the third, which ignores its layer
+        assert_eq!(objects.collisions(), [(a, b)]);
+    }
+
+    #[test]
+    fn waveforms_weigh_the_target_over_a_period() {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+
+        assert!(close(Waveform::Linear.weight(0.5, 1.0), 0.5));
+        assert!(close(Waveform::Linear.weight(3.0, 1.0), 1.0));
+
+        // a quarter of the way to the peak, which is halfway through
+        assert!(close(Waveform::Sine.weight(0.5, 2.0), 0.5));
+        assert!(close(Waveform::Sine.weight(1.0, 2.0), 1.0));
+        assert!(close(Waveform::Sine.weight(2.0, 2.0), 0.0));
+
+        assert_eq!(Waveform::Square.weight(0.25, 1.0), 0.0);
+        assert_eq!(Waveform::Square.weight(1.75, 1.0), 1.0);
+        assert!(!Waveform::Sine.is_finite() && !Waveform::Square.is_finite());
+    }
+
+    #[test]
+    fn light_tweens_sample_their_waveform() {
+        let id = LOCAL_IDS.start;
+        let mut light = basic(id, Vector::zeros());
+        light.set_color(Color::new([1.0, 1.0, 1.0, 1.0], true));
+        light.set_light_intensity(1.0);
+        let mut objects = scene([light]);
+
+        let red = Color::new([1.0, 0.0, 0.0, 1.0], true);
+        let sample = |objects: &RawObjects| {
+            let data = objects.opaque[&id].data();
+            (data.light_intensity(), data.raw_color()[1])
+        };
+
+        // halfway through a linear tween
+        assert!(objects.tween_light(id, 3.0, red, 1.0, Waveform::Linear));
+        for _ in 0..5 {
+            objects.update_light_tweens(0.1)
+        }
+        let (intensity, green) = sample(&objects);
+        assert!((intensity - 2.0).abs() < 1e-4 && (green - 0.5).abs() < 1e-4);
+
+        // a quarter through the period of a pulse (from where the linear one stopped)
+        assert!(objects.tween_light(id, 4.0, red, 0.8, Waveform::Sine));
+        for _ in 0..2 {
+            objects.update_light_tweens(0.1)
+        }
+        let (intensity, _) = sample(&objects);
+        assert!((intensity - 3.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn ids_of_kind_returns_exactly_that_kind() {
+        let player = |id| {
+            let data = RawObjectData::Player(PlayerData::new(Vector::zeros()));
+            ObjectData::new(id, Color::WHITE, data)
+        };
+        let objects = scene([
+            player(0),
+            basic(LOCAL_IDS.start, Vector::zeros()),
+            player(5),
+            basic(LOCAL_IDS.start + 1, Vector::zeros()),
+        ]);
+
+        let mut players = objects.ids_of_kind(RawObjectDataUnit::Player);
+        players.sort_unstable();
+        assert_eq!(players, [0, 5]);
+
+        let mut basics = objects.ids_of_kind(RawObjectDataUnit::Basic);
+        basics.sort_unstable();
+        assert_eq!(basics, [LOCAL_IDS.start, LOCAL_IDS.start + 1]);
+    }
+
+    #[test]
+    fn world_bounds_enclose_every_object_tightly() {
+        assert_eq!(RawObjects::default().world_bounds(), None);
+
+        let objects = scene(
+            [
+                Vector::new(-3.0, 0.0, 1.0),
+                Vector::new(2.0, 5.0, 0.0),
+                Vector::new(0.0, -1.0, -4.0),
+            ]
+            .into_iter()
+            .zip(LOCAL_IDS)
+            .map(|(pos, id)| basic(id, pos)),
+        );
+
+        assert_eq!(
+            objects.world_bounds(),
+            Some((Vector::new(-4.0, -2.0, -5.0), Vector::new(3.0, 6.0, 2.0)))
+        );
+    }
+
+    #[test]
+    fn merging_remaps_parents() {
+        let [a, b] = [LOCAL_IDS.start, LOCAL_IDS.start + 1];
+        let mut objects = scene([basic(a, Vector::zeros())]);
+
+        let mut child = basic(b, Vector::y());
+        child.set_parent(Some(a));
+        let other = scene([basic(a, Vector::x()), child]);
+
+        let (mapping, unfit) = objects.merge_with(other);
+        assert!(unfit.is_empty());
+
+        // the parent collided, so it moved (and so did its child, into its place)
+        let parent = mapping[&a];
+        assert_eq!(parent, b);
+        assert_eq!(objects.get_mut(mapping[&b]).unwrap().parent(), Some(parent));
+    }
+
+    #[test]
+    fn lod_selection_straddles_thresholds_with_hysteresis() {
+        let mesh = object(basic(0, Vector::zeros()));
+        let lods = LodMeshes::new(vec![(20.0, mesh), (10.0, mesh)]);
+
+        assert_eq!(lods.select(5.0), None);
+        assert_eq!(lods.select(10.0), Some(0));
+        assert_eq!(lods.select(19.9), Some(0));
+        assert_eq!(lods.select(20.1), Some(1));
+
+        // kept until well within the threshold
+        assert_eq!(lods.select(19.0), Some(1));
+        assert_eq!(lods.select(17.9), Some(0));
+        assert_eq!(lods.select(9.5), Some(0));
+        assert_eq!(lods.select(8.9), None);
     }
 }
@@ -1,125 +1,867 @@
 use crate::*;
 use crossbeam_channel::Receiver;
-use glow::{Context, HasContext, NativeProgram, COLOR_BUFFER_BIT, DEPTH_BUFFER_BIT};
-use std::io::{stdout, Write};
+use glow::{
+    Context, HasContext, BACK, CCW, COLOR_BUFFER_BIT, CW, DEPTH_BUFFER_BIT, FRAMEBUFFER_SRGB,
+    FRONT, FRONT_AND_BACK, LEQUAL, LESS, LINE, POLYGON_MODE, POLYGON_OFFSET_LINE, TEXTURE0,
+    TEXTURE_2D,
+};
+use sdl2::video::Window;
+use std::{
+    fmt::Debug,
+    io::{stdout, Write},
+    time::Instant,
+};
 use sync_select::*;
 
-unsafe fn setup_simple_obj(
+/// Where the (linear) output of the shaders is converted into sRGB.
+///
+/// At most one of the conversions is active, so colors are never corrected twice.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ColorManagement {
+    srgb_framebuffer: bool,
+    shader_gamma: bool,
+}
+
+impl ColorManagement {
+    pub const fn srgb_framebuffer(&self) -> bool {
+        self.srgb_framebuffer
+    }
+
+    pub const fn shader_gamma(&self) -> bool {
+        self.shader_gamma
+    }
+
+    /// Let the hardware convert on write (disables the gamma step of the shaders).
+    pub fn set_srgb_framebuffer(&mut self, enabled: bool) {
+        self.srgb_framebuffer = enabled;
+        if enabled {
+            self.shader_gamma = false
+        }
+    }
+
+    /// Convert at the end of the shaders (disables the sRGB framebuffer).
+    pub fn set_shader_gamma(&mut self, enabled: bool) {
+        self.shader_gamma = enabled;
+        if enabled {
+            self.srgb_framebuffer = false
+        }
+    }
+
+    /// Toggle `GL_FRAMEBUFFER_SRGB` to match.
+    pub fn apply(&self, gl: &Context) {
+        unsafe {
+            if self.srgb_framebuffer {
+                gl.enable(FRAMEBUFFER_SRGB)
+            } else {
+                gl.disable(FRAMEBUFFER_SRGB)
+            }
+        }
+    }
+}
+
+/// Upload the uniforms shared by every object of a program (once per program bind).
+pub unsafe fn setup_program(
     gl: &Context,
-    native: NativeProgram,
-    model: &[f32],
-    view: &[f32],
-    projection: &[f32],
-    obj_col: &[f32],
+    program: Program,
+    cam: &RawCamera,
+    color: ColorManagement,
+    clip: Option<ClipPlane>,
+    light_pos: &[f32],
+    light_col: &[f32],
 ) {
-    // model matrix
-    gl.uniform_matrix_4_f32_slice(
-        gl.get_uniform_location(native, "model").as_ref(),
-        false,
-        model,
+    let native = program.native();
+
+    // cross-section (a zero plane keeps everything)
+    gl.uniform_4_f32_slice(
+        gl.get_uniform_location(native, "clip_plane").as_ref(),
+        &clip.map_or([0.0; 4], |plane| plane.to_vec4()),
+    );
+
+    // manual gamma correction
+    gl.uniform_1_i32(
+        gl.get_uniform_location(native, "shader_gamma").as_ref(),
+        color.shader_gamma() as i32,
     );
 
     // view matrix
     gl.uniform_matrix_4_f32_slice(
         gl.get_uniform_location(native, "view").as_ref(),
         false,
-        view,
+        cam.view().as_slice(),
     );
 
     // projection matrix
     gl.uniform_matrix_4_f32_slice(
         gl.get_uniform_location(native, "proj").as_ref(),
         false,
-        projection,
+        cam.projection().as_slice(),
     );
 
-    // object color
-    gl.uniform_4_f32_slice(gl.get_uniform_location(native, "obj_col").as_ref(), obj_col);
+    // logarithmic depth
+    gl.uniform_1_i32(
+        gl.get_uniform_location(native, "use_log_depth").as_ref(),
+        cam.is_log_depth() as i32,
+    );
+    gl.uniform_1_f32(
+        gl.get_uniform_location(native, "log_depth_coef").as_ref(),
+        cam.log_depth_coef(),
+    );
+
+    // 'normal' (ambient + diffuse + specular) shading
+    if program.kind() == ProgramUnit::Normal {
+        // camera position
+        gl.uniform_3_f32_slice(
+            gl.get_uniform_location(native, "view_pos").as_ref(),
+            cam.pos().as_slice(),
+        );
+
+        // light position attibute
+        gl.uniform_3_f32_slice(
+            gl.get_uniform_location(native, "light_pos").as_ref(),
+            light_pos,
+        );
+
+        // light color attribute
+        gl.uniform_3_f32_slice(
+            gl.get_uniform_location(native, "light_col").as_ref(),
+            light_col,
+        );
+    }
 }
 
-unsafe fn setup_normal_obj(
-    gl: &Context,
-    native: NativeProgram,
-    view_pos: &[f32],
-    light_pos: &[f32],
-    light_col: &[f32],
-) {
-    // camera position
-    gl.uniform_3_f32_slice(
-        gl.get_uniform_location(native, "view_pos").as_ref(),
-        view_pos,
+/// Upload (or disable) the shadow mapping of a program using the 'normal' shading.
+///
+/// The shadow map is never sampled while rendering into it (i.e., in depth-only passes).
+unsafe fn setup_shadows(gl: &Context, program: Program, shadows: Option<&Shadows>) {
+    if program.kind() != ProgramUnit::Normal {
+        return;
+    }
+    let native = program.native();
+
+    gl.uniform_1_i32(
+        gl.get_uniform_location(native, "use_shadows").as_ref(),
+        shadows.is_some() as i32,
     );
 
-    // light position attibute
-    gl.uniform_3_f32_slice(
-        gl.get_uniform_location(native, "light_pos").as_ref(),
-        light_pos,
+    let Some(shadows) = shadows else {
+        return;
+    };
+
+    gl.uniform_matrix_4_f32_slice(
+        gl.get_uniform_location(native, "light_space").as_ref(),
+        false,
+        shadows.light_space.as_slice(),
+    );
+    gl.uniform_1_f32(
+        gl.get_uniform_location(native, "shadow_bias").as_ref(),
+        shadows.settings.bias,
+    );
+    gl.uniform_1_f32(
+        gl.get_uniform_location(native, "shadow_normal_bias")
+            .as_ref(),
+        shadows.settings.normal_bias,
     );
 
-    // light color attribute
-    gl.uniform_3_f32_slice(
-        gl.get_uniform_location(native, "light_col").as_ref(),
-        light_col,
+    gl.active_texture(TEXTURE0 + ShadowMap::UNIT);
+    gl.bind_texture(TEXTURE_2D, Some(shadows.texture));
+    gl.uniform_1_i32(
+        gl.get_uniform_location(native, "shadow_map").as_ref(),
+        ShadowMap::UNIT as i32,
     );
 }
 
-unsafe fn render_obj(
-    gl: &Context,
-    obj: &Object,
-    model: &[f32],
-    view: &[f32],
-    projection: &[f32],
-    view_pos: &[f32],
-    light_pos: &[f32],
-    light_col: &[f32],
-) {
-    // current program
+/// Upload the uniforms specific to `obj` (resolving its color through `palette`).
+unsafe fn setup_obj(gl: &Context, obj: &Object, palette: &Palette) {
     let program = obj.program();
     let native = program.native();
-    gl.use_program(Some(native));
 
-    // every object inherits the attributes of a 'simple' shader
-    setup_simple_obj(gl, native, model, view, projection, obj.color());
+    // model matrix
+    gl.uniform_matrix_4_f32_slice(
+        gl.get_uniform_location(native, "model").as_ref(),
+        false,
+        obj.model().as_slice(),
+    );
+
+    // object color
+    gl.uniform_4_f32_slice(
+        gl.get_uniform_location(native, "obj_col").as_ref(),
+        &obj.resolved_color(palette),
+    );
+    let tint = obj
+        .highlight()
+        .map_or(obj.tint(), |highlight| obj.tint().multiply(&highlight));
+    gl.uniform_3_f32_slice(gl.get_uniform_location(native, "tint").as_ref(), &tint[..3]);
 
-    // 'normal' (ambient + diffuse + specular) shading
+    // material attributes
     if program.kind() == ProgramUnit::Normal {
-        setup_normal_obj(gl, native, view_pos, light_pos, light_col);
+        // normals to world space (precomputed inverse-transpose)
+        gl.uniform_matrix_3_f32_slice(
+            gl.get_uniform_location(native, "normal_matrix").as_ref(),
+            false,
+            obj.normal_matrix().as_slice(),
+        );
+
+        let material = obj.material();
+
+        gl.uniform_1_f32(
+            gl.get_uniform_location(native, "shininess").as_ref(),
+            material.shininess(),
+        );
+        gl.uniform_3_f32_slice(
+            gl.get_uniform_location(native, "spec_col").as_ref(),
+            &material.specular()[..3],
+        );
+
+        // self-illumination
+        gl.uniform_3_f32_slice(
+            gl.get_uniform_location(native, "emissive").as_ref(),
+            &obj.emissive()[..3],
+        );
+
+        gl.uniform_1_i32(
+            gl.get_uniform_location(native, "receive_shadow").as_ref(),
+            obj.receive_shadow() as i32,
+        );
+    }
+}
+
+/// Point the (constant) normal of a mesh without normals at `light_pos`.
+///
+/// The VAO doesn't enable the `norm` attribute, so the generic value is read instead
+/// of garbage, rendering the object fully lit rather than black.
+unsafe fn fallback_normal(gl: &Context, obj: &Object, light_pos: &Vector) {
+    let native = obj.program().native();
+    let Some(location) = gl.get_attrib_location(native, "norm") else {
+        return;
+    };
+
+    // into model space (the shader applies the inverse-transpose)
+    let to_light = light_pos - obj.bounding_sphere().0;
+    let norm = obj.model().fixed_view::<3, 3>(0, 0).transpose() * to_light;
+    gl.vertex_attrib_3_f32(location, norm.x, norm.y, norm.z);
+}
+
+/// Render `obj` with the geometry of `mesh`, expecting its program to be in use.
+///
+/// Returns the number of draw calls (two for [`is_two_pass`] objects).
+unsafe fn render_obj(
+    gl: &Context,
+    obj: &Object,
+    mesh: &Object,
+    light_pos: &Vector,
+    palette: &Palette,
+    two_pass: bool,
+) -> usize {
+    setup_obj(gl, obj, palette);
+
+    if obj.program().kind() == ProgramUnit::Normal && !mesh.has_norms() {
+        fallback_normal(gl, obj, light_pos)
+    }
+
+    if two_pass {
+        // the far (back) faces, then the near (front) faces over them
+        for cull in [FRONT, BACK] {
+            gl.cull_face(cull);
+            draw_mesh(gl, obj, mesh);
+        }
+        gl.cull_face(BACK);
+        2
+    } else {
+        draw_mesh(gl, obj, mesh);
+        1
+    }
+}
+
+/// Whether `obj` is drawn in two passes (see [`ObjectData::two_pass_transparency`]).
+fn is_two_pass(obj: &Object) -> bool {
+    obj.two_pass_transparency() && obj.alpha() < 1.0
+}
+
+/// Redraw `mesh` as lines of `color` on top of the fill of `obj` (see [`render_obj`]).
+///
+/// The lines are pulled towards the camera so they win the depth test against their
+/// own faces (logarithmic depth overrides the offset, leaving only the `LEQUAL` test).
+unsafe fn render_wire_overlay(gl: &Context, obj: &Object, mesh: &Object, color: Color) {
+    let native = obj.program().native();
+
+    // unlit: the lit color is zeroed and the overlay is emitted instead
+    if obj.program().kind() == ProgramUnit::Normal {
+        gl.uniform_4_f32_slice(
+            gl.get_uniform_location(native, "obj_col").as_ref(),
+            &[0.0, 0.0, 0.0, color.alpha()],
+        );
+        gl.uniform_3_f32_slice(
+            gl.get_uniform_location(native, "emissive").as_ref(),
+            &color[..3],
+        );
+    } else {
+        gl.uniform_4_f32_slice(gl.get_uniform_location(native, "obj_col").as_ref(), &color);
+    }
+    gl.uniform_3_f32_slice(gl.get_uniform_location(native, "tint").as_ref(), &[1.0; 3]);
+
+    // (front, back)
+    let mut mode = [0; 2];
+    gl.get_parameter_i32_slice(POLYGON_MODE, &mut mode);
+
+    gl.polygon_mode(FRONT_AND_BACK, LINE);
+    gl.enable(POLYGON_OFFSET_LINE);
+    gl.polygon_offset(-1.0, -1.0);
+    gl.depth_func(LEQUAL);
+
+    draw_mesh(gl, obj, mesh);
+
+    gl.depth_func(LESS);
+    gl.disable(POLYGON_OFFSET_LINE);
+    gl.polygon_mode(FRONT_AND_BACK, mode[0] as u32);
+}
+
+/// Draw the elements of `mesh` with the winding of `obj`.
+unsafe fn draw_mesh(gl: &Context, obj: &Object, mesh: &Object) {
+    // mirrored models invert the winding order (keep culling correct)
+    let flipped = obj.flips_winding();
+
+    if flipped {
+        gl.front_face(CW);
     }
 
     // bind then render
-    gl.bind_vertex_array(Some(obj.vao()));
-    gl.draw_elements(obj.mode(), obj.len(), obj.element_type(), 0);
+    gl.bind_vertex_array(Some(mesh.vao()));
+    gl.draw_elements(mesh.mode(), mesh.len(), mesh.element_type(), 0);
 
     // clean up
     gl.bind_vertex_array(None);
-    gl.use_program(None);
+
+    if flipped {
+        gl.front_face(CCW);
+    }
+}
+
+/// Whether any of `obj` may be visible (the cheap sphere test first, then the tighter box).
+///
+/// Custom geometry may exceed the unit bounds, so it's never culled.
+fn is_in_frustum(frustum: &Frustum, obj: &Object) -> bool {
+    if obj.geometry() == Geometry::Custom {
+        return true;
+    }
+    let (center, radius) = obj.bounding_sphere();
+    frustum.intersects_sphere(&center, radius) && frustum.intersects_aabb(&obj.bounds())
 }
 
-pub fn display(gl: &Context, window: &Window, cam: &RawCamera, objects: &RawObjects) {
-    unsafe {
-        gl.clear_color(0.1, 0.1, 0.1, 1.0);
-        gl.clear(COLOR_BUFFER_BIT | DEPTH_BUFFER_BIT);
+type SortKeyFn = dyn Fn(&Object, &Vector) -> u64 + Send + Sync;
 
-        // camera attributes
-        let view = cam.view().as_slice();
-        let projection = cam.projection().as_slice();
-        let view_pos = cam.pos().as_slice();
+/// The order objects of the same priority are drawn in (ascending), given the eye position.
+///
+/// Objects sharing a program are only grouped while consecutive, so a key that ignores
+/// programs (e.g., distance) trades program binds for its order.
+pub struct DrawSortKey(Box<SortKeyFn>);
 
-        // light attributes
-        let light = objects.lights().next().unwrap(); /////////////////////////////////////////// TODO
-        let light_pos = light.pos().as_slice();
-        let light_col = &light.color()[..3];
+impl DrawSortKey {
+    pub fn new(f: impl Fn(&Object, &Vector) -> u64 + Send + Sync + 'static) -> Self {
+        Self(Box::new(f))
+    }
 
-        // render objects here (light obj last)
-        objects.iter().for_each(|obj| {
-            let model = obj.model().as_slice();
+    /// Batch by program (the default).
+    pub fn by_program() -> Self {
+        Self::new(|obj, _| obj.program().native().0.get() as u64)
+    }
 
-            render_obj(
-                gl, obj, model, view, projection, view_pos, light_pos, light_col,
-            );
+    /// Nearest first (fewest overdrawn fragments for opaque objects).
+    pub fn front_to_back() -> Self {
+        Self::new(Self::distance_bits)
+    }
+
+    /// Farthest first (blending order for transparent objects).
+    pub fn back_to_front() -> Self {
+        Self::new(|obj, eye| u64::MAX - Self::distance_bits(obj, eye))
+    }
+
+    pub fn key(&self, obj: &Object, eye: &Vector) -> u64 {
+        (self.0)(obj, eye)
+    }
+
+    /// The squared distance to the center of `obj` (the bits of a non-negative float order alike).
+    fn distance_bits(obj: &Object, eye: &Vector) -> u64 {
+        (obj.bounding_sphere().0 - eye).norm_squared().to_bits() as u64
+    }
+}
+
+impl Debug for DrawSortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DrawSortKey")
+    }
+}
+
+/// Sort `objects` into the order they're drawn in (by priority, then by `key` as seen from `eye`).
+pub fn draw_order<'a>(
+    objects: impl Iterator<Item = &'a Object>,
+    key: Option<&DrawSortKey>,
+    eye: &Vector,
+) -> Vec<&'a Object> {
+    let mut objects = objects.collect::<Vec<_>>();
+
+    match key {
+        Some(key) => objects.sort_by_cached_key(|obj| (obj.priority(), key.key(obj, eye))),
+        None => objects.sort_by_key(|obj| (obj.priority(), obj.program().native())),
+    }
+    objects
+}
+
+/// Statistics of a single [`RawObjects::draw`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RenderStats {
+    pub draw_calls: usize,
+    pub program_binds: usize,
+    pub triangles: usize,
+    /// Only measured with a [`GpuTimer`] (see [`RawObjects::enable_gpu_timer`]).
+    pub pass_times: PassTimes,
+}
+
+impl RawObjects {
+    /// Render every visible object into the current framebuffer region.
+    ///
+    /// Objects are grouped by program, so each program is bound (and its shared
+    /// uniforms uploaded) once, leaving only per-object uniforms and VAOs to change.
+    /// Groups are split by [`ObjectData::priority`] (ascending) when they differ.
+    pub fn draw(&self, gl: &Context, cam: &RawCamera) -> RenderStats {
+        let frustum = cam.frustum();
+
+        let stats = self.timed(gl, RenderPass::Opaque, || {
+            debug_group(gl, "opaque", || {
+                self.draw_objects(
+                    gl,
+                    cam,
+                    self.visible().filter(|obj| is_in_frustum(&frustum, obj)),
+                    true,
+                )
+            })
+        });
+        gl_report(gl, "opaque pass");
+        stats
+    }
+
+    /// Render `ghosts` over the scene (lit like it, but never part of it).
+    pub fn draw_ghosts(
+        &self,
+        gl: &Context,
+        cam: &RawCamera,
+        ghosts: &[GhostObject],
+    ) -> RenderStats {
+        let frustum = cam.frustum();
+
+        let stats = debug_group(gl, "ghosts", || {
+            self.draw_objects(
+                gl,
+                cam,
+                ghosts
+                    .iter()
+                    .map(GhostObject::object)
+                    .filter(|obj| is_in_frustum(&frustum, obj)),
+                true,
+            )
+        });
+        gl_report(gl, "ghost pass");
+        stats
+    }
+
+    /// Render only the depth of every shadow caster (e.g., from the light's point of view).
+    pub fn draw_depth_only(&self, gl: &Context, cam: &RawCamera) -> RenderStats {
+        unsafe { gl.color_mask(false, false, false, false) }
+        let stats = self.timed(gl, RenderPass::Depth, || {
+            debug_group(gl, "depth", || {
+                self.draw_objects(gl, cam, self.shadow_casters(), false)
+            })
         });
+        unsafe { gl.color_mask(true, true, true, true) }
+        gl_report(gl, "depth pass");
+        stats
+    }
+
+    /// Time `pass` on the GPU (if enabled), recording it into the stats of `f`.
+    fn timed(
+        &self,
+        gl: &Context,
+        pass: RenderPass,
+        f: impl FnOnce() -> RenderStats,
+    ) -> RenderStats {
+        let Some(timer) = self.gpu_timer() else {
+            return f();
+        };
+
+        timer.begin(gl, pass);
+        let mut stats = f();
+        stats.pass_times.set(pass, timer.end(gl, pass));
+        stats
+    }
+
+    fn draw_objects<'a>(
+        &self,
+        gl: &Context,
+        cam: &RawCamera,
+        objects: impl Iterator<Item = &'a Object>,
+        color_pass: bool,
+    ) -> RenderStats {
+        let mut stats = RenderStats::default();
+
+        let objects = draw_order(objects, self.draw_sort_key(), cam.pos());
+
+        unsafe {
+            // light attributes
+            let light = self.lights().next().unwrap(); /////////////////////////////////////////// TODO
+            let light_pos = light.pos();
+            let light_col = &light.light_color();
+
+            for group in objects.chunk_by(|a, b| {
+                a.priority() == b.priority() && a.program().native() == b.program().native()
+            }) {
+                let program = group[0].program();
+                gl.use_program(Some(program.native()));
+                setup_program(
+                    gl,
+                    program,
+                    cam,
+                    self.color(),
+                    self.clip_plane(),
+                    light_pos.as_slice(),
+                    light_col,
+                );
+                setup_shadows(gl, program, self.shadows().filter(|_| color_pass));
+                gl_report(gl, "setup_program");
+                stats.program_binds += 1;
+
+                for obj in group {
+                    let distance = (obj.bounding_sphere().0 - cam.pos()).norm();
+                    let mesh = self.lod_mesh(obj, distance);
+                    let two_pass = color_pass && is_two_pass(obj);
+                    let draws = render_obj(gl, obj, mesh, light_pos, self.draw_palette(), two_pass);
+
+                    if let Err(e) = gl_check(gl, "draw") {
+                        error!("[{}] {}", obj.id(), e)
+                    }
+
+                    stats.draw_calls += draws;
+                    stats.triangles += mesh.triangles() * draws;
+
+                    if let Some(color) = obj.wire_overlay().filter(|_| color_pass) {
+                        render_wire_overlay(gl, obj, mesh, color);
+                        stats.draw_calls += 1;
+                    }
+                }
+            }
+
+            gl.use_program(None);
+        }
+        stats
+    }
+}
+
+/// Drives the frames of the window: advancing the scene between them and presenting them.
+pub struct Renderer {
+    window: Window,
+    last_frame: Instant,
+    day_length: Option<f32>,
+    debug: DebugLines,
+    /// The 'simple' program (debug lines and the overlay are drawn with).
+    simple: Program,
+    crosshair: ScreenObject,
+    show_bounds: bool,
+    fxaa: Option<FxaaPass>,
+    scaled: Option<ScaledTarget>,
+    screenshot: bool,
+    stepper: DebugStepper,
+    /// The path of the camera being recorded, and its duration so far.
+    recording: Option<(Clip, f32)>,
+    ghosts: Vec<GhostObject>,
+}
+
+impl Renderer {
+    /// The color of the outlines of [`Renderer::toggle_bounds`].
+    pub const BOUNDS_COLOR: Color = Color::new([0.2, 1.0, 0.2, 1.0], true);
+
+    /// The crosshair marking the center of the window (what's hovered).
+    pub const CROSSHAIR_SIZE: f32 = 16.0;
+    pub const CROSSHAIR_THICKNESS: f32 = 0.125;
+    pub const CROSSHAIR_COLOR: Color = Color::new([1.0, 1.0, 1.0, 0.8], false);
+
+    /// The look of the ghosts of [`Renderer::toggle_recording`] (sized like a player).
+    pub const GHOST_COLOR: Color = Color::new([0.6, 0.8, 1.0, 1.0], false);
+    pub const GHOST_DIM: Vector = Vector::new(
+        Capsule::PLAYER_RADIUS,
+        Capsule::PLAYER_HEIGHT / 2.0,
+        Capsule::PLAYER_RADIUS,
+    );
+
+    /// The file of [`Renderer::request_screenshot`] (overwritten every time).
+    pub const SCREENSHOT_PATH: &str = "screenshot.ppm";
+
+    pub fn new(gl: &Context, window: Window, programs: &Shaders) -> Result<Self> {
+        Ok(Self {
+            window,
+            last_frame: Instant::now(),
+            day_length: None,
+            debug: DebugLines::new(gl)?,
+            simple: programs.simple(),
+            crosshair: ScreenObject::crosshair(
+                gl,
+                Self::CROSSHAIR_SIZE,
+                Self::CROSSHAIR_THICKNESS,
+                Self::CROSSHAIR_COLOR,
+            )?,
+            show_bounds: false,
+            fxaa: None,
+            scaled: None,
+            screenshot: false,
+            stepper: DebugStepper::default(),
+            recording: None,
+            ghosts: Vec::new(),
+        })
+    }
+
+    /// Move the sun over a day of `seconds` (see [`RawObjects::set_time_of_day`]).
+    pub fn set_day_length(&mut self, seconds: Option<f32>) {
+        self.day_length = seconds.filter(|seconds| *seconds > 0.0)
+    }
+
+    /// Freeze (or resume) everything local to the client, returning whether it's frozen.
+    ///
+    /// Rendering and the camera carry on regardless.
+    pub fn toggle_pause(&mut self) -> bool {
+        if self.stepper.is_paused() {
+            self.stepper.resume()
+        } else {
+            self.stepper.pause()
+        }
+        self.stepper.is_paused()
+    }
+
+    /// Advance the frozen objects by a single step on the next frame.
+    pub fn step_once(&mut self) {
+        self.stepper.step_once()
+    }
+
+    /// Start recording the path of the camera, or stop and replay it with a looping ghost
+    /// (of `program`), returning whether it's recording.
+    pub fn toggle_recording(&mut self, gl: &Context, program: Program) -> Result<bool> {
+        let Some((clip, _)) = self.recording.take() else {
+            self.recording = Some(Default::default());
+            return Ok(true);
+        };
+        if clip.is_empty() {
+            return Ok(false);
+        }
+
+        let data = ObjectData::new(
+            0,
+            Self::GHOST_COLOR,
+            RawObjectData::Basic(BasicData::new(Vector::zeros(), Self::GHOST_DIM)),
+        );
+        let obj = Object::create_with(gl, program, data)?;
+
+        let mut ghost = GhostObject::new(obj, clip, GhostObject::ALPHA);
+        ghost.set_looping(true);
+        self.ghosts.push(ghost);
+        Ok(false)
+    }
+
+    /// Show (or hide) the bounds of every visible object, returning whether they're shown.
+    pub fn toggle_bounds(&mut self) -> bool {
+        self.show_bounds = !self.show_bounds;
+        self.show_bounds
+    }
+
+    /// Enable (or disable) anti-aliasing with an [`FxaaPass`], returning whether it's enabled.
+    pub fn toggle_fxaa(&mut self, gl: &Context) -> Result<bool> {
+        match self.fxaa.take() {
+            Some(fxaa) => fxaa.delete(gl),
+            None => {
+                let (w, h) = self.window.drawable_size();
+                self.fxaa = Some(FxaaPass::new(gl, w as i32, h as i32)?)
+            }
+        }
+        Ok(self.fxaa.is_some())
+    }
+
+    /// The fraction of the window resolution the scene is rendered at.
+    pub fn render_scale(&self) -> f32 {
+        self.scaled.as_ref().map_or(1.0, ScaledTarget::scale)
+    }
+
+    /// Render the scene at `scale` (clamped) of the window resolution, then upscale it
+    /// (see [`ScaledTarget`]). At a scale of 1 it's rendered directly again.
+    pub fn set_render_scale(&mut self, gl: &Context, scale: f32) -> Result {
+        let scale = ScaledTarget::clamp_scale(scale);
+
+        if scale == 1.0 {
+            if let Some(scaled) = self.scaled.take() {
+                scaled.delete(gl)
+            }
+            return Ok(());
+        }
+
+        match &mut self.scaled {
+            Some(scaled) => scaled.set_scale(gl, scale),
+            None => {
+                let (w, h) = self.window.drawable_size();
+                self.scaled = Some(ScaledTarget::new(gl, w as i32, h as i32, scale)?)
+            }
+        }
+        Ok(())
+    }
+
+    /// Capture the next frame into [`Renderer::SCREENSHOT_PATH`].
+    pub fn request_screenshot(&mut self) {
+        self.screenshot = true
+    }
+
+    /// Follow the new size of the window.
+    pub fn resize(&mut self, gl: &Context, width: i32, height: i32) {
+        if let Some(fxaa) = &mut self.fxaa {
+            fxaa.resize(gl, width, height)
+        }
+        if let Some(scaled) = &mut self.scaled {
+            scaled.resize(gl, width, height)
+        }
+    }
+
+    /// Start a frame, returning the time (in seconds) since the previous one.
+    pub fn tick(&mut self) -> f32 {
+        let now = Instant::now();
+        let dt = now - self.last_frame;
+        self.last_frame = now;
+        dt.as_secs_f32()
+    }
+
+    /// Advance everything local to the client by `dt` seconds, in fixed steps (none while
+    /// paused, see [`Renderer::toggle_pause`]).
+    ///
+    /// Objects advance by [`RawObjects::max_dt`] at most, so a stall doesn't launch them.
+    pub fn update(&mut self, gl: &Context, cam: &RawCamera, objects: &mut RawObjects, dt: f32) {
+        let (steps, _) = self.stepper.advance(Duration::from_secs_f32(dt));
+        let step = self.stepper.step().as_secs_f32();
+
+        for _ in 0..steps {
+            if let Some(day_length) = self.day_length {
+                objects.set_time_of_day(objects.time_of_day() + step / day_length)
+            }
+
+            objects.update(step);
+            objects.update_light_tweens(step);
+            objects.update_projectiles(gl, step);
+            objects.update_despawns(gl, step);
+        }
+        if steps == 0 {
+            return;
+        }
+        let elapsed = step * steps as f32;
+
+        for ghost in &mut self.ghosts {
+            ghost.advance(elapsed)
+        }
+        if let Some((clip, time)) = &mut self.recording {
+            let attr = cam.attr();
+
+            *time += elapsed;
+            clip.record(
+                *time,
+                attr.eye,
+                UnitQuaternion::face_towards(&attr.target, &attr.up),
+            );
+        }
+    }
+
+    /// Render a frame and present it.
+    pub fn display(&mut self, gl: &Context, cam: &RawCamera, objects: &RawObjects) {
+        if let Some(fxaa) = &self.fxaa {
+            fxaa.begin(gl)
+        }
+        if let Some(scaled) = &self.scaled {
+            scaled.begin(gl)
+        }
+
+        unsafe {
+            gl.clear_color(0.1, 0.1, 0.1, 1.0);
+            gl.clear(COLOR_BUFFER_BIT | DEPTH_BUFFER_BIT);
+        }
+
+        objects.draw(gl, cam);
+
+        if !self.ghosts.is_empty() {
+            objects.draw_ghosts(gl, cam, &self.ghosts);
+        }
+
+        if self.show_bounds {
+            self.debug.clear();
+
+            for obj in objects.visible() {
+                self.debug.aabb(&obj.data().bounds(), Self::BOUNDS_COLOR)
+            }
+            self.debug.draw(gl, cam, objects.color(), self.simple, 1.0);
+        }
+
+        // upscaled before anti-aliasing, which then covers the whole window
+        if let Some(scaled) = &self.scaled {
+            scaled.end_and_upscale(gl, self.fxaa.as_ref().map(FxaaPass::framebuffer))
+        }
+        if let Some(fxaa) = &self.fxaa {
+            fxaa.end_and_resolve(gl)
+        }
+
+        // over the final image, so it's neither scaled nor filtered
+        let (w, h) = self.window.drawable_size();
+        self.crosshair
+            .draw(gl, self.simple, objects.color(), (w as i32, h as i32));
+
+        if std::mem::take(&mut self.screenshot) {
+            match self.screenshot(gl) {
+                Ok(()) => info!("[render] Screenshot saved into {}", Self::SCREENSHOT_PATH),
+                Err(e) => error!("[render] Failed to take a screenshot: {}", e),
+            }
+        }
+
         // swap window
-        window.gl_swap_window();
+        self.window.gl_swap_window();
+    }
+
+    /// Save the frame in the default framebuffer (before it's presented).
+    fn screenshot(&self, gl: &Context) -> Result {
+        let (w, h) = self.window.drawable_size();
+        let pixels = read_framebuffer(gl, None, w as i32, h as i32)?;
+
+        std::fs::write(
+            Self::SCREENSHOT_PATH,
+            encode_ppm(&pixels, w as usize, h as usize),
+        )
+        .map_err(Into::into)
+    }
+
+    pub fn delete(&self, gl: &Context) {
+        self.debug.delete(gl);
+        self.crosshair.delete(gl);
+
+        for ghost in &self.ghosts {
+            ghost.delete(gl)
+        }
+
+        if let Some(fxaa) = &self.fxaa {
+            fxaa.delete(gl)
+        }
+        if let Some(scaled) = &self.scaled {
+            scaled.delete(gl)
+        }
+    }
+}
+
+impl Debug for Renderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Renderer")
+            .field("last_frame", &self.last_frame)
+            .field("day_length", &self.day_length)
+            .field("show_bounds", &self.show_bounds)
+            .field("fxaa", &self.fxaa)
+            .field("scaled", &self.scaled)
+            .field("stepper", &self.stepper)
+            .field("recording", &self.recording)
+            .field("ghosts", &self.ghosts)
+            .finish_non_exhaustive()
     }
 }
 
@@ -0,0 +1,155 @@
+use crate::*;
+use glow::{
+    Context, HasContext, NativeFramebuffer, NativeRenderbuffer, COLOR_ATTACHMENT0,
+    COLOR_BUFFER_BIT, DEPTH_ATTACHMENT, DEPTH_COMPONENT24, DRAW_FRAMEBUFFER, FRAMEBUFFER,
+    FRAMEBUFFER_COMPLETE, LINEAR, READ_FRAMEBUFFER, RENDERBUFFER, RGBA8,
+};
+
+/// Renders the scene at a fraction of the window resolution (dynamic resolution).
+///
+/// The scene is rendered into an offscreen target between [`ScaledTarget::begin`] and
+/// [`ScaledTarget::end_and_upscale`], which then stretches it over another framebuffer.
+#[derive(Debug)]
+pub struct ScaledTarget {
+    fbo: NativeFramebuffer,
+    color: NativeRenderbuffer,
+    depth: NativeRenderbuffer,
+    window: (i32, i32),
+    scale: f32,
+}
+
+impl ScaledTarget {
+    /// The bounds of the render scale (beyond 1 is supersampling).
+    pub const MIN_SCALE: f32 = 0.25;
+    pub const MAX_SCALE: f32 = 2.0;
+
+    pub fn new(gl: &Context, width: i32, height: i32, scale: f32) -> Result<Self> {
+        unsafe {
+            let fbo = gl.create_framebuffer()?;
+            let color = gl.create_renderbuffer()?;
+            let depth = gl.create_renderbuffer()?;
+
+            let target = Self {
+                fbo,
+                color,
+                depth,
+                window: (width, height),
+                scale: Self::clamp_scale(scale),
+            };
+
+            target.allocate(gl);
+
+            gl.bind_framebuffer(FRAMEBUFFER, Some(fbo));
+            gl.framebuffer_renderbuffer(FRAMEBUFFER, COLOR_ATTACHMENT0, RENDERBUFFER, Some(color));
+            gl.framebuffer_renderbuffer(FRAMEBUFFER, DEPTH_ATTACHMENT, RENDERBUFFER, Some(depth));
+
+            let status = gl.check_framebuffer_status(FRAMEBUFFER);
+            gl.bind_framebuffer(FRAMEBUFFER, None);
+
+            if status != FRAMEBUFFER_COMPLETE {
+                target.delete(gl);
+                return Err(Error::Framebuffer(status));
+            }
+            Ok(target)
+        }
+    }
+
+    /// Keep `scale` within [`ScaledTarget::MIN_SCALE`]..=[`ScaledTarget::MAX_SCALE`].
+    pub fn clamp_scale(scale: f32) -> f32 {
+        if scale.is_nan() {
+            return 1.0;
+        }
+        scale.clamp(Self::MIN_SCALE, Self::MAX_SCALE)
+    }
+
+    /// The resolution of the offscreen target for a window of `(width, height)` at `scale`.
+    pub fn scaled_size((width, height): (i32, i32), scale: f32) -> (i32, i32) {
+        let scale = Self::clamp_scale(scale);
+        let dim = |n: i32| ((n as f32 * scale).round() as i32).max(1);
+        (dim(width), dim(height))
+    }
+
+    pub const fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// The resolution the scene is rendered at.
+    pub fn size(&self) -> (i32, i32) {
+        Self::scaled_size(self.window, self.scale)
+    }
+
+    /// Change the render scale (clamped), reallocating the target if its resolution changes.
+    pub fn set_scale(&mut self, gl: &Context, scale: f32) {
+        let size = self.size();
+        self.scale = Self::clamp_scale(scale);
+
+        if self.size() != size {
+            unsafe { self.allocate(gl) }
+        }
+    }
+
+    /// Follow the window resolution, reallocating the target if it has changed.
+    pub fn resize(&mut self, gl: &Context, width: i32, height: i32) {
+        if self.window != (width, height) {
+            self.window = (width, height);
+            unsafe { self.allocate(gl) }
+        }
+    }
+
+    /// Direct all rendering into the (scaled) target, covering all of it.
+    pub fn begin(&self, gl: &Context) {
+        let (width, height) = self.size();
+
+        unsafe {
+            gl.bind_framebuffer(FRAMEBUFFER, Some(self.fbo));
+            gl.viewport(0, 0, width, height);
+        }
+    }
+
+    /// Stretch the target over `target` (the default framebuffer if `None`), binding it and
+    /// restoring the window viewport.
+    pub fn end_and_upscale(&self, gl: &Context, target: Option<NativeFramebuffer>) {
+        let (width, height) = self.size();
+        let (window_width, window_height) = self.window;
+
+        unsafe {
+            gl.bind_framebuffer(READ_FRAMEBUFFER, Some(self.fbo));
+            gl.bind_framebuffer(DRAW_FRAMEBUFFER, target);
+            gl.blit_framebuffer(
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                window_width,
+                window_height,
+                COLOR_BUFFER_BIT,
+                LINEAR,
+            );
+
+            gl.bind_framebuffer(FRAMEBUFFER, target);
+            gl.viewport(0, 0, window_width, window_height);
+        }
+        gl_report(gl, "upscale pass");
+    }
+
+    pub fn delete(&self, gl: &Context) {
+        unsafe {
+            gl.delete_framebuffer(self.fbo);
+            gl.delete_renderbuffer(self.color);
+            gl.delete_renderbuffer(self.depth);
+        }
+    }
+
+    /// (Re)allocate the storage of the attachments for the current (scaled) size.
+    unsafe fn allocate(&self, gl: &Context) {
+        let (width, height) = self.size();
+
+        for (renderbuffer, format) in [(self.color, RGBA8), (self.depth, DEPTH_COMPONENT24)] {
+            gl.bind_renderbuffer(RENDERBUFFER, Some(renderbuffer));
+            gl.renderbuffer_storage(RENDERBUFFER, format, width, height);
+        }
+        gl.bind_renderbuffer(RENDERBUFFER, None);
+    }
+}
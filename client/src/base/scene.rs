@@ -0,0 +1,189 @@
+use crate::*;
+use bincode::{deserialize_from, serialize_into};
+use glow::Context;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+/// Identifies a scene file.
+const SCENE_MAGIC: [u8; 4] = *b"BLZS";
+
+/// Bumped whenever the layout of [`SceneObject`] changes.
+const SCENE_VERSION: u16 = 8;
+
+/// Everything required to rebuild an [`Object`].
+#[derive(Serialize, Deserialize)]
+struct SceneObject {
+    geometry: Geometry,
+    shaded: bool,
+    data: ObjectData,
+
+    // skipped by the serialization of [`ObjectData`]
+    material: MaterialData,
+    emissive: Color,
+    tint: Color,
+}
+
+impl RawObjects {
+    /// write every object into a versioned binary scene file.
+    ///
+    /// Objects with [`Geometry::Custom`] can't be rebuilt, so they're skipped.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result {
+        let scene = self
+            .iter()
+            .filter(|obj| {
+                let custom = obj.geometry() == Geometry::Custom;
+                if custom {
+                    warn!(
+                        "[scene] Skipping object with custom geometry ({})",
+                        obj.id()
+                    )
+                }
+                !custom
+            })
+            .map(|obj| SceneObject {
+                geometry: obj.geometry(),
+                shaded: obj.program().kind() == ProgramUnit::Normal,
+                data: *obj.data(),
+                material: *obj.material(),
+                emissive: obj.emissive(),
+                tint: obj.tint(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_scene(&mut writer, &scene)?;
+        writer.flush().map_err(Into::into)
+    }
+
+    /// rebuild (with fresh buffers) every object of a scene file, returning how many were added.
+    ///
+    /// Nothing is added unless the whole file is valid and every object could be rebuilt.
+    pub fn load(
+        &mut self,
+        gl: &Context,
+        programs: &Shaders,
+        path: impl AsRef<Path>,
+    ) -> Result<usize> {
+        let scene = read_scene(BufReader::new(File::open(path)?))?;
+
+        let mut objects = Vec::with_capacity(scene.len());
+        for SceneObject {
+            geometry,
+            shaded,
+            mut data,
+            material,
+            emissive,
+            tint,
+        } in scene
+        {
+            let program = if shaded {
+                programs.normal()
+            } else {
+                programs.simple()
+            };
+
+            // render-local state isn't serialized
+            data.set_material(material);
+            data.set_emissive(emissive);
+            data.set_tint(tint);
+            data.transform_upt();
+
+            match Object::create_geometry_with(gl, program, geometry, data) {
+                Ok(obj) => objects.push(obj),
+                Err(e) => {
+                    for obj in &objects {
+                        free_buffers(gl, obj.buffers())
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        let len = objects.len();
+        for obj in objects {
+            self.insert(gl, obj);
+        }
+        Ok(len)
+    }
+}
+
+/// write the header and `scene` into `writer`.
+fn write_scene(mut writer: impl Write, scene: &[SceneObject]) -> Result {
+    writer.write_all(&SCENE_MAGIC)?;
+    writer.write_all(&SCENE_VERSION.to_le_bytes())?;
+    serialize_into(writer, scene).map_err(Into::into)
+}
+
+/// read (and check the header of) a whole scene from `reader`.
+fn read_scene(mut reader: impl Read) -> Result<Vec<SceneObject>> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != SCENE_MAGIC {
+        return Err("Not a scene file".into());
+    }
+
+    let mut version = [0; 2];
+    reader.read_exact(&mut version)?;
+    let version = u16::from_le_bytes(version);
+    if version != SCENE_VERSION {
+        return Err(format!("Unsupported scene version: {}", version).into());
+    }
+
+    deserialize_from(reader).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube(id: Id, pos: Vector, color: Color) -> SceneObject {
+        let raw_data = RawObjectData::Basic(BasicData::new(pos, Vector::repeat(1.0)));
+        SceneObject {
+            geometry: Geometry::Cube,
+            shaded: true,
+            data: ObjectData::new(id, color, raw_data),
+            material: Default::default(),
+            emissive: Default::default(),
+            tint: Color::WHITE,
+        }
+    }
+
+    #[test]
+    fn scenes_round_trip() {
+        let scene = (0..5)
+            .map(|i| {
+                let color = Color::new([i as f32 / 4.0, 0.5, 1.0, 1.0], false);
+                cube(i, Vector::new(i as f32, -1.0, 2.0), color)
+            })
+            .collect::<Vec<_>>();
+
+        let mut bytes = Vec::new();
+        write_scene(&mut bytes, &scene).unwrap();
+        let loaded = read_scene(&bytes[..]).unwrap();
+
+        assert_eq!(loaded.len(), scene.len());
+        for (a, b) in scene.iter().zip(&loaded) {
+            assert_eq!(a.data.id(), b.data.id());
+            assert_eq!(a.data.pos(), b.data.pos());
+            assert_eq!(a.data.raw_color()[..], b.data.raw_color()[..]);
+            assert_eq!(a.geometry, b.geometry);
+        }
+    }
+
+    #[test]
+    fn foreign_or_outdated_files_are_rejected() {
+        let mut bytes = Vec::new();
+        write_scene(&mut bytes, &[cube(0, Vector::zeros(), Color::WHITE)]).unwrap();
+
+        let mut foreign = bytes.clone();
+        foreign[0] = b'X';
+        assert!(read_scene(&foreign[..]).is_err());
+
+        let mut outdated = bytes;
+        outdated[4..6].copy_from_slice(&(SCENE_VERSION - 1).to_le_bytes());
+        assert!(read_scene(&outdated[..]).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+use crate::*;
+use bytemuck::cast_slice;
+use glow::{
+    Context, HasContext, NativeBuffer, NativeVertexArray, ARRAY_BUFFER, DEPTH_TEST,
+    ELEMENT_ARRAY_BUFFER, FLOAT, STATIC_DRAW, TRIANGLES, UNSIGNED_BYTE,
+};
+
+/// The point of the viewport a [`ScreenObject`] is positioned relative to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Anchor {
+    #[default]
+    Center,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Anchor {
+    /// The position of the anchor in pixels (from the bottom-left, as in GL).
+    pub fn origin(&self, (width, height): (i32, i32)) -> (f32, f32) {
+        let (width, height) = (width as f32, height as f32);
+
+        match self {
+            Self::Center => (width / 2.0, height / 2.0),
+            Self::TopLeft => (0.0, height),
+            Self::TopRight => (width, height),
+            Self::BottomLeft => (0.0, 0.0),
+            Self::BottomRight => (width, 0.0),
+        }
+    }
+}
+
+/// The outline of a [`ScreenObject`], spanning [-1, 1] like every other unit geometry.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScreenShape {
+    Quad,
+    /// Two bars crossing at the center, `thickness` being their width relative to their length.
+    Crosshair {
+        thickness: f32,
+    },
+}
+
+impl ScreenShape {
+    /// The vertices (position) and triangle indices of the shape.
+    pub fn mesh(&self) -> (Vec<f32>, Vec<u8>) {
+        let rects = match *self {
+            Self::Quad => vec![(1.0, 1.0)],
+            Self::Crosshair { thickness } => {
+                let t = thickness.clamp(0.0, 1.0);
+                vec![(1.0, t), (t, 1.0)]
+            }
+        };
+
+        let mut vertices = Vec::with_capacity(rects.len() * 12);
+        let mut indices = Vec::with_capacity(rects.len() * 6);
+
+        for (i, (x, y)) in rects.into_iter().enumerate() {
+            vertices.extend([-x, -y, 0.0, x, -y, 0.0, x, y, 0.0, -x, y, 0.0]);
+
+            let n = i as u8 * 4;
+            indices.extend([n, n + 1, n + 2, n, n + 2, n + 3]);
+        }
+        (vertices, indices)
+    }
+}
+
+/// A flat shape drawn over the scene in pixel coordinates (e.g., a crosshair).
+///
+/// It's drawn with the 'simple' program through an orthographic projection of the
+/// viewport, so the camera never affects it, and it follows its [`Anchor`] as the
+/// resolution changes.
+#[derive(Debug)]
+pub struct ScreenObject {
+    anchor: Anchor,
+    offset: (f32, f32),
+    size: (f32, f32),
+    color: Color,
+    vao: NativeVertexArray,
+    vbo: NativeBuffer,
+    ebo: NativeBuffer,
+    len: i32,
+}
+
+impl ScreenObject {
+    /// Construct a `shape` of `size` pixels, centered `offset` pixels away from `anchor`.
+    pub fn new(
+        gl: &Context,
+        shape: ScreenShape,
+        anchor: Anchor,
+        offset: (f32, f32),
+        size: (f32, f32),
+        color: Color,
+    ) -> Result<Self> {
+        let (vertices, indices) = shape.mesh();
+
+        unsafe {
+            let vao = gl.create_vertex_array()?;
+            let vbo = gl.create_buffer()?;
+            let ebo = gl.create_buffer()?;
+
+            gl.bind_vertex_array(Some(vao));
+
+            gl.bind_buffer(ARRAY_BUFFER, Some(vbo));
+            gl.buffer_data_u8_slice(ARRAY_BUFFER, cast_slice(&vertices), STATIC_DRAW);
+
+            gl.bind_buffer(ELEMENT_ARRAY_BUFFER, Some(ebo));
+            gl.buffer_data_u8_slice(ELEMENT_ARRAY_BUFFER, &indices, STATIC_DRAW);
+
+            // enable `pos` attribute
+            gl.enable_vertex_attrib_array(0);
+            gl.vertex_attrib_pointer_f32(0, 3, FLOAT, false, 3 * size_of::<f32>() as i32, 0);
+
+            // unbind buffers
+            gl.bind_vertex_array(None);
+            gl.bind_buffer(ARRAY_BUFFER, None);
+            gl.bind_buffer(ELEMENT_ARRAY_BUFFER, None);
+            gl_check(gl, "screen object upload")?;
+
+            Ok(Self {
+                anchor,
+                offset,
+                size,
+                color,
+                vao,
+                vbo,
+                ebo,
+                len: indices.len() as i32,
+            })
+        }
+    }
+
+    /// Construct a centered crosshair `size` pixels across.
+    pub fn crosshair(gl: &Context, size: f32, thickness: f32, color: Color) -> Result<Self> {
+        Self::new(
+            gl,
+            ScreenShape::Crosshair { thickness },
+            Anchor::Center,
+            (0.0, 0.0),
+            (size, size),
+            color,
+        )
+    }
+
+    pub const fn anchor(&self) -> Anchor {
+        self.anchor
+    }
+
+    pub fn set_anchor(&mut self, anchor: Anchor) {
+        self.anchor = anchor
+    }
+
+    /// The offset (in pixels) of the center from the anchor.
+    pub const fn offset(&self) -> (f32, f32) {
+        self.offset
+    }
+
+    pub fn set_offset(&mut self, offset: (f32, f32)) {
+        self.offset = offset
+    }
+
+    /// The dimensions in pixels.
+    pub const fn size(&self) -> (f32, f32) {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: (f32, f32)) {
+        self.size = size
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color
+    }
+
+    /// The center in pixels (from the bottom-left) within a viewport of `(width, height)`.
+    pub fn center(&self, viewport: (i32, i32)) -> (f32, f32) {
+        let (x, y) = self.anchor.origin(viewport);
+        (x + self.offset.0, y + self.offset.1)
+    }
+
+    /// The center in normalized device coordinates within a viewport of `(width, height)`.
+    pub fn ndc(&self, viewport: (i32, i32)) -> (f32, f32) {
+        let (x, y) = self.center(viewport);
+        let (width, height) = viewport;
+        (
+            x / width.max(1) as f32 * 2.0 - 1.0,
+            y / height.max(1) as f32 * 2.0 - 1.0,
+        )
+    }
+
+    /// Pixel space -> clip space of a viewport of `(width, height)`.
+    pub fn projection((width, height): (i32, i32)) -> Matrix {
+        Matrix::new_orthographic(
+            0.0,
+            width.max(1) as f32,
+            0.0,
+            height.max(1) as f32,
+            -1.0,
+            1.0,
+        )
+    }
+
+    /// Unit space -> pixel space of a viewport of `(width, height)`.
+    pub fn model(&self, viewport: (i32, i32)) -> Matrix {
+        let (x, y) = self.center(viewport);
+        let (width, height) = self.size;
+
+        Matrix::new_translation(&Vector::new(x, y, 0.0))
+            * Matrix::new_nonuniform_scaling(&Vector::new(width / 2.0, height / 2.0, 1.0))
+    }
+
+    /// Draw over everything with the 'simple' `program` into a viewport of `(width, height)`.
+    pub fn draw(
+        &self,
+        gl: &Context,
+        program: Program,
+        color: ColorManagement,
+        viewport: (i32, i32),
+    ) {
+        let native = program.native();
+
+        unsafe {
+            gl.use_program(Some(native));
+
+            for (name, matrix) in [
+                ("model", self.model(viewport)),
+                ("view", Matrix::identity()),
+                ("proj", Self::projection(viewport)),
+            ] {
+                gl.uniform_matrix_4_f32_slice(
+                    gl.get_uniform_location(native, name).as_ref(),
+                    false,
+                    matrix.as_slice(),
+                );
+            }
+
+            // never clipped, and depth is irrelevant
+            gl.uniform_4_f32_slice(
+                gl.get_uniform_location(native, "clip_plane").as_ref(),
+                &[0.0; 4],
+            );
+            gl.uniform_1_i32(gl.get_uniform_location(native, "use_log_depth").as_ref(), 0);
+            gl.uniform_1_i32(
+                gl.get_uniform_location(native, "shader_gamma").as_ref(),
+                color.shader_gamma() as i32,
+            );
+
+            gl.uniform_4_f32_slice(
+                gl.get_uniform_location(native, "obj_col").as_ref(),
+                &self.color,
+            );
+            gl.uniform_3_f32_slice(gl.get_uniform_location(native, "tint").as_ref(), &[1.0; 3]);
+
+            gl.disable(DEPTH_TEST);
+
+            gl.bind_vertex_array(Some(self.vao));
+            gl.draw_elements(TRIANGLES, self.len, UNSIGNED_BYTE, 0);
+            gl.bind_vertex_array(None);
+
+            gl.enable(DEPTH_TEST);
+            gl.use_program(None);
+        }
+        gl_report(gl, "screen pass");
+    }
+
+    pub fn delete(&self, gl: &Context) {
+        unsafe {
+            gl.delete_vertex_array(self.vao);
+            gl.delete_buffer(self.vbo);
+            gl.delete_buffer(self.ebo);
+        }
+    }
+}
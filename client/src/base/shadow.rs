@@ -0,0 +1,166 @@
+use crate::*;
+use glow::{
+    Context, HasContext, NativeFramebuffer, NativeTexture, PixelUnpackData, CLAMP_TO_BORDER,
+    DEPTH_ATTACHMENT, DEPTH_BUFFER_BIT, DEPTH_COMPONENT, DEPTH_COMPONENT24, FLOAT, FRAMEBUFFER,
+    FRAMEBUFFER_COMPLETE, NEAREST, NONE, TEXTURE_2D, TEXTURE_BORDER_COLOR, TEXTURE_MAG_FILTER,
+    TEXTURE_MIN_FILTER, TEXTURE_WRAP_S, TEXTURE_WRAP_T,
+};
+
+/// The tuning of shadow mapping (adjustable at runtime).
+///
+/// `bias` is subtracted from the depth of a fragment before it's compared against the
+/// shadow map (too little causes acne, too much detaches shadows from their casters,
+/// i.e., peter-panning). `normal_bias` pushes the sampled position along the surface
+/// normal instead, which fixes acne on grazing surfaces without detaching as much.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowSettings {
+    pub bias: f32,
+    pub normal_bias: f32,
+    pub map_size: u32,
+}
+
+impl ShadowSettings {
+    /// The world position the shadow map is sampled at for a fragment at `pos`.
+    pub fn normal_offset(&self, pos: &Vector, normal: &Vector) -> Vector {
+        let normal = normal.try_normalize(f32::EPSILON).unwrap_or_default();
+        pos + normal * self.normal_bias
+    }
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            bias: 0.005,
+            normal_bias: 0.02,
+            map_size: 2048,
+        }
+    }
+}
+
+/// The (depth-only) view of the scene from a light, sampled while shading.
+#[derive(Clone, Copy, Debug)]
+pub struct Shadows {
+    pub texture: NativeTexture,
+    /// World space -> the clip space of the light.
+    pub light_space: Matrix,
+    pub settings: ShadowSettings,
+}
+
+/// The depth texture every shadow caster is rendered into (see [`RawObjects::draw_depth_only`]).
+#[derive(Debug)]
+pub struct ShadowMap {
+    fbo: NativeFramebuffer,
+    depth: NativeTexture,
+    settings: ShadowSettings,
+}
+
+impl ShadowMap {
+    /// The texture unit the shadow map is bound to while shading.
+    pub const UNIT: u32 = 1;
+
+    pub fn new(gl: &Context, settings: ShadowSettings) -> Result<Self> {
+        unsafe {
+            let fbo = gl.create_framebuffer()?;
+            let depth = gl.create_texture()?;
+
+            let map = Self {
+                fbo,
+                depth,
+                settings,
+            };
+
+            map.allocate(gl);
+
+            // everything outside of the map is lit
+            gl.bind_texture(TEXTURE_2D, Some(depth));
+            gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MIN_FILTER, NEAREST as i32);
+            gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MAG_FILTER, NEAREST as i32);
+            gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_WRAP_S, CLAMP_TO_BORDER as i32);
+            gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_WRAP_T, CLAMP_TO_BORDER as i32);
+            gl.tex_parameter_f32_slice(TEXTURE_2D, TEXTURE_BORDER_COLOR, &[1.0; 4]);
+            gl.bind_texture(TEXTURE_2D, None);
+
+            // depth only
+            gl.bind_framebuffer(FRAMEBUFFER, Some(fbo));
+            gl.framebuffer_texture_2d(FRAMEBUFFER, DEPTH_ATTACHMENT, TEXTURE_2D, Some(depth), 0);
+            gl.draw_buffer(NONE);
+            gl.read_buffer(NONE);
+
+            let status = gl.check_framebuffer_status(FRAMEBUFFER);
+            gl.bind_framebuffer(FRAMEBUFFER, None);
+
+            if status != FRAMEBUFFER_COMPLETE {
+                map.delete(gl);
+                return Err(Error::Framebuffer(status));
+            }
+            Ok(map)
+        }
+    }
+
+    pub const fn settings(&self) -> &ShadowSettings {
+        &self.settings
+    }
+
+    /// Change the settings, reallocating the map if its size changes.
+    pub fn set_settings(&mut self, gl: &Context, settings: ShadowSettings) {
+        let resized = settings.map_size != self.settings.map_size;
+        self.settings = settings;
+
+        if resized {
+            unsafe { self.allocate(gl) }
+        }
+    }
+
+    /// Render the depth of every shadow caster as seen by `light`.
+    ///
+    /// The viewport is left covering the map (restore it before rendering the scene).
+    pub fn render(&self, gl: &Context, objects: &RawObjects, light: &RawCamera) -> RenderStats {
+        let size = self.settings.map_size as i32;
+
+        unsafe {
+            gl.bind_framebuffer(FRAMEBUFFER, Some(self.fbo));
+            gl.viewport(0, 0, size, size);
+            gl.clear(DEPTH_BUFFER_BIT);
+        }
+
+        let stats = objects.draw_depth_only(gl, light);
+
+        unsafe { gl.bind_framebuffer(FRAMEBUFFER, None) }
+        stats
+    }
+
+    /// What the scene samples to be shadowed from `light` (see [`RawObjects::set_shadows`]).
+    pub fn shadows(&self, light: &RawCamera) -> Shadows {
+        Shadows {
+            texture: self.depth,
+            light_space: light.projection() * light.view(),
+            settings: self.settings,
+        }
+    }
+
+    pub fn delete(&self, gl: &Context) {
+        unsafe {
+            gl.delete_framebuffer(self.fbo);
+            gl.delete_texture(self.depth);
+        }
+    }
+
+    /// (Re)allocate the storage of the depth texture for the current size.
+    unsafe fn allocate(&self, gl: &Context) {
+        let size = self.settings.map_size as i32;
+
+        gl.bind_texture(TEXTURE_2D, Some(self.depth));
+        gl.tex_image_2d(
+            TEXTURE_2D,
+            0,
+            DEPTH_COMPONENT24 as i32,
+            size,
+            size,
+            0,
+            DEPTH_COMPONENT,
+            FLOAT,
+            PixelUnpackData::Slice(None),
+        );
+        gl.bind_texture(TEXTURE_2D, None);
+    }
+}
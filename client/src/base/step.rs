@@ -0,0 +1,108 @@
+use crate::*;
+use std::time::Duration;
+
+/// Splits frame time into fixed simulation steps, with a freeze-frame mode for debugging.
+///
+/// While paused, the simulation only advances on [`DebugStepper::step_once`], and the
+/// interpolation alpha is held at its value when paused so the frozen frame stays still
+/// (rendering and the camera carry on regardless).
+#[derive(Clone, Copy, Debug)]
+pub struct DebugStepper {
+    step: Duration,
+    accumulator: Duration,
+    paused: bool,
+    pending: u32,
+    alpha: f32,
+}
+
+impl DebugStepper {
+    pub fn new(step: Duration) -> Self {
+        Self {
+            step: step.max(MILISECOND),
+            accumulator: Duration::ZERO,
+            paused: false,
+            pending: 0,
+            alpha: 0.0,
+        }
+    }
+
+    /// The duration of a single simulation step.
+    pub const fn step(&self) -> Duration {
+        self.step
+    }
+
+    pub const fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Freeze the simulation (idempotent).
+    pub fn pause(&mut self) {
+        self.paused = true
+    }
+
+    /// Advance the paused simulation by exactly one step on the next frame.
+    pub fn step_once(&mut self) {
+        if self.paused {
+            self.pending += 1
+        }
+    }
+
+    /// Carry on from where the simulation was paused (without catching up).
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.pending = 0;
+        self.accumulator = self.step.mul_f32(self.alpha);
+    }
+
+    /// Account for a frame of `frame_dt`, returning the number of steps to simulate and
+    /// the interpolation alpha between the last two of them.
+    ///
+    /// A single frame covers at most [`MAX_DT`] seconds (hitches are slowed down instead).
+    pub fn advance(&mut self, frame_dt: Duration) -> (u32, f32) {
+        if self.paused {
+            return (std::mem::take(&mut self.pending), self.alpha);
+        }
+
+        self.accumulator += frame_dt.min(Duration::from_secs_f32(MAX_DT));
+
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+
+        self.alpha = self.accumulator.as_secs_f32() / self.step.as_secs_f32();
+        (steps, self.alpha)
+    }
+}
+
+impl Default for DebugStepper {
+    fn default() -> Self {
+        Self::new(TICK_RATE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_once_advances_exactly_one_step() {
+        let mut stepper = DebugStepper::new(Duration::from_millis(10));
+        let half = |alpha: f32| (alpha - 0.5).abs() < 1e-4;
+
+        // a step and a half, then frozen
+        let (steps, alpha) = stepper.advance(Duration::from_millis(15));
+        assert!(steps == 1 && half(alpha));
+        stepper.pause();
+        assert_eq!(stepper.advance(Duration::from_millis(50)), (0, alpha));
+
+        stepper.step_once();
+        assert_eq!(stepper.advance(Duration::from_millis(50)), (1, alpha));
+        assert_eq!(stepper.advance(Duration::from_millis(50)), (0, alpha));
+
+        // carrying on from the held alpha
+        stepper.resume();
+        assert_eq!(stepper.advance(Duration::from_millis(6)).0, 1);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::*;
+use glow::{
+    Context, HasContext, NativeTexture, PixelUnpackData, CLAMP_TO_EDGE, LINEAR,
+    LINEAR_MIPMAP_LINEAR, MIRRORED_REPEAT, NEAREST, NEAREST_MIPMAP_LINEAR, ONE,
+    ONE_MINUS_SRC_ALPHA, REPEAT, RGBA, RGBA8, SRC_ALPHA, TEXTURE0, TEXTURE_2D, TEXTURE_MAG_FILTER,
+    TEXTURE_MIN_FILTER, TEXTURE_WRAP_S, TEXTURE_WRAP_T, UNSIGNED_BYTE,
+};
+
+/// How texture coordinates outside of `[0, 1]` are sampled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextureWrap {
+    /// Tile the texture (e.g., floors).
+    #[default]
+    Repeat,
+    /// Tile the texture, mirroring every other repetition.
+    MirroredRepeat,
+    /// Stretch the edge texels (e.g., UI sprites).
+    ClampToEdge,
+}
+
+impl TextureWrap {
+    pub const fn gl(&self) -> u32 {
+        match self {
+            Self::Repeat => REPEAT,
+            Self::MirroredRepeat => MIRRORED_REPEAT,
+            Self::ClampToEdge => CLAMP_TO_EDGE,
+        }
+    }
+}
+
+/// How texels are sampled when the texture is scaled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Blocky (e.g., pixel-art).
+    Nearest,
+    #[default]
+    Linear,
+}
+
+impl TextureFilter {
+    /// The filter used for magnification.
+    pub const fn gl(&self) -> u32 {
+        match self {
+            Self::Nearest => NEAREST,
+            Self::Linear => LINEAR,
+        }
+    }
+
+    /// The filter used for minification, blending between mip levels if `mipmaps`.
+    pub const fn gl_min(&self, mipmaps: bool) -> u32 {
+        match (self, mipmaps) {
+            (Self::Nearest, true) => NEAREST_MIPMAP_LINEAR,
+            (Self::Linear, true) => LINEAR_MIPMAP_LINEAR,
+            (filter, false) => filter.gl(),
+        }
+    }
+}
+
+/// The sampling state of a [`Texture`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureParams {
+    pub wrap_s: TextureWrap,
+    pub wrap_t: TextureWrap,
+    pub min_filter: TextureFilter,
+    pub mag_filter: TextureFilter,
+    pub generate_mipmaps: bool,
+}
+
+impl TextureParams {
+    /// Every `(parameter, value)` pair set on the texture.
+    pub const fn gl(&self) -> [(u32, u32); 4] {
+        [
+            (TEXTURE_WRAP_S, self.wrap_s.gl()),
+            (TEXTURE_WRAP_T, self.wrap_t.gl()),
+            (
+                TEXTURE_MIN_FILTER,
+                self.min_filter.gl_min(self.generate_mipmaps),
+            ),
+            (TEXTURE_MAG_FILTER, self.mag_filter.gl()),
+        ]
+    }
+}
+
+impl Default for TextureParams {
+    fn default() -> Self {
+        Self {
+            wrap_s: TextureWrap::default(),
+            wrap_t: TextureWrap::default(),
+            min_filter: TextureFilter::default(),
+            mag_filter: TextureFilter::default(),
+            generate_mipmaps: true,
+        }
+    }
+}
+
+/// How the color of the pixels of a [`Texture`] relates to their alpha.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AlphaMode {
+    /// Color independent of alpha (blending them darkens filtered edges, i.e., halos).
+    #[default]
+    Straight,
+    /// Color already multiplied by alpha.
+    Premultiplied,
+    /// Straight alpha, premultiplied while uploading.
+    PremultiplyOnUpload,
+}
+
+/// Multiply the color of every RGBA8 pixel by its alpha (rounding to nearest).
+pub fn premultiply(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u16;
+
+        for c in &mut pixel[..3] {
+            *c = ((*c as u16 * alpha + 127) / 255) as u8;
+        }
+    }
+}
+
+/// A 2D RGBA8 texture.
+#[derive(Clone, Copy, Debug)]
+pub struct Texture {
+    handle: NativeTexture,
+    size: (i32, i32),
+    params: TextureParams,
+    premultiplied: bool,
+}
+
+impl Texture {
+    /// Upload tightly packed straight-alpha RGBA8 `pixels` (rows are bottom-up, as in GL).
+    pub fn from_rgba(
+        gl: &Context,
+        width: i32,
+        height: i32,
+        pixels: &[u8],
+        params: TextureParams,
+    ) -> Result<Self> {
+        Self::from_rgba_with(gl, (width, height), pixels, params, AlphaMode::Straight)
+    }
+
+    /// Upload tightly packed RGBA8 `pixels` of `(width, height)` with alpha as of `alpha`.
+    pub fn from_rgba_with(
+        gl: &Context,
+        (width, height): (i32, i32),
+        pixels: &[u8],
+        params: TextureParams,
+        alpha: AlphaMode,
+    ) -> Result<Self> {
+        let mut owned;
+        let pixels = if alpha == AlphaMode::PremultiplyOnUpload {
+            owned = pixels.to_vec();
+            premultiply(&mut owned);
+            &owned[..]
+        } else {
+            pixels
+        };
+
+        unsafe {
+            let handle = gl.create_texture()?;
+            gl.bind_texture(TEXTURE_2D, Some(handle));
+            gl.tex_image_2d(
+                TEXTURE_2D,
+                0,
+                RGBA8 as i32,
+                width,
+                height,
+                0,
+                RGBA,
+                UNSIGNED_BYTE,
+                PixelUnpackData::Slice(Some(pixels)),
+            );
+
+            for (parameter, value) in params.gl() {
+                gl.tex_parameter_i32(TEXTURE_2D, parameter, value as i32);
+            }
+            if params.generate_mipmaps {
+                gl.generate_mipmap(TEXTURE_2D);
+            }
+
+            gl.bind_texture(TEXTURE_2D, None);
+            if let Err(e) = gl_check(gl, "texture upload") {
+                gl.delete_texture(handle);
+                return Err(e);
+            }
+
+            Ok(Self {
+                handle,
+                size: (width, height),
+                params,
+                premultiplied: alpha != AlphaMode::Straight,
+            })
+        }
+    }
+
+    pub const fn handle(&self) -> NativeTexture {
+        self.handle
+    }
+
+    pub const fn size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    pub const fn params(&self) -> &TextureParams {
+        &self.params
+    }
+
+    /// Whether the color of the texels is multiplied by their alpha.
+    pub const fn premultiplied(&self) -> bool {
+        self.premultiplied
+    }
+
+    /// The `(source, destination)` blend factors compositing this texture correctly.
+    pub const fn blend_func(&self) -> (u32, u32) {
+        if self.premultiplied {
+            (ONE, ONE_MINUS_SRC_ALPHA)
+        } else {
+            (SRC_ALPHA, ONE_MINUS_SRC_ALPHA)
+        }
+    }
+
+    /// Switch blending to suit this texture (the default suits premultiplied alpha).
+    pub fn apply_blend(&self, gl: &Context) {
+        let (src, dst) = self.blend_func();
+        unsafe { gl.blend_func(src, dst) }
+    }
+
+    /// Bind the texture to the texture `unit` (e.g., `0` for `TEXTURE0`).
+    pub fn bind(&self, gl: &Context, unit: u32) {
+        unsafe {
+            gl.active_texture(TEXTURE0 + unit);
+            gl.bind_texture(TEXTURE_2D, Some(self.handle));
+        }
+    }
+
+    pub fn delete(&self, gl: &Context) {
+        unsafe { gl.delete_texture(self.handle) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_map_to_gl_enums() {
+        let params = TextureParams {
+            wrap_s: TextureWrap::Repeat,
+            wrap_t: TextureWrap::ClampToEdge,
+            min_filter: TextureFilter::Nearest,
+            mag_filter: TextureFilter::Nearest,
+            generate_mipmaps: false,
+        };
+
+        assert_eq!(
+            params.gl(),
+            [
+                (TEXTURE_WRAP_S, REPEAT),
+                (TEXTURE_WRAP_T, CLAMP_TO_EDGE),
+                (TEXTURE_MIN_FILTER, NEAREST),
+                (TEXTURE_MAG_FILTER, NEAREST),
+            ]
+        );
+        assert_eq!(TextureWrap::MirroredRepeat.gl(), MIRRORED_REPEAT);
+    }
+
+    #[test]
+    fn mipmaps_only_when_requested() {
+        let mut params = TextureParams::default();
+        assert_eq!(params.gl()[2], (TEXTURE_MIN_FILTER, LINEAR_MIPMAP_LINEAR));
+
+        params.generate_mipmaps = false;
+        assert_eq!(params.gl()[2], (TEXTURE_MIN_FILTER, LINEAR));
+
+        // magnification never samples mip levels
+        assert_eq!(params.gl()[3], (TEXTURE_MAG_FILTER, LINEAR));
+    }
+
+    #[test]
+    fn premultiply_scales_color_by_alpha() {
+        let mut pixels = [200, 100, 50, 128, 255, 255, 255, 0];
+        premultiply(&mut pixels);
+
+        assert_eq!(pixels, [100, 50, 25, 128, 0, 0, 0, 0]);
+    }
+}
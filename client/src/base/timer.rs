@@ -0,0 +1,121 @@
+use crate::*;
+use glow::{Context, HasContext, NativeQuery, QUERY_RESULT, QUERY_RESULT_AVAILABLE, TIME_ELAPSED};
+use std::time::Duration;
+
+/// A pass of [`RawObjects`] timed on the GPU (see [`RawObjects::enable_gpu_timer`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RenderPass {
+    /// [`RawObjects::draw_depth_only`] (e.g., into a [`ShadowMap`]).
+    Depth,
+    /// [`RawObjects::draw`].
+    Opaque,
+}
+
+impl RenderPass {
+    pub const COUNT: usize = 2;
+}
+
+/// The GPU time of the latest measured frame of each [`RenderPass`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PassTimes([Option<Duration>; RenderPass::COUNT]);
+
+impl PassTimes {
+    pub const fn get(&self, pass: RenderPass) -> Option<Duration> {
+        self.0[pass as usize]
+    }
+
+    pub fn set(&mut self, pass: RenderPass, time: Option<Duration>) {
+        self.0[pass as usize] = time
+    }
+}
+
+/// The (double-buffered) queries of a single pass.
+#[derive(Clone, Copy, Debug)]
+struct PassQueries {
+    queries: [NativeQuery; 2],
+    /// The query written this frame (the other one was written last frame).
+    current: usize,
+    pending: [bool; 2],
+    last: Option<Duration>,
+}
+
+/// Measures how long the GPU spends on each [`RenderPass`].
+///
+/// Every pass alternates between two queries, so the result read each frame is the
+/// previous one, which is usually available by then (reading the current one would
+/// stall until the GPU catches up). While a result isn't available, the last one is
+/// reported instead.
+#[derive(Debug)]
+pub struct GpuTimer {
+    passes: Mutex<[PassQueries; RenderPass::COUNT]>,
+}
+
+impl GpuTimer {
+    pub fn new(gl: &Context) -> Result<Self> {
+        let mut queries = Vec::with_capacity(RenderPass::COUNT * 2);
+
+        for _ in 0..RenderPass::COUNT * 2 {
+            match unsafe { gl.create_query() } {
+                Ok(query) => queries.push(query),
+                Err(e) => {
+                    for query in queries {
+                        unsafe { gl.delete_query(query) }
+                    }
+                    return Err(e.into());
+                }
+            }
+        }
+
+        let passes = std::array::from_fn(|i| PassQueries {
+            queries: [queries[i * 2], queries[i * 2 + 1]],
+            current: 0,
+            pending: [false; 2],
+            last: None,
+        });
+        Ok(Self {
+            passes: Mutex::new(passes),
+        })
+    }
+
+    /// Start timing `pass` (passes can't be nested).
+    pub fn begin(&self, gl: &Context, pass: RenderPass) {
+        let passes = self.passes.lock();
+        let queries = &passes[pass as usize];
+
+        unsafe { gl.begin_query(TIME_ELAPSED, queries.queries[queries.current]) }
+    }
+
+    /// Stop timing `pass`, returning its time as of the latest available result.
+    pub fn end(&self, gl: &Context, pass: RenderPass) -> Option<Duration> {
+        let mut passes = self.passes.lock();
+        let queries = &mut passes[pass as usize];
+
+        unsafe { gl.end_query(TIME_ELAPSED) }
+        queries.pending[queries.current] = true;
+
+        // the query of the previous frame
+        queries.current ^= 1;
+        let previous = queries.current;
+
+        if queries.pending[previous] {
+            let query = queries.queries[previous];
+
+            unsafe {
+                if gl.get_query_parameter_u32(query, QUERY_RESULT_AVAILABLE) != 0 {
+                    let nanos = gl.get_query_parameter_u32(query, QUERY_RESULT);
+                    queries.last = Some(Duration::from_nanos(nanos as u64));
+                    queries.pending[previous] = false;
+                }
+            }
+        }
+        queries.last
+    }
+
+    pub fn delete(&self, gl: &Context) {
+        for queries in self.passes.lock().iter() {
+            for query in queries.queries {
+                unsafe { gl.delete_query(query) }
+            }
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use crate::*;
 use enum_unit::*;
 use glow::{
-    Context, HasContext, NativeProgram, BLEND, CULL_FACE, DEPTH_TEST, FRAGMENT_SHADER, LESS,
-    ONE_MINUS_SRC_ALPHA, SRC_ALPHA, VERTEX_SHADER,
+    Context, HasContext, NativeProgram, BLEND, BUFFER, CULL_FACE, DEBUG_SOURCE_APPLICATION,
+    DEPTH_TEST, FRAGMENT_SHADER, LESS, ONE, ONE_MINUS_SRC_ALPHA, PRIMITIVE_RESTART_FIXED_INDEX,
+    VERTEX_ARRAY, VERTEX_SHADER,
 };
 use sdl2::{
     video::{GLContext, Window},
@@ -39,6 +40,14 @@ impl Shaders {
         self.normal
     }
 
+    /// The program of the specified kind.
+    pub const fn get(&self, kind: ProgramUnit) -> Program {
+        match kind {
+            ProgramUnit::Simple => self.simple,
+            ProgramUnit::Normal => self.normal,
+        }
+    }
+
     pub fn delete(self, gl: &Context) {
         unsafe {
             gl.delete_program(self.simple.native());
@@ -102,31 +111,47 @@ pub fn process_shaders(gl: &Context, shader_sources: [(u32, &str); 2]) -> Result
     Ok(program)
 }
 
-pub fn init_shaders(gl: &Context) -> Result<Shaders> {
-    let simple_shader_sources = [
-        (
-            VERTEX_SHADER,
-            include_str!("../../shaders/simple/shader.vert"),
-        ),
-        (
-            FRAGMENT_SHADER,
-            include_str!("../../shaders/simple/shader.frag"),
-        ),
-    ];
-
-    let normal_shader_sources = [
-        (
-            VERTEX_SHADER,
-            include_str!("../../shaders/normal/shader.vert"),
-        ),
-        (
-            FRAGMENT_SHADER,
-            include_str!("../../shaders/normal/shader.frag"),
-        ),
-    ];
-
-    let simple_shader = process_shaders(gl, simple_shader_sources)?;
-    let normal_shader = process_shaders(gl, normal_shader_sources)?;
+/// Enable `defines` (e.g., `LOG_DEPTH`) in a shader source, right after its `#version`.
+pub fn with_defines(source: &str, defines: &[&str]) -> String {
+    let (version, rest) = source.split_once('\n').unwrap_or((source, ""));
+
+    let mut out = String::with_capacity(source.len() + defines.len() * 24);
+    out.push_str(version);
+    out.push('\n');
+
+    for define in defines {
+        out.push_str("#define ");
+        out.push_str(define);
+        out.push('\n');
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Compile the programs, writing logarithmic depth from the fragment shaders if `log_depth`.
+///
+/// Without it, the shaders never write `gl_FragDepth`, keeping early depth testing.
+pub fn init_shaders(gl: &Context, log_depth: bool) -> Result<Shaders> {
+    let defines: &[&str] = if log_depth { &["LOG_DEPTH"] } else { &[] };
+
+    let compile = |vert: &str, frag: &str| {
+        process_shaders(
+            gl,
+            [
+                (VERTEX_SHADER, &with_defines(vert, defines)),
+                (FRAGMENT_SHADER, &with_defines(frag, defines)),
+            ],
+        )
+    };
+
+    let simple_shader = compile(
+        include_str!("../../shaders/simple/shader.vert"),
+        include_str!("../../shaders/simple/shader.frag"),
+    )?;
+    let normal_shader = compile(
+        include_str!("../../shaders/normal/shader.vert"),
+        include_str!("../../shaders/normal/shader.frag"),
+    )?;
 
     let simple = Program::Simple(simple_shader);
     let normal = Program::Normal(normal_shader);
@@ -186,27 +211,132 @@ pub fn init() -> Result<(
         // face culling (default: back)
         gl.enable(CULL_FACE);
 
-        // alpha transparency
+        // alpha transparency (the shaders output premultiplied alpha)
         gl.enable(BLEND);
-        gl.blend_func(SRC_ALPHA, ONE_MINUS_SRC_ALPHA);
+        gl.blend_func(ONE, ONE_MINUS_SRC_ALPHA);
+
+        // the maximum index value separates primitives (e.g., fans)
+        gl.enable(PRIMITIVE_RESTART_FIXED_INDEX);
     }
 
     Ok((sdl, video, gl, window, events, event_pump, gl_context))
 }
 
+/// Fail with the GL error `code` (`NO_ERROR` is fine), mentioning `context`.
+#[cfg(any(feature = "gl-debug", test))]
+fn gl_result(code: u32, context: &str) -> Result {
+    match code {
+        glow::NO_ERROR => Ok(()),
+        code => Err(Error::GlError {
+            code,
+            context: context.to_string(),
+        }),
+    }
+}
+
+/// Fail with the pending GL error (if any), mentioning `context`.
+#[cfg(feature = "gl-debug")]
+pub fn gl_check(gl: &Context, context: &str) -> Result {
+    gl_result(unsafe { gl.get_error() }, context)
+}
+
+/// No-op without the `gl-debug` feature.
+#[cfg(not(feature = "gl-debug"))]
+#[inline(always)]
+pub fn gl_check(_gl: &Context, _context: &str) -> Result {
+    Ok(())
+}
+
+/// Log the pending GL error (if any) of the pass named `pass`, carrying on regardless.
+pub fn gl_report(gl: &Context, pass: &str) {
+    if let Err(e) = gl_check(gl, pass) {
+        error!("{}", e)
+    }
+}
+
+/// Name a GL object (`identifier`: e.g., `BUFFER`) for debugging tools, such as RenderDoc.
+///
+/// The label is only built when the `gl-debug` feature is on and the context supports it.
+#[cfg(feature = "gl-debug")]
+pub fn gl_label(gl: &Context, identifier: u32, name: u32, label: impl FnOnce() -> String) {
+    if gl.supports_debug() {
+        unsafe { gl.object_label(identifier, name, Some(label())) }
+    }
+}
+
+/// No-op without the `gl-debug` feature.
+#[cfg(not(feature = "gl-debug"))]
+#[inline(always)]
+pub fn gl_label(_gl: &Context, _identifier: u32, _name: u32, _label: impl FnOnce() -> String) {}
+
+/// Label the VAO, VBO, and EBO of an object as `<label> VAO`, etc.
+pub fn label_buffers(gl: &Context, buffers: Buffers, label: impl Fn() -> String) {
+    gl_label(gl, VERTEX_ARRAY, buffers.vao().0.get(), || {
+        format!("{} VAO", label())
+    });
+    gl_label(gl, BUFFER, buffers.vbo().0.get(), || {
+        format!("{} VBO", label())
+    });
+    gl_label(gl, BUFFER, buffers.ebo().0.get(), || {
+        format!("{} EBO", label())
+    });
+}
+
+/// Run `f` within a named debug group, so tools like RenderDoc can group its calls per pass.
+pub fn debug_group<T>(gl: &Context, name: &str, f: impl FnOnce() -> T) -> T {
+    #[cfg(feature = "gl-debug")]
+    let supported = gl.supports_debug();
+
+    #[cfg(not(feature = "gl-debug"))]
+    let supported = false;
+
+    if supported {
+        unsafe { gl.push_debug_group(DEBUG_SOURCE_APPLICATION, 0, name) }
+    }
+    let result = f();
+    if supported {
+        unsafe { gl.pop_debug_group() }
+    }
+    result
+}
+
+/// Free the buffers owned by an object (shared buffers are left to their [`MeshCache`]).
 pub fn free_buffers(gl: &Context, buffers: Buffers) {
     unsafe {
         gl.delete_vertex_array(buffers.vao());
-        gl.delete_buffer(buffers.vbo());
-        gl.delete_buffer(buffers.ebo());
+
+        if !buffers.is_shared() {
+            gl.delete_buffer(buffers.vbo());
+            gl.delete_buffer(buffers.ebo());
+        }
     }
 }
 
-pub fn free_objects<'a>(gl: &Context, objects: impl Iterator<Item = &'a Object>) {
-    objects.for_each(|obj| free_buffers(gl, obj.buffers()));
+pub fn clean_up(gl: &Context, programs: Shaders, objects: &mut RawObjects) {
+    programs.delete(gl);
+    objects.clear(gl);
 }
 
-pub fn clean_up<'a>(gl: &Context, programs: Shaders, objects: impl Iterator<Item = &'a Object>) {
-    programs.delete(gl);
-    free_objects(gl, objects);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glow::{INVALID_OPERATION, NO_ERROR, OUT_OF_MEMORY};
+
+    #[test]
+    fn injected_gl_errors_are_reported() {
+        assert!(gl_result(NO_ERROR, "draw").is_ok());
+
+        for code in [INVALID_OPERATION, OUT_OF_MEMORY] {
+            match gl_result(code, "draw") {
+                Err(Error::GlError {
+                    code: found,
+                    context,
+                }) => {
+                    assert_eq!(found, code);
+                    assert_eq!(context, "draw");
+                }
+                other => panic!("Expected a GL error, found {:?}", other),
+            }
+        }
+    }
 }
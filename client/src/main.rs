@@ -7,8 +7,8 @@ use crossbeam_channel::{bounded, Receiver, Sender};
 use glow::{HasContext, FILL, FRONT_AND_BACK, LINE};
 use sdl2::{
     event::{Event, EventSender, WindowEvent},
-    keyboard::Keycode,
-    video::{SwapInterval, Window},
+    keyboard::{Keycode, Scancode},
+    video::SwapInterval,
     EventPump,
 };
 use std::{
@@ -17,7 +17,7 @@ use std::{
         Arc,
     },
     thread::{spawn, JoinHandle},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use sync_select::*;
 
@@ -52,10 +52,90 @@ fn handle_game_events(s: &SyncSelect, receiver: Receiver<GameEvent>, sender: Eve
     });
 }
 
+/// The scene file of the save (F5) and load (F9) hotkeys.
+const SCENE_PATH: &str = "scene.blzs";
+
+/// Handle the keys that aren't part of the movement input (see [`try_from_scancode`]).
+fn process_hotkey(
+    gl: &GL,
+    programs: &Shaders,
+    key: Scancode,
+    renderer: &mut Renderer,
+    objects: ObjectsRef,
+) {
+    match key {
+        Scancode::F3 => {
+            let shown = renderer.toggle_bounds();
+            debug!("[debug] Bounds {}", if shown { "shown" } else { "hidden" })
+        }
+        Scancode::F4 => match renderer.toggle_fxaa(gl) {
+            Ok(enabled) => info!("[render] FXAA {}", if enabled { "on" } else { "off" }),
+            Err(e) => error!("[render] Failed to enable FXAA: {}", e),
+        },
+        Scancode::F5 => match objects.read().save(SCENE_PATH) {
+            Ok(()) => info!("[scene] Saved into {}", SCENE_PATH),
+            Err(e) => error!("[scene] Failed to save: {}", e),
+        },
+        Scancode::F6 => {
+            // toggle half resolution
+            let scale = if renderer.render_scale() == 1.0 {
+                0.5
+            } else {
+                1.0
+            };
+
+            match renderer.set_render_scale(gl, scale) {
+                Ok(()) => info!("[render] Render scale {}", scale),
+                Err(e) => error!("[render] Failed to scale rendering: {}", e),
+            }
+        }
+        Scancode::F7 => {
+            let paused = renderer.toggle_pause();
+            debug!(
+                "[debug] Objects {}",
+                if paused { "frozen" } else { "resumed" }
+            )
+        }
+        Scancode::F8 => renderer.step_once(),
+        Scancode::F9 => {
+            // the server keeps updating the objects that are still around
+            let mut objects = objects.write();
+            objects.clear(gl);
+
+            match objects.load(gl, programs, SCENE_PATH) {
+                Ok(len) => info!("[scene] Loaded {} objects from {}", len, SCENE_PATH),
+                Err(e) => error!("[scene] Failed to load: {}", e),
+            }
+        }
+        Scancode::F10 => match renderer.toggle_recording(gl, programs.normal()) {
+            Ok(true) => info!("[ghost] Recording"),
+            Ok(false) => info!("[ghost] Stopped recording"),
+            Err(e) => error!("[ghost] Failed to create a ghost: {}", e),
+        },
+        Scancode::F12 => renderer.request_screenshot(),
+        _ => (),
+    }
+}
+
+/// Move the user `steps` times by `keys`, keeping them out of solid objects.
+fn move_player(cam: &Camera, objects: ObjectsRef, keys: Flags, steps: u16) {
+    let mut cam = cam.write();
+
+    for _ in 0..steps {
+        cam.input(keys);
+
+        let correction = objects.read().resolve_player(*cam.pos());
+        if correction != Vector::zeros() {
+            cam.attr_mut().eye += correction;
+            cam.upt();
+        }
+    }
+}
+
 fn process_raw_events(
     gl: &GL,
     programs: &Shaders,
-    window: Window,
+    renderer: &mut Renderer,
     mut ep: EventPump,
     (cam, objects, running): (Camera, ObjectsRef, Arc<AtomicBool>),
     (ms_verify_sender, kb_verify_sender): (Sender<bool>, Sender<bool>),
@@ -80,28 +160,43 @@ fn process_raw_events(
                                     gl.viewport(0, 0, w, h);
                                 }
                                 cam.write().upt_aspect_ratio(w, h);
+                                renderer.resize(gl, w, h);
+                            }
+                            let dt = renderer.tick();
+                            {
+                                let mut objects = objects.write();
+
+                                // materialize (some of) the objects that arrived since
+                                objects.process_spawn_queue(
+                                    gl,
+                                    RawObjects::SPAWN_BUDGET,
+                                    Instant::now,
+                                )?;
+                                objects.refresh_bvh();
+                                renderer.update(gl, &cam.read(), &mut objects, dt);
+
+                                // the cursor is locked to the center of the window
+                                let attr = cam.read().attr();
+                                objects.update_hover(attr.eye, attr.target);
                             }
+
                             // render a frame
-                            display(gl, &window, &cam.read(), &objects.read());
+                            renderer.display(gl, &cam.read(), &objects.read());
                         }
                         GameEvent::Object(action) => {
-                            match action {
-                                ObjectAction::Add { data } => {
-                                    let mut obj =
-                                        Object::create_cube_with(gl, programs.normal(), data)?;
-
+                            match *action {
+                                ObjectAction::Add { mut data } => {
                                     // initial transformations if player
-                                    if obj.data().player_ref().is_some() {
-                                        obj.data_mut().transform_upt();
+                                    if data.player_ref().is_some() {
+                                        data.transform_upt();
                                     }
 
-                                    objects.write().insert(obj);
+                                    // created within the budget of the upcoming frames
+                                    objects.write().spawn_deferred(programs.normal(), data);
                                 }
 
                                 ObjectAction::Rem { id } => {
-                                    if let Some(obj) = objects.write().remove(id) {
-                                        free_buffers(gl, obj.buffers());
-                                    }
+                                    objects.write().delete(gl, id);
                                 }
 
                                 ObjectAction::Upt { data } => {
@@ -133,7 +228,17 @@ fn process_raw_events(
                                             }
                                         },
                                         Input::Keyboard(flags) => {
-                                            cam.write().input(flags);
+                                            move_player(&cam, objects, flags, 1);
+                                            kb_verify_sender.send(true)?;
+                                        }
+                                        Input::Held { keys, ticks } => {
+                                            // one step per held tick, like the server
+                                            move_player(
+                                                &cam,
+                                                objects,
+                                                keys,
+                                                ticks.min(MAX_HELD_TICKS),
+                                            );
                                             kb_verify_sender.send(true)?;
                                         }
                                     };
@@ -181,6 +286,8 @@ fn process_raw_events(
                             mode = !mode;
                         }
                     }
+                } else {
+                    process_hotkey(gl, programs, key, renderer, objects);
                 }
             }
 
@@ -210,7 +317,7 @@ fn main() -> Result {
     ev.register_custom_event::<GameEvent>()?;
 
     // program shaders
-    let programs = init_shaders(&gl)?;
+    let programs = init_shaders(&gl, cfg.log_depth())?;
 
     // SDL's built-in timer subsystem
     let timer = sdl.timer()?;
@@ -255,28 +362,39 @@ fn main() -> Result {
         // basic 'light' structure
         raw.new_light(
             &gl,
-            -128,
+            LOCAL_IDS.start,
             programs.simple(),
             Vector::new(3.0, 2.0, -4.0),
             Vector::new(0.5, 0.5, 0.5),
             Color::new([1.0, 1.0, 0.8, 1.0], true),
         )?;
+        raw.set_sun(Some(LOCAL_IDS.start));
 
         // basic 'land' structure
         raw.new_cube(
             &gl,
-            -127,
+            LOCAL_IDS.start + 1,
             programs.normal(),
             Vector::new(0.0, -2.0, 0.0),
             Vector::new(7.5, 0.1, 7.5),
             Color::new([1.0, 0.5, 0.31, 0.9], false),
             RawObjectDataUnit::Basic,
         )?;
+
+        // fail loudly instead of rendering nothing
+        if let Err(problems) = raw.validate() {
+            for (id, problem) in problems {
+                error!("[validate] [{}] {}", id, problem)
+            }
+            return Err("Invalid scene".into());
+        }
         Objects::new(raw)
     };
 
     // the user's camera
     let cam = Camera::new(window.size());
+    let day_length = cfg.day_length();
+    cam.write().set_log_depth(cfg.log_depth());
 
     // mouse/keyboard facilitation channels
     let (ms_verify_sender, ms_verify_receiver) = bounded::<bool>(1);
@@ -308,11 +426,15 @@ fn main() -> Result {
     // post short-circuitry handler
     let _ss = handle_sync_select(s, event_sender);
 
+    // presents every frame
+    let mut renderer = Renderer::new(&gl, window, &programs)?;
+    renderer.set_day_length(day_length);
+
     // main thread
     if let Err(e) = process_raw_events(
         &gl,
         &programs,
-        window,
+        &mut renderer,
         ep,
         (cam, &objects, running),
         (kb_verify_sender, ms_verify_sender),
@@ -322,7 +444,8 @@ fn main() -> Result {
     }
 
     // clean everything up
-    clean_up(&gl, programs, objects.read().iter());
+    renderer.delete(&gl);
+    clean_up(&gl, programs, &mut objects.write());
 
     Ok(())
 }
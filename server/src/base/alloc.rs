@@ -0,0 +1,150 @@
+use crate::*;
+use std::collections::BTreeSet;
+
+/// The ids of a single kind taken so far.
+#[derive(Debug, Default)]
+struct Namespace {
+    /// The number of ids ever taken.
+    next: u16,
+    /// Ids taken then released, handed out again before any new one.
+    released: BTreeSet<Id>,
+}
+
+/// Hands out object ids, the server being their sole authority.
+///
+/// Each kind draws from its own range (players are non-negative, everything else is
+/// negative, except for [`LOCAL_IDS`]), so ids of different kinds never collide.
+/// Released ids are reused (lowest first) before new ones are taken.
+#[derive(Debug, Default)]
+pub struct IdAllocator {
+    players: Mutex<Namespace>,
+    basics: Mutex<Namespace>,
+}
+
+impl IdAllocator {
+    /// The first id and the number of ids of `kind`.
+    const fn namespace(kind: RawObjectDataUnit) -> (Id, u16) {
+        match kind {
+            RawObjectDataUnit::Player => (0, Id::MAX as u16 + 1),
+            RawObjectDataUnit::Basic => (LOCAL_IDS.end, LOCAL_IDS.end.unsigned_abs() as u16),
+        }
+    }
+
+    const fn ids(&self, kind: RawObjectDataUnit) -> &Mutex<Namespace> {
+        match kind {
+            RawObjectDataUnit::Player => &self.players,
+            RawObjectDataUnit::Basic => &self.basics,
+        }
+    }
+
+    const fn id(kind: RawObjectDataUnit, n: u16) -> Option<Id> {
+        let (first, len) = Self::namespace(kind);
+
+        if n < len {
+            Some(first + n as Id)
+        } else {
+            None
+        }
+    }
+
+    /// The id the next allocation of `kind` returns (if any remain).
+    pub fn peek(&self, kind: RawObjectDataUnit) -> Option<Id> {
+        let ids = self.ids(kind).lock();
+
+        match ids.released.first() {
+            Some(&id) => Some(id),
+            None => Self::id(kind, ids.next),
+        }
+    }
+
+    /// Take the next unique id of `kind`, returning `None` once every one is taken.
+    pub fn allocate(&self, kind: RawObjectDataUnit) -> Option<Id> {
+        let mut ids = self.ids(kind).lock();
+
+        if let Some(id) = ids.released.pop_first() {
+            return Some(id);
+        }
+        let id = Self::id(kind, ids.next)?;
+        ids.next += 1;
+        Some(id)
+    }
+
+    /// Give back `id` of `kind` (e.g., once its object is removed), so it can be reused.
+    ///
+    /// Returns `false` (ignoring it) if `id` isn't currently taken.
+    pub fn release(&self, kind: RawObjectDataUnit, id: Id) -> bool {
+        let (first, _) = Self::namespace(kind);
+        let mut ids = self.ids(kind).lock();
+
+        let taken = id
+            .checked_sub(first)
+            .is_some_and(|n| n >= 0 && (n as u16) < ids.next);
+
+        taken && ids.released.insert(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kinds_never_collide_nor_take_local_ids() {
+        let ids = IdAllocator::default();
+
+        let players = (0..=Id::MAX)
+            .map(|_| ids.allocate(RawObjectDataUnit::Player))
+            .collect::<Option<Vec<_>>>()
+            .unwrap();
+        let basics =
+            std::iter::from_fn(|| ids.allocate(RawObjectDataUnit::Basic)).collect::<Vec<_>>();
+
+        assert_eq!(ids.allocate(RawObjectDataUnit::Player), None);
+        assert_eq!(basics.len(), LOCAL_IDS.end.unsigned_abs() as usize);
+        assert!(players.iter().all(|id| *id >= 0));
+        assert!(basics.iter().all(|id| *id < 0 && !LOCAL_IDS.contains(id)));
+    }
+
+    #[test]
+    fn released_ids_are_reused_first() {
+        let ids = IdAllocator::default();
+        let kind = RawObjectDataUnit::Player;
+
+        for _ in 0..3 {
+            ids.allocate(kind);
+        }
+        assert!(ids.release(kind, 1));
+        assert!(!ids.release(kind, 1));
+        assert!(!ids.release(kind, 3));
+
+        assert_eq!(ids.peek(kind), Some(1));
+        assert_eq!(ids.allocate(kind), Some(1));
+        assert_eq!(ids.allocate(kind), Some(3));
+    }
+
+    #[test]
+    fn concurrent_spawns_get_distinct_ids() {
+        let ids = IdAllocator::default();
+
+        let mut taken = std::thread::scope(|s| {
+            let handles = (0..4)
+                .map(|_| {
+                    s.spawn(|| {
+                        (0..16)
+                            .map(|_| ids.allocate(RawObjectDataUnit::Basic).unwrap())
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        taken.sort_unstable();
+        taken.dedup();
+        assert_eq!(taken.len(), 64);
+    }
+}
@@ -20,6 +20,18 @@ pub struct Config {
     /// Server ticks/sec
     #[arg(long, default_value_t = 128, value_parser = value_parser!(u16).range(1..1024))]
     tps: u16,
+
+    /// The fewest ticks/sec the server slows down to under load
+    #[arg(long, default_value_t = 32, value_parser = value_parser!(u16).range(1..1024))]
+    min_tps: u16,
+
+    /// Sustained UDP messages/sec allowed per client
+    #[arg(long, default_value_t = 1024.0)]
+    rate_limit: f32,
+
+    /// UDP messages allowed per client at once
+    #[arg(long, default_value_t = 256)]
+    burst_limit: u32,
 }
 
 impl Config {
@@ -34,6 +46,16 @@ impl Config {
     pub fn tps(&self) -> Duration {
         calc_tps(self.tps)
     }
+
+    /// A fresh governor of the tick rate (see [`TickGovernor`]).
+    pub fn tick_governor(&self) -> TickGovernor {
+        TickGovernor::new(self.tps(), calc_tps(self.min_tps))
+    }
+
+    /// A fresh limiter for a single client.
+    pub fn rate_limiter(&self) -> RateLimiter {
+        RateLimiter::new(self.rate_limit, self.burst_limit)
+    }
 }
 
 impl Default for Config {
@@ -4,6 +4,9 @@ pub type Result<T = (), E = Error> = std::result::Result<T, E>;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
+    #[error("Rate limited: {0}")]
+    RateLimited(SocketAddr),
+
     #[error(transparent)]
     Blazed(BlazedError),
 }
@@ -0,0 +1,83 @@
+use std::time::Instant;
+
+/// A token bucket, allowing `burst` messages at once, then refilling at `rate` messages/sec.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimiter {
+    rate: f32,
+    burst: f32,
+    tokens: f32,
+    last: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(rate: f32, burst: u32) -> Self {
+        Self {
+            rate,
+            burst: burst as f32,
+            tokens: burst as f32,
+            last: None,
+        }
+    }
+
+    /// Consume a token (if available) for a message received at `now`.
+    pub fn allow(&mut self, now: Instant) -> bool {
+        if let Some(last) = self.last {
+            let elapsed = now.saturating_duration_since(last).as_secs_f32();
+            self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        }
+        self.last = Some(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn burst_is_allowed_then_limited() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new(10.0, 3);
+
+        assert!((0..3).all(|_| limiter.allow(now)));
+        assert!(!limiter.allow(now));
+    }
+
+    #[test]
+    fn tokens_refill_at_rate() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new(10.0, 2);
+        while limiter.allow(now) {}
+
+        // a token every 100ms
+        assert!(!limiter.allow(now + Duration::from_millis(50)));
+        assert!(limiter.allow(now + Duration::from_millis(150)));
+        assert!(!limiter.allow(now + Duration::from_millis(150)));
+    }
+
+    #[test]
+    fn tokens_never_exceed_burst() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new(10.0, 2);
+        limiter.allow(now);
+
+        let later = now + Duration::from_secs(60);
+        assert_eq!((0..5).filter(|_| limiter.allow(later)).count(), 2);
+    }
+
+    #[test]
+    fn time_going_backwards_adds_nothing() {
+        let now = Instant::now() + Duration::from_secs(1);
+        let mut limiter = RateLimiter::new(10.0, 1);
+
+        assert!(limiter.allow(now));
+        assert!(!limiter.allow(now - Duration::from_secs(1)));
+    }
+}
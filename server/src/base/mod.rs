@@ -1,9 +1,21 @@
+mod alloc;
 mod cfg;
 mod err;
+mod limit;
 mod net;
+mod spawn;
+mod state;
+mod stats;
+mod tick;
 
+pub use alloc::*;
 pub use cfg::*;
 pub use err::*;
+pub use limit::*;
 pub use net::*;
+pub use spawn::*;
+pub use state::*;
+pub use stats::*;
+pub use tick::*;
 
 pub use blazed_demo::*;
@@ -1,81 +1,261 @@
 use crate::*;
 use crossbeam_channel::{Receiver, Sender};
 
+/// Verify the hello of a joining client, returning the id it's going to be given.
+///
+/// The id is only peeked, it's taken once the client has fully joined.
+fn admit(packet: Packet, ids: &IdAllocator) -> Result<Id> {
+    packet.into_client_handshake()?;
+
+    ids.peek(RawObjectDataUnit::Player)
+        .ok_or_else(|| "No available player ids".into())
+}
+
 fn handshake(
     tcp: &TcpClient,
-    clients_udp: UdpClients,
+    clients_udp: &UdpClients,
     receiver_addr: &Receiver<SocketAddr>,
-    id: Id,
-) -> Result<SocketAddr> {
+    state: &ServerState,
+) -> Result<(Id, SocketAddr)> {
+    // accounted for once the handshake succeeds
+    let mut traffic = NetStats::default();
+
     debug!("TCP [ ][1] Receiving handshake");
     let mut buf = [0; PACKET_SIZE];
-    tcp.recv::<PacketKind, Packet, PACKET_SIZE>(&mut buf, PacketKind::Handshake)?
-        .into_client_handshake()?;
+    let packet = tcp.recv::<PacketKind, Packet, PACKET_SIZE>(&mut buf, PacketKind::Handshake)?;
+    traffic.recv(packet_len(&packet)?);
+    let id = admit(packet, state.ids())?;
 
     // reply with server handshake
     debug!("TCP [ ][2] Sending handshake");
-    tcp.send(&Packet::Handshake {
+    traffic.sent(tcp.send(&Packet::Handshake {
         handshake: Handshake::server(id),
-    })?;
+    })?);
 
     debug!("TCP [3][5] Waiting for UDP address");
     let addr = receiver_addr.recv()?;
 
     debug!("TCP [ ][6] Sending gamestates");
-    for &data in clients_udp.read().values() {
-        tcp.send(&Packet::AddObj { data })?;
+    for &data in clients_udp
+        .read()
+        .values()
+        .chain(state.spawned().read().values().map(|(_, data)| data))
+    {
+        traffic.sent(tcp.send(&Packet::AddObj { data })?);
     }
     debug!("TCP [ ][7] Finishing");
-    tcp.send(&Packet::Flush)?;
+    traffic.sent(tcp.send(&Packet::Flush)?);
 
-    Ok(addr)
+    state.stats().lock().insert(id, traffic);
+    Ok((id, addr))
 }
 
-fn _handle_alive(tcp: &TcpClient) -> Result<()> {
+/// Check a requested object against the rules, then allocate its id, confirming it to
+/// the requester before distributing it, returning the bytes sent.
+///
+/// Rejected requests (or those of which no ids remain) are only answered to the requester.
+fn handle_spawn(
+    tcp: &TcpClient,
+    request: SpawnRequest,
+    state: &ServerState,
+    sender: &Sender<Packet>,
+) -> Result<usize> {
+    let admitted = state.admit(&request);
+    let SpawnRequest {
+        client, request, ..
+    } = request;
+
+    let data = match admitted {
+        Ok(data) => data,
+        Err(reason) => {
+            warn!(
+                "[spawn] Rejected request {} of ({}): {:?}",
+                request, client, reason
+            );
+            return Ok(tcp.send(&Packet::SpawnReject { request, reason })?);
+        }
+    };
+
+    let sent = tcp.send(&Packet::SpawnConfirm { request, data })?;
+    sender.send(Packet::AddObj { data })?;
+    Ok(sent)
+}
+
+/// Register a hit-scan of `shooter` against the players as they were at `tick` (as seen
+/// by the shooter), distributing the hit (if any).
+///
+/// Ticks no longer kept (or yet to come) are ignored.
+fn handle_fire(
+    shooter: Id,
+    (tick, origin, dir): (u64, Vector, Vector),
+    state: &ServerState,
+    sender: &Sender<Packet>,
+) -> Result {
+    let target = match state.history().lock().snapshot_at(tick) {
+        Some(snapshot) => snapshot.raycast(&origin, &dir),
+        None => {
+            warn!("[fire] Tick {} of ({}) is not kept", tick, shooter);
+            return Ok(());
+        }
+    };
+
+    if let Some(target) = target.filter(|target| *target != shooter) {
+        sender.send(Packet::Hit { shooter, target })?
+    }
+    Ok(())
+}
+
+fn _handle_alive(
+    tcp: &TcpClient,
+    id: Id,
+    state: &ServerState,
+    sender: &Sender<Packet>,
+) -> Result<()> {
     let mut buf = [0; PACKET_SIZE];
     let spinner = SpinSleeper::default();
 
     loop {
-        tcp.recv::<_, Packet, PACKET_SIZE>(&mut buf, PacketKind::Ping)?;
-        tcp.send(&Packet::Ping)?;
+        let packet = tcp.recv::<_, Packet, PACKET_SIZE>(
+            &mut buf,
+            PacketKind::Ping | PacketKind::SpawnReq | PacketKind::RemObj | PacketKind::Fire,
+        )?;
+        let recv = packet_len(&packet)?;
+
+        let sent = match packet {
+            Packet::SpawnReq { request, data } => {
+                let request = SpawnRequest {
+                    client: id,
+                    request,
+                    data,
+                };
+                handle_spawn(tcp, request, state, sender)?
+            }
+            // only what the client requested itself
+            Packet::RemObj { id: object } => {
+                if state.despawn(id, object) {
+                    sender.send(Packet::RemObj { id: object })?
+                }
+                0
+            }
+            Packet::Fire { tick, origin, dir } => {
+                handle_fire(id, (tick, origin, dir), state, sender)?;
+                0
+            }
+            _ => tcp.send(&Packet::Ping)?,
+        };
+
+        if let Some(stats) = state.stats().lock().get_mut(&id) {
+            stats.recv(recv);
+            stats.sent(sent);
+        }
 
-        spinner.sleep(PING_MINIMUM);
+        // only pings are paced
+        if matches!(packet, Packet::Ping) {
+            spinner.sleep(PING_MINIMUM);
+        }
     }
 }
 
 fn handle_alive(
     tcp: TcpClient,
-    addr: SocketAddr,
-    clients_tcp: TcpClients,
-    clients_udp: UdpClients,
+    id: Id,
+    (clients_tcp, clients_udp): (TcpClients, UdpClients),
+    state: Arc<ServerState>,
     sender: Sender<Packet>,
 ) -> JoinHandle<Result> {
     spawn(move || {
-        if let Err(e) = _handle_alive(&tcp) {
+        if let Err(e) = _handle_alive(&tcp, id, &state, &sender) {
             warn!("{:?}", e)
         }
+        state.stats().lock().remove(&id);
 
-        if let Some(user) = clients_udp.write().remove(&addr) {
-            let id = user.id();
-
-            // remove client before send packet to TCP channel
-            clients_tcp.write().remove(&id);
-
-            // send packet to TCP channel
-            sender.send(Packet::RemObj { id })?
-        }
+        leave(id, (&clients_tcp, &clients_udp), &state, &sender)?;
         Ok(())
     })
 }
 
-fn handle_dist(s: &SyncSelect, clients_tcp: TcpClients, receiver_packet: Receiver<Packet>) {
+/// Free the player of the client `id` along with whatever it spawned, distributing the
+/// removals to every remaining client.
+///
+/// Returns `false` if the client already left (e.g., it was kicked).
+fn leave(
+    id: Id,
+    (clients_tcp, clients_udp): (&TcpClients, &UdpClients),
+    state: &ServerState,
+    sender: &Sender<Packet>,
+) -> Result<bool> {
+    let left = {
+        let mut clients_udp = clients_udp.write();
+        let before = clients_udp.len();
+        clients_udp.retain(|_, user| user.id() != id);
+        clients_udp.len() < before
+    };
+    if !left {
+        return Ok(false);
+    }
+
+    // remove client before send packet to TCP channel
+    clients_tcp.write().remove(&id);
+
+    // whatever it spawned leaves along with it
+    for object in state.despawn_owned(id) {
+        sender.send(Packet::RemObj { id: object })?
+    }
+
+    // the id may be taken by whoever joins next
+    state.ids().release(RawObjectDataUnit::Player, id);
+
+    // send packet to TCP channel
+    sender.send(Packet::RemObj { id })?;
+    Ok(true)
+}
+
+/// Disconnect the specified client, telling it why first, then free its player (and
+/// whatever it spawned), distributing the removals to every remaining client.
+pub fn kick(
+    id: Id,
+    reason: DisconnectReason,
+    clients: (&TcpClients, &UdpClients),
+    state: &ServerState,
+    sender: &Sender<Packet>,
+) -> Result {
+    // taken out first, so nothing else is sent after the reason
+    let tcp = clients
+        .0
+        .write()
+        .remove(&id)
+        .ok_or(format!("[kick] No client ({})", id))?;
+
+    info!("[kick] Disconnecting ({}): {:?}", id, reason);
+
+    // close regardless, a client which can't be told is disconnected all the same
+    let sent = tcp.send(&Packet::Disconnect { reason });
+    let closed = tcp.close();
+
+    // its connection handler finds it gone
+    leave(id, clients, state, sender)?;
+
+    closed?;
+    sent.map(|_| ()).map_err(Into::into)
+}
+
+fn handle_dist(
+    s: &SyncSelect,
+    clients_tcp: TcpClients,
+    receiver_packet: Receiver<Packet>,
+    stats: Stats,
+) {
     s.spawn(move || -> Result {
         loop {
             let packet = receiver_packet.recv()?;
 
             // distribute updates
-            for tcp in clients_tcp.read().values() {
-                tcp.send(&packet)?;
+            for (id, tcp) in clients_tcp.read().iter() {
+                let bytes = tcp.send(&packet)?;
+
+                if let Some(stats) = stats.lock().get_mut(id) {
+                    stats.sent(bytes)
+                }
             }
         }
     });
@@ -84,11 +264,9 @@ fn handle_dist(s: &SyncSelect, clients_tcp: TcpClients, receiver_packet: Receive
 fn handle_incoming(
     s: &SyncSelect,
     tcp_listener: TcpServer,
-    clients_tcp: TcpClients,
-    clients_udp: UdpClients,
-    sender_packet: Sender<Packet>,
-    receiver_addr: Receiver<SocketAddr>,
-    id: Arc<AtomicId>,
+    (clients_tcp, clients_udp): (TcpClients, UdpClients),
+    (sender_packet, receiver_addr): (Sender<Packet>, Receiver<SocketAddr>),
+    state: Arc<ServerState>,
 ) {
     s.spawn(move || -> Result {
         for tcp in tcp_listener.incoming() {
@@ -99,17 +277,12 @@ fn handle_incoming(
                 continue;
             };
 
-            match handshake(
-                &tcp,
-                clients_udp.clone(),
-                &receiver_addr,
-                id.load(Ordering::Relaxed),
-            ) {
-                Ok(addr) => {
+            match handshake(&tcp, &clients_udp, &receiver_addr, &state) {
+                Ok((id, addr)) => {
                     debug!("TCP [ ][8] Handshake complete");
 
-                    // increment if handshake was successful
-                    let id = id.fetch_add(1, Ordering::Relaxed);
+                    // only taken if handshake was successful
+                    state.ids().allocate(RawObjectDataUnit::Player);
 
                     // contruct client's initial object data
                     let data = ObjectData::new(
@@ -129,9 +302,9 @@ fn handle_incoming(
 
                     _ = handle_alive(
                         tcp,
-                        addr,
-                        clients_tcp.clone(),
-                        clients_udp.clone(),
+                        id,
+                        (clients_tcp.clone(), clients_udp.clone()),
+                        state.clone(),
                         sender_packet.clone(),
                     );
                 }
@@ -145,27 +318,113 @@ fn handle_incoming(
 pub fn init_tcp(
     s: &SyncSelect,
     tcp: TcpServer,
-    clients_tcp: TcpClients,
-    clients_udp: UdpClients,
-    sender_packet: Sender<Packet>,
-    receiver_addr: Receiver<SocketAddr>,
-    receiver_packet: Receiver<Packet>,
-    id: Arc<AtomicId>,
+    (clients_tcp, clients_udp): (TcpClients, UdpClients),
+    (sender_packet, receiver_addr, receiver_packet): (
+        Sender<Packet>,
+        Receiver<SocketAddr>,
+        Receiver<Packet>,
+    ),
+    state: Arc<ServerState>,
 ) {
     s.spawn_with(move |s| -> Result {
+        let stats = state.stats().clone();
+
         handle_incoming(
             s,
             tcp,
-            clients_tcp.clone(),
-            clients_udp,
-            sender_packet,
-            receiver_addr,
-            id,
+            (clients_tcp.clone(), clients_udp),
+            (sender_packet, receiver_addr),
+            state,
         );
 
         // init TCP distribution thread
-        handle_dist(s, clients_tcp, receiver_packet);
+        handle_dist(s, clients_tcp, receiver_packet, stats);
 
         Ok(())
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::unbounded;
+    use std::io::Read;
+
+    #[test]
+    fn kicking_despawns_and_tells_the_others() {
+        let state = ServerState::new(SpawnRules::default(), RateLimiter::new(10.0, 10));
+        let (clients_tcp, clients_udp) = (TcpClients::default(), UdpClients::default());
+        let (sender, receiver) = unbounded();
+
+        // the kicked client and a remaining one
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut peers = Vec::new();
+        for id in [0, 1] {
+            let tcp = TcpClient::new(listener.local_addr().unwrap()).unwrap();
+            clients_tcp.write().insert(id, tcp);
+            peers.push(listener.accept().unwrap().0);
+
+            let player = state.ids().allocate(RawObjectDataUnit::Player).unwrap();
+            let data = ObjectData::new(
+                player,
+                Color::WHITE,
+                RawObjectData::Player(PlayerData::new(Vector::zeros())),
+            );
+            clients_udp
+                .write()
+                .insert(get_socket_addr(9000 + id as u16), data);
+        }
+
+        let object = state.ids().allocate(RawObjectDataUnit::Basic).unwrap();
+        state.add_spawned(
+            0,
+            ObjectData::new(
+                object,
+                Color::WHITE,
+                RawObjectData::Basic(BasicData::new(Vector::zeros(), Vector::repeat(1.0))),
+            ),
+        );
+
+        let clients = (&clients_tcp, &clients_udp);
+        kick(0, DisconnectReason::Kicked, clients, &state, &sender).unwrap();
+
+        // told why, then closed
+        let mut told = Vec::new();
+        peers[0].read_to_end(&mut told).unwrap();
+        assert_eq!(
+            told.len(),
+            packet_len(&Packet::Disconnect {
+                reason: DisconnectReason::Kicked
+            })
+            .unwrap()
+        );
+
+        // everything it had is distributed as removed (once)
+        let removed = receiver.try_iter().collect::<Vec<_>>();
+        assert!(matches!(
+            removed[..],
+            [Packet::RemObj { id: a }, Packet::RemObj { id: 0 }] if a == object
+        ));
+        assert!(state.spawned().read().is_empty());
+        assert_eq!(state.ids().peek(RawObjectDataUnit::Player), Some(0));
+        assert!(!leave(0, clients, &state, &sender).unwrap());
+
+        // to whoever remains
+        assert_eq!(clients_tcp.read().keys().copied().collect::<Vec<_>>(), [1]);
+        assert!(clients_udp.read().values().all(|user| user.id() == 1));
+    }
+
+    #[test]
+    fn mismatched_hello_allocates_no_id() {
+        let ids = IdAllocator::default();
+        let hello = |version| Packet::Handshake {
+            handshake: Handshake::hello(version),
+        };
+
+        assert!(admit(hello(PROTOCOL_VERSION + 1), &ids).is_err());
+        assert_eq!(ids.peek(RawObjectDataUnit::Player), Some(0));
+
+        assert_eq!(admit(hello(PROTOCOL_VERSION), &ids).ok(), Some(0));
+        assert_eq!(ids.allocate(RawObjectDataUnit::Player), Some(0));
+    }
+}
@@ -1,11 +1,11 @@
 use crate::*;
 use crossbeam_channel::{bounded, Receiver, Sender};
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     net::SocketAddr,
     sync::atomic::AtomicBool,
     thread::{park, Thread},
-    time::Duration,
+    time::Instant,
 };
 
 fn handle_dist(
@@ -14,13 +14,16 @@ fn handle_dist(
     clients_udp: UdpClients,
     updated: Arc<Mutex<HashSet<SocketAddr>>>,
     advance: Arc<AtomicBool>,
-    tps: Duration,
+    mut governor: TickGovernor,
+    state: Arc<ServerState>,
 ) -> JoinHandle<Result> {
     s.spawn(move || -> Result {
         let (spinner, backoff): (SpinSleeper, Backoff) = Default::default();
+        let mut tick = 0;
 
         loop {
-            spinner.sleep(tps);
+            spinner.sleep(governor.tick());
+            tick += 1;
             while !advance.load(Ordering::SeqCst) {
                 if backoff.is_completed() {
                     park();
@@ -28,6 +31,7 @@ fn handle_dist(
                     backoff.snooze();
                 }
             }
+            let start = Instant::now();
 
             // distribute updates to each player
             for upt_addr in updated.lock().drain() {
@@ -38,12 +42,43 @@ fn handle_dist(
                 };
 
                 // send update to each client
-                for addr in clients_udp.read().keys() {
-                    if let Err(e) = udp.send_to(&Packet::UptObj { data }, addr) {
-                        error!("{:?}", e)
+                for (addr, client) in clients_udp.read().iter() {
+                    match udp.send_to(&Packet::UptObj { data, tick }, addr) {
+                        Ok(bytes) => {
+                            if let Some(stats) = state.stats().lock().get_mut(&client.id()) {
+                                stats.sent(bytes)
+                            }
+                        }
+                        Err(e) => error!("{:?}", e),
                     }
                 }
             }
+
+            // the recent positions of every player (for lag compensation)
+            state.history().lock().record(Snapshot::new(
+                tick,
+                clients_udp
+                    .read()
+                    .values()
+                    .map(|data| (data.id(), *data.pos())),
+            ));
+
+            // slow down (or recover) according to the load
+            if let Some(tick_rate) = governor.record(start.elapsed()) {
+                warn!("Tick rate adapted to {:?}", tick_rate);
+
+                for (addr, client) in clients_udp.read().iter() {
+                    match udp.send_to(&Packet::TickRate { tick_rate }, addr) {
+                        Ok(bytes) => {
+                            if let Some(stats) = state.stats().lock().get_mut(&client.id()) {
+                                stats.sent(bytes)
+                            }
+                        }
+                        Err(e) => error!("{:?}", e),
+                    }
+                }
+            }
+
             backoff.reset();
             advance.store(false, Ordering::Release);
         }
@@ -71,6 +106,12 @@ fn _handle_packets(
             Mouse::Motion { xrel, yrel } => obj.attr_mut().look_at(xrel, yrel),
         },
         Input::Keyboard(kb) => obj.attr_mut().input(kb),
+        Input::Held { keys, ticks } => {
+            // one step per held tick (bounded to avoid abuse)
+            for _ in 0..ticks.min(MAX_HELD_TICKS) {
+                obj.attr_mut().input(keys)
+            }
+        }
     };
     Ok(addr)
 }
@@ -102,7 +143,8 @@ fn init_write(
     udp: UdpServer,
     clients_udp: UdpClients,
     receiver_packet: Receiver<(Packet, SocketAddr)>,
-    tps: Duration,
+    governor: TickGovernor,
+    state: Arc<ServerState>,
 ) {
     let updated: Arc<Mutex<HashSet<SocketAddr>>> = Default::default();
     let advance: Arc<AtomicBool> = Default::default();
@@ -113,7 +155,8 @@ fn init_write(
         clients_udp.clone(),
         updated.clone(),
         advance.clone(),
-        tps,
+        governor,
+        state,
     );
 
     handle_packets(
@@ -130,17 +173,39 @@ fn handle_incoming(
     s: &SyncSelect,
     udp: UdpServer,
     clients_udp: UdpClients,
-    sender_packet: Sender<(Packet, SocketAddr)>,
-    sender_addr: Sender<SocketAddr>,
+    (sender_packet, sender_addr): (Sender<(Packet, SocketAddr)>, Sender<SocketAddr>),
+    state: Arc<ServerState>,
 ) {
     s.spawn(move || -> Result {
         let mut buf = [0; PACKET_SIZE];
 
+        // per-client message limits
+        let mut limiters = HashMap::<SocketAddr, RateLimiter>::new();
+
         loop {
             match udp.recv_from(&mut buf, PacketKind::all()) {
                 Ok((packet, addr)) => {
                     // check if user already exists
-                    if clients_udp.read().contains_key(&addr) {
+                    let id = clients_udp.read().get(&addr).map(|data| data.id());
+
+                    if let Some(id) = id {
+                        // dropped packets still used up bandwidth
+                        if let (Some(stats), Ok(bytes)) =
+                            (state.stats().lock().get_mut(&id), packet_len(&packet))
+                        {
+                            stats.recv(bytes)
+                        }
+
+                        // drop flooding messages
+                        if !limiters
+                            .entry(addr)
+                            .or_insert_with(|| state.limiter())
+                            .allow(Instant::now())
+                        {
+                            warn!("{}", Error::RateLimited(addr));
+                            continue;
+                        }
+
                         // send to read channel
                         _ = sender_packet.try_send((packet, addr));
                         continue;
@@ -148,6 +213,9 @@ fn handle_incoming(
 
                     debug!("UDP [ ][3] Received handshake");
 
+                    // forget the limits of disconnected clients
+                    limiters.retain(|addr, _| clients_udp.read().contains_key(addr));
+
                     // validate packet
                     if let Err(e) = packet.into_client_handshake() {
                         error!("{:?}", e);
@@ -172,21 +240,28 @@ fn handle_incoming(
 
 pub fn init_udp(
     s: &SyncSelect,
-    udp_a: UdpServer,
-    udp_b: UdpServer,
+    (udp_a, udp_b): (UdpServer, UdpServer),
     clients_udp: UdpClients,
     sender_addr: Sender<SocketAddr>,
-    tps: Duration,
+    governor: TickGovernor,
+    state: Arc<ServerState>,
 ) {
     // real-time game data channel
     let (sender_packet, receiver_packet) = bounded(8);
 
     s.spawn_with(move |s| -> Result {
         // handle outgoing
-        init_write(s, udp_a, clients_udp.clone(), receiver_packet, tps);
+        init_write(
+            s,
+            udp_a,
+            clients_udp.clone(),
+            receiver_packet,
+            governor,
+            state.clone(),
+        );
 
         // handle incoming UDP packets
-        handle_incoming(s, udp_b, clients_udp, sender_packet, sender_addr);
+        handle_incoming(s, udp_b, clients_udp, (sender_packet, sender_addr), state);
 
         Ok(())
     });
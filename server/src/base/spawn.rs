@@ -0,0 +1,45 @@
+use crate::*;
+use std::fmt::Debug;
+
+/// A request of a client to spawn an object (see [`SpawnRules::set_spawn_validator`]).
+#[derive(Clone, Copy, Debug)]
+pub struct SpawnRequest {
+    /// The id of the requesting player.
+    pub client: Id,
+    /// The number the client tagged the request with.
+    pub request: u16,
+    /// The requested object (its id is yet to be allocated).
+    pub data: ObjectData,
+}
+
+type SpawnValidator = dyn Fn(&SpawnRequest) -> Result<(), RejectReason> + Send + Sync;
+
+/// The game rules every spawn request is checked against before its object is created.
+#[derive(Default)]
+pub struct SpawnRules {
+    validator: RwLock<Option<Box<SpawnValidator>>>,
+}
+
+impl SpawnRules {
+    /// Check every following request with `validator`, replacing any previous one.
+    ///
+    /// Rejected requests are answered with [`Packet::SpawnReject`] and create nothing.
+    pub fn set_spawn_validator(&self, validator: Box<SpawnValidator>) {
+        *self.validator.write() = Some(validator)
+    }
+
+    pub fn validate(&self, request: &SpawnRequest) -> Result<(), RejectReason> {
+        self.validator
+            .read()
+            .as_ref()
+            .map_or(Ok(()), |f| f(request))
+    }
+}
+
+impl Debug for SpawnRules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpawnRules")
+            .field("validator", &self.validator.read().is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,189 @@
+use crate::*;
+
+/// What the connection handlers share besides the clients themselves.
+#[derive(Debug)]
+pub struct ServerState {
+    ids: IdAllocator,
+    spawned: Spawned,
+    rules: SpawnRules,
+    limiter: RateLimiter,
+    stats: Stats,
+    history: Mutex<SnapshotHistory>,
+}
+
+impl ServerState {
+    /// Check spawn requests against `rules`, limiting every client like `limiter`.
+    pub fn new(rules: SpawnRules, limiter: RateLimiter) -> Self {
+        Self {
+            ids: Default::default(),
+            spawned: Default::default(),
+            rules,
+            limiter,
+            stats: Default::default(),
+            history: Mutex::new(SnapshotHistory::new(MAX_HISTORY_TICKS)),
+        }
+    }
+
+    /// The sole authority of object ids.
+    pub const fn ids(&self) -> &IdAllocator {
+        &self.ids
+    }
+
+    /// The objects spawned on request of clients (along with the id of the requester).
+    pub const fn spawned(&self) -> &Spawned {
+        &self.spawned
+    }
+
+    /// Record `data` (its id already allocated) as spawned on request of `owner`.
+    pub fn add_spawned(&self, owner: Id, data: ObjectData) {
+        self.spawned.write().insert(data.id(), (owner, data));
+    }
+
+    /// Check `request` against the rules, then allocate the id of its object and record it
+    /// as spawned, returning the object (with its id).
+    ///
+    /// Nothing is created (nor allocated) if the request is rejected.
+    pub fn admit(&self, request: &SpawnRequest) -> Result<ObjectData, RejectReason> {
+        self.rules.validate(request)?;
+
+        let mut data = request.data;
+        let id = self.ids.allocate(data.kind()).ok_or(RejectReason::NoIds)?;
+
+        data.set_id(id);
+        self.add_spawned(request.client, data);
+        Ok(data)
+    }
+
+    /// Remove the object `id` if spawned on request of `owner`, releasing its id.
+    pub fn despawn(&self, owner: Id, id: Id) -> bool {
+        let mut spawned = self.spawned.write();
+
+        let Some(&(by, data)) = spawned.get(&id) else {
+            return false;
+        };
+        if by != owner {
+            return false;
+        }
+        spawned.remove(&id);
+        self.ids.release(data.kind(), id);
+        true
+    }
+
+    /// Remove every object spawned on request of `owner` (e.g., once it left), releasing
+    /// their ids, returning them.
+    pub fn despawn_owned(&self, owner: Id) -> Vec<Id> {
+        let mut spawned = self.spawned.write();
+        let mut owned = Vec::new();
+
+        spawned.retain(|&id, (by, data)| {
+            if *by != owner {
+                return true;
+            }
+            self.ids.release(data.kind(), id);
+            owned.push(id);
+            false
+        });
+        owned
+    }
+
+    /// A fresh limiter for a single client.
+    pub const fn limiter(&self) -> RateLimiter {
+        self.limiter
+    }
+
+    /// The traffic of each connection.
+    pub const fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// The recent positions of every player (for lag compensation).
+    pub const fn history(&self) -> &Mutex<SnapshotHistory> {
+        &self.history
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> ServerState {
+        ServerState::new(SpawnRules::default(), RateLimiter::new(10.0, 10))
+    }
+
+    fn spawn(state: &ServerState, owner: Id) -> Id {
+        let id = state.ids().allocate(RawObjectDataUnit::Basic).unwrap();
+        let data = ObjectData::new(
+            id,
+            Color::WHITE,
+            RawObjectData::Basic(BasicData::new(Vector::zeros(), Vector::repeat(1.0))),
+        );
+        state.add_spawned(owner, data);
+        id
+    }
+
+    #[test]
+    fn requests_out_of_bounds_create_nothing() {
+        let rules = SpawnRules::default();
+        rules.set_spawn_validator(Box::new(|request| {
+            if request.data.pos().norm() <= 10.0 {
+                Ok(())
+            } else {
+                Err(RejectReason::OutOfBounds)
+            }
+        }));
+        let state = ServerState::new(rules, RateLimiter::new(10.0, 10));
+
+        let request = |pos| SpawnRequest {
+            client: 0,
+            request: 1,
+            data: ObjectData::new(
+                0,
+                Color::WHITE,
+                RawObjectData::Basic(BasicData::new(pos, Vector::repeat(1.0))),
+            ),
+        };
+        let next = state.ids().peek(RawObjectDataUnit::Basic);
+
+        let outside = request(Vector::new(20.0, 0.0, 0.0));
+        assert_eq!(state.admit(&outside).err(), Some(RejectReason::OutOfBounds));
+        assert!(state.spawned().read().is_empty());
+        assert_eq!(state.ids().peek(RawObjectDataUnit::Basic), next);
+
+        let inside = state.admit(&request(Vector::new(2.0, 0.0, 0.0))).unwrap();
+        assert_eq!(Some(inside.id()), next);
+        assert_eq!(
+            state.spawned().read().keys().copied().collect::<Vec<_>>(),
+            [inside.id()]
+        );
+    }
+
+    #[test]
+    fn despawning_releases_ids() {
+        let state = state();
+
+        // far more than there are ids of basic objects
+        for _ in 0..1000 {
+            let id = spawn(&state, 0);
+            assert!(!state.despawn(1, id));
+            assert!(state.despawn(0, id));
+        }
+        assert!(state.spawned().read().is_empty());
+    }
+
+    #[test]
+    fn leaving_despawns_only_what_was_requested() {
+        let state = state();
+        let mine = [spawn(&state, 0), spawn(&state, 0)];
+        let theirs = spawn(&state, 1);
+
+        let mut despawned = state.despawn_owned(0);
+        despawned.sort_unstable();
+
+        assert_eq!(despawned, mine);
+        assert_eq!(
+            state.spawned().read().keys().copied().collect::<Vec<_>>(),
+            [theirs]
+        );
+        assert_eq!(state.ids().peek(RawObjectDataUnit::Basic), Some(mine[0]));
+    }
+}
@@ -0,0 +1,73 @@
+use crate::*;
+use std::mem::take;
+
+/// Traffic of a single connection since the last [`NetStats::take`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NetStats {
+    pub bytes_sent: u64,
+    pub bytes_recv: u64,
+    pub packets_sent: u64,
+    pub packets_recv: u64,
+}
+
+impl NetStats {
+    /// Account for a packet of `bytes` sent to the client.
+    pub fn sent(&mut self, bytes: usize) {
+        self.bytes_sent += bytes as u64;
+        self.packets_sent += 1;
+    }
+
+    /// Account for a packet of `bytes` received from the client.
+    pub fn recv(&mut self, bytes: usize) {
+        self.bytes_recv += bytes as u64;
+        self.packets_recv += 1;
+    }
+
+    /// Return the accumulated traffic, starting over from zero.
+    pub fn take(&mut self) -> Self {
+        take(self)
+    }
+}
+
+/// Log (then reset) the traffic of every connection once per second.
+pub fn init_stats(s: &SyncSelect, stats: Stats) {
+    s.spawn(move || -> Result {
+        let spinner = SpinSleeper::default();
+
+        loop {
+            spinner.sleep(SECOND);
+
+            for (id, stats) in stats.lock().iter_mut() {
+                let NetStats {
+                    bytes_sent,
+                    bytes_recv,
+                    packets_sent,
+                    packets_recv,
+                } = stats.take();
+
+                debug!(
+                    "[{}] sent {} B/s ({} packets/s), received {} B/s ({} packets/s)",
+                    id, bytes_sent, packets_sent, bytes_recv, packets_recv
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sends_accumulate_until_taken() {
+        let mut stats = NetStats::default();
+        stats.sent(100);
+        stats.sent(100);
+        stats.recv(12);
+
+        let taken = stats.take();
+        assert_eq!((taken.bytes_sent, taken.packets_sent), (200, 2));
+        assert_eq!((taken.bytes_recv, taken.packets_recv), (12, 1));
+        assert_eq!(stats, NetStats::default());
+    }
+}
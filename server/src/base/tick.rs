@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+/// Adapts the tick rate to the load of the server, so it slows down instead of lagging behind.
+///
+/// A tick overruns when its work takes longer than the tick itself. After
+/// [`TickGovernor::OVERRUN_TICKS`] consecutive overruns the tick is lengthened (down to the
+/// floor rate), and after [`TickGovernor::RECOVER_TICKS`] consecutive ticks using at most
+/// [`TickGovernor::RECOVER_LOAD`] of it, shortened again (up to the nominal rate). The gap
+/// between both thresholds keeps the rate from oscillating.
+#[derive(Clone, Copy, Debug)]
+pub struct TickGovernor {
+    nominal: Duration,
+    floor: Duration,
+    current: Duration,
+    overruns: u32,
+    recoveries: u32,
+}
+
+impl TickGovernor {
+    pub const OVERRUN_TICKS: u32 = 16;
+    pub const RECOVER_TICKS: u32 = 256;
+
+    /// The fraction of a tick its work may take to count towards recovery.
+    pub const RECOVER_LOAD: f32 = 0.5;
+
+    /// How much a tick is lengthened (or shortened) at once.
+    pub const STEP: f32 = 1.25;
+
+    /// Tick every `nominal`, slowing down to every `floor` at most.
+    pub fn new(nominal: Duration, floor: Duration) -> Self {
+        Self {
+            nominal,
+            floor: floor.max(nominal),
+            current: nominal,
+            overruns: 0,
+            recoveries: 0,
+        }
+    }
+
+    /// The current duration of a tick.
+    pub const fn tick(&self) -> Duration {
+        self.current
+    }
+
+    /// Account for a tick whose work took `work`, returning the new duration of a tick if it changed.
+    pub fn record(&mut self, work: Duration) -> Option<Duration> {
+        if work > self.current {
+            self.overruns += 1;
+            self.recoveries = 0;
+        } else if work <= self.current.mul_f32(Self::RECOVER_LOAD) {
+            self.recoveries += 1;
+            self.overruns = 0;
+        } else {
+            self.overruns = 0;
+            self.recoveries = 0;
+        }
+
+        let next = if self.overruns >= Self::OVERRUN_TICKS {
+            self.current.mul_f32(Self::STEP).min(self.floor)
+        } else if self.recoveries >= Self::RECOVER_TICKS {
+            self.current.div_f32(Self::STEP).max(self.nominal)
+        } else {
+            return None;
+        };
+
+        self.overruns = 0;
+        self.recoveries = 0;
+
+        if next == self.current {
+            return None;
+        }
+        self.current = next;
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOMINAL: Duration = Duration::from_millis(4);
+    const FLOOR: Duration = Duration::from_millis(16);
+
+    #[test]
+    fn sustained_overruns_slow_down_then_fast_ticks_recover() {
+        let mut governor = TickGovernor::new(NOMINAL, FLOOR);
+        let slow = NOMINAL * 2;
+
+        for _ in 1..TickGovernor::OVERRUN_TICKS {
+            assert_eq!(governor.record(slow), None);
+        }
+        let slowed = governor.record(slow).unwrap();
+        assert!(slowed > NOMINAL);
+        assert_eq!(governor.tick(), slowed);
+
+        let fast = Duration::ZERO;
+        for _ in 1..TickGovernor::RECOVER_TICKS {
+            assert_eq!(governor.record(fast), None);
+        }
+        assert_eq!(governor.record(fast), Some(NOMINAL));
+    }
+
+    #[test]
+    fn interrupted_overruns_never_slow_down() {
+        let mut governor = TickGovernor::new(NOMINAL, FLOOR);
+
+        for _ in 0..TickGovernor::OVERRUN_TICKS * 4 {
+            for _ in 1..TickGovernor::OVERRUN_TICKS {
+                assert_eq!(governor.record(NOMINAL * 2), None);
+            }
+            // neither an overrun nor light enough to recover
+            assert_eq!(governor.record(NOMINAL.mul_f32(0.75)), None);
+        }
+        assert_eq!(governor.tick(), NOMINAL);
+    }
+
+    #[test]
+    fn rate_stays_within_floor_and_nominal() {
+        let mut governor = TickGovernor::new(NOMINAL, FLOOR);
+
+        for _ in 0..TickGovernor::OVERRUN_TICKS * 64 {
+            governor.record(Duration::from_secs(1));
+        }
+        assert_eq!(governor.tick(), FLOOR);
+
+        for _ in 0..TickGovernor::RECOVER_TICKS * 64 {
+            governor.record(Duration::ZERO);
+        }
+        assert_eq!(governor.tick(), NOMINAL);
+    }
+}
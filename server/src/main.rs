@@ -13,6 +13,8 @@ use sync_select::*;
 pub type TcpClients = Arc<RwLock<HashMap<Id, TcpClient>>>;
 pub type UdpClients = Arc<RwLock<HashMap<SocketAddr, ObjectData>>>;
 pub type Updates = Arc<Mutex<HashSet<SocketAddr>>>;
+pub type Stats = Arc<Mutex<HashMap<Id, NetStats>>>;
+pub type Spawned = Arc<RwLock<HashMap<Id, (Id, ObjectData)>>>;
 
 fn handle_ctrlc(s: &SyncSelect) -> Result {
     let thread = s.thread();
@@ -44,8 +46,15 @@ fn main() -> Result {
     // share client TCP packets
     let (sender_packet, receiver_packet) = unbounded::<Packet>();
 
-    // monotonic user identity (default: 0)
-    let id = Default::default();
+    // the game rules spawn requests are checked against (players only join)
+    let rules = SpawnRules::default();
+    rules.set_spawn_validator(Box::new(|request| match request.data.kind() {
+        RawObjectDataUnit::Player => Err(RejectReason::Forbidden),
+        RawObjectDataUnit::Basic => Ok(()),
+    }));
+
+    // ids, spawned objects, and traffic shared by every connection
+    let state = Arc::new(ServerState::new(rules, cfg.rate_limiter()));
 
     // short-circuiting local thread manager
     let s = SyncSelect::default();
@@ -57,16 +66,41 @@ fn main() -> Result {
     init_tcp(
         &s,
         tcp,
-        clients_tcp,
-        clients_udp.clone(),
-        sender_packet,
-        receiver_addr,
-        receiver_packet,
-        id,
+        (clients_tcp.clone(), clients_udp.clone()),
+        (sender_packet.clone(), receiver_addr, receiver_packet),
+        state.clone(),
     );
 
     // handle UDP packets
-    init_udp(&s, udp, udp_clone, clients_udp, sender_addr, cfg.tps());
+    init_udp(
+        &s,
+        (udp, udp_clone),
+        clients_udp.clone(),
+        sender_addr,
+        cfg.tick_governor(),
+        state.clone(),
+    );
+
+    // report bandwidth
+    init_stats(&s, state.stats().clone());
+
+    // wait until interrupted (or any thread stops)
+    drop(s);
+
+    // tell everyone why they're disconnected
+    let ids = clients_tcp.read().keys().copied().collect::<Vec<_>>();
+    for id in ids {
+        let clients = (&clients_tcp, &clients_udp);
+        if let Err(e) = kick(
+            id,
+            DisconnectReason::ShuttingDown,
+            clients,
+            &state,
+            &sender_packet,
+        ) {
+            warn!("{:?}", e)
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,217 @@
+use crate::*;
+
+#[derive(Clone, Copy, Debug)]
+enum Node {
+    Leaf {
+        aabb: Aabb,
+        id: Id,
+    },
+    Branch {
+        aabb: Aabb,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Node {
+    const fn aabb(&self) -> &Aabb {
+        match self {
+            Self::Leaf { aabb, .. } | Self::Branch { aabb, .. } => aabb,
+        }
+    }
+}
+
+/// A bounding volume hierarchy (AABB tree) for raycasting static geometry.
+///
+/// The tree is immutable; rebuild it whenever the geometry changes.
+#[derive(Clone, Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    pub fn build(items: impl IntoIterator<Item = (Id, Aabb)>) -> Self {
+        let mut items = items.into_iter().collect::<Vec<_>>();
+        let mut nodes = Vec::with_capacity(items.len() * 2);
+
+        if !items.is_empty() {
+            Self::build_node(&mut nodes, &mut items);
+        }
+        Self { nodes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| matches!(node, Node::Leaf { .. }))
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The id of the nearest box hit by the ray (see [`Aabb::ray_intersection`]).
+    pub fn raycast(&self, origin: &Vector, dir: &Vector) -> Option<Id> {
+        self.raycast_detailed(origin, dir).map(|(id, ..)| id)
+    }
+
+    /// The nearest box hit by the ray, along with the distance to it and the face hit.
+    ///
+    /// Children are visited front-to-back, skipping any that start beyond the nearest hit.
+    pub fn raycast_detailed(&self, origin: &Vector, dir: &Vector) -> Option<(Id, f32, CubeFace)> {
+        let root = self.nodes.first()?;
+        let mut best: Option<(Id, f32, CubeFace)> = None;
+        let mut stack = Vec::new();
+
+        if let Some(t) = ray_entry(root.aabb(), origin, dir) {
+            stack.push((t, 0));
+        }
+
+        while let Some((t, index)) = stack.pop() {
+            if best.is_some_and(|(_, distance, _)| t >= distance) {
+                continue;
+            }
+
+            match self.nodes[index] {
+                Node::Leaf { aabb, id } => {
+                    if let Some((distance, face)) = aabb.ray_intersection(origin, dir) {
+                        if best.is_none_or(|(_, best, _)| distance < best) {
+                            best = Some((id, distance, face))
+                        }
+                    }
+                }
+                Node::Branch { left, right, .. } => {
+                    let mut children = [left, right]
+                        .into_iter()
+                        .filter_map(|child| {
+                            ray_entry(self.nodes[child].aabb(), origin, dir).map(|t| (t, child))
+                        })
+                        .collect::<Vec<_>>();
+
+                    // the nearer child is popped first
+                    children.sort_by(|a, b| b.0.total_cmp(&a.0));
+                    stack.extend(children);
+                }
+            }
+        }
+        best
+    }
+
+    fn build_node(nodes: &mut Vec<Node>, items: &mut [(Id, Aabb)]) -> usize {
+        let aabb = items
+            .iter()
+            .map(|(_, aabb)| *aabb)
+            .reduce(|a, b| a.union(&b))
+            .expect("no items");
+
+        let index = nodes.len();
+
+        if let [(id, aabb)] = items {
+            nodes.push(Node::Leaf {
+                aabb: *aabb,
+                id: *id,
+            });
+            return index;
+        }
+
+        // split at the median along the longest axis
+        let axis = (aabb.max - aabb.min).imax();
+        let center = |aabb: &Aabb| aabb.min[axis] + aabb.max[axis];
+        items.sort_by(|(_, a), (_, b)| center(a).total_cmp(&center(b)));
+
+        // reserve this node (children are pushed after it)
+        nodes.push(Node::Branch {
+            aabb,
+            left: 0,
+            right: 0,
+        });
+
+        let (lower, upper) = items.split_at_mut(items.len() / 2);
+        let left = Self::build_node(nodes, lower);
+        let right = Self::build_node(nodes, upper);
+
+        nodes[index] = Node::Branch { aabb, left, right };
+        index
+    }
+}
+
+/// The distance along the ray to where it's first within the box (zero if it starts inside).
+fn ray_entry(aabb: &Aabb, origin: &Vector, dir: &Vector) -> Option<f32> {
+    let (mut t_near, mut t_far) = (0.0f32, f32::INFINITY);
+
+    for i in 0..3 {
+        if dir[i].abs() <= f32::EPSILON {
+            if !(aabb.min[i]..=aabb.max[i]).contains(&origin[i]) {
+                return None;
+            }
+            continue;
+        }
+
+        let t1 = (aabb.min[i] - origin[i]) / dir[i];
+        let t2 = (aabb.max[i] - origin[i]) / dir[i];
+
+        t_near = t_near.max(t1.min(t2));
+        t_far = t_far.min(t1.max(t2));
+    }
+    (t_near <= t_far).then_some(t_near)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A deterministic stream of numbers in [0, 1) (xorshift).
+    fn sampler(mut seed: u32) -> impl FnMut() -> f32 {
+        move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            (seed >> 8) as f32 / (1 << 24) as f32
+        }
+    }
+
+    fn brute_force(items: &[(Id, Aabb)], origin: &Vector, dir: &Vector) -> Option<(Id, f32)> {
+        items
+            .iter()
+            .filter_map(|(id, aabb)| Some((*id, aabb.ray_intersection(origin, dir)?.0)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut next = sampler(0x9e37_79b9);
+        let mut vector = |scale: f32| Vector::new(next() - 0.5, next() - 0.5, next() - 0.5) * scale;
+
+        let items = (0..100)
+            .map(|i| (i as Id, Aabb::from_center(&vector(40.0), &vector(4.0))))
+            .collect::<Vec<_>>();
+        let bvh = Bvh::build(items.iter().copied());
+        assert_eq!(bvh.len(), items.len());
+
+        let mut hits = 0;
+        for _ in 0..64 {
+            let origin = vector(60.0);
+            let dir = (vector(40.0) - origin).normalize();
+
+            let expected = brute_force(&items, &origin, &dir);
+            let found = bvh.raycast_detailed(&origin, &dir);
+
+            assert_eq!(
+                found.map(|(_, distance, _)| distance),
+                expected.map(|(_, distance)| distance)
+            );
+            hits += expected.is_some() as usize;
+        }
+        // most rays aim into the boxes
+        assert!(hits > 0);
+    }
+
+    #[test]
+    fn empty_tree_hits_nothing() {
+        let bvh = Bvh::build([]);
+
+        assert!(bvh.is_empty());
+        assert_eq!(bvh.raycast(&Vector::zeros(), &Vector::x()), None);
+    }
+}
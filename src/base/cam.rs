@@ -5,10 +5,12 @@ use std::ops::{AddAssign, SubAssign};
 pub type Point = nalgebra::Point3<f32>;
 pub type Vector = nalgebra::Vector3<f32>;
 pub type Matrix = nalgebra::Matrix4<f32>;
+pub type Matrix3 = nalgebra::Matrix3<f32>;
 pub type Translation = nalgebra::Translation3<f32>;
 pub type Rotation = nalgebra::Rotation3<f32>;
 pub type Scale = nalgebra::Scale3<f32>;
 pub type Perspective = nalgebra::Perspective3<f32>;
+pub type Orthographic = nalgebra::Orthographic3<f32>;
 pub type UnitQuaternion = nalgebra::UnitQuaternion<f32>;
 pub type Isometry = nalgebra::Isometry3<f32>;
 
@@ -45,7 +47,7 @@ impl Axis {
     }
 
     fn x_bound(&mut self) {
-        self.inner = (self.inner + Self::X_BOUND) % Self::X_BOUND;
+        self.inner = self.inner.rem_euclid(Self::X_BOUND);
     }
 
     fn y_bound(&mut self) {
@@ -55,6 +57,10 @@ impl Axis {
     fn update(&mut self) {
         self.radians = self.inner * RADIAN
     }
+
+    fn offset(&mut self, delta: f32, sensitivity: f32) {
+        self.inner += delta * sensitivity;
+    }
 }
 
 impl AddAssign<i32> for Axis {
@@ -107,6 +113,21 @@ impl CameraAttr {
         self.yaw += xrel;
         self.pitch -= yrel;
 
+        self.upt_target();
+    }
+
+    /// Apply mouse deltas with an explicit sensitivity (degrees per unit).
+    ///
+    /// The deltas are already frame-relative, so no frame time is involved.
+    /// Pitch is clamped to (-89°, 89°) and yaw is wrapped into [0, 2π).
+    pub fn apply_look(&mut self, dx: f32, dy: f32, sensitivity: f32) {
+        self.yaw.offset(dx, sensitivity);
+        self.pitch.offset(-dy, sensitivity);
+
+        self.upt_target();
+    }
+
+    fn upt_target(&mut self) {
         // prevent overflow
         self.yaw.x_bound();
 
@@ -132,23 +153,36 @@ impl CameraAttr {
         self.target.normalize_mut();
     }
 
+    /// Move a step of `speed` for every key of `flags`.
+    ///
+    /// Steps are accumulated in fixed point (see [`FixedVector`]), so the server and the
+    /// clients predicting it land on the same position, however many steps are taken.
     pub fn input(&mut self, flags: Flags) {
         let mut target = self.target;
         target.y = 0.0;
 
+        let mut eye = FixedVector::from(self.eye);
+        let mut moved = false;
+
         for key in flags.iter() {
-            match key {
+            let step = match key {
                 // new
-                Flags::W => self.eye += target.normalize() * self.speed,
-                Flags::A => self.eye -= target.cross(&self.up).normalize() * self.speed,
-                Flags::S => self.eye -= target.normalize() * self.speed,
-                Flags::D => self.eye += target.cross(&self.up).normalize() * self.speed,
-
-                Flags::SPACE => self.eye += self.up * self.speed,
-                Flags::SHIFT => self.eye -= self.up * self.speed,
+                Flags::W => target.normalize(),
+                Flags::A => -target.cross(&self.up).normalize(),
+                Flags::S => -target.normalize(),
+                Flags::D => target.cross(&self.up).normalize(),
+
+                Flags::SPACE => self.up,
+                Flags::SHIFT => -self.up,
+
+                _ => continue,
+            };
+            eye += FixedVector::from(step * self.speed);
+            moved = true;
+        }
 
-                _ => (),
-            }
+        if moved {
+            self.eye = eye.into()
         }
     }
 }
@@ -159,14 +193,95 @@ impl Default for CameraAttr {
     }
 }
 
+/// A rectangular region of the window, in pixels (origin: bottom-left).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Viewport {
+    pub const fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+}
+
+/// Logarithmic depth of a clip-space `w` value, mapped into [0, 1] over (0, `far`].
+///
+/// Mirrors the `gl_FragDepth` write of the shaders when log-depth is enabled.
+pub fn log_depth(w: f32, far: f32) -> f32 {
+    (1.0 + w).log2() * log_depth_coef(far)
+}
+
+/// The uniform coefficient used by the shaders to compute logarithmic depth.
+pub fn log_depth_coef(far: f32) -> f32 {
+    1.0 / (1.0 + far).log2()
+}
+
+/// How a [`RawCamera`] projects the scene.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProjectionMode {
+    /// A vertical field of view (in degrees).
+    Perspective { fov: f32 },
+
+    /// Parallel projection, showing `size` world units vertically (horizontally scaled by the aspect ratio).
+    Orthographic { size: f32 },
+}
+
+impl ProjectionMode {
+    /// The projection matrix for a viewport of `aspect` (width / height).
+    pub fn matrix(&self, aspect: f32, near: f32, far: f32) -> Matrix {
+        match *self {
+            Self::Perspective { fov } => {
+                Perspective::new(aspect, fov * RADIAN, near, far).to_homogeneous()
+            }
+            Self::Orthographic { size } => {
+                let (half_w, half_h) = (size * aspect / 2.0, size / 2.0);
+                Orthographic::new(-half_w, half_w, -half_h, half_h, near, far).to_homogeneous()
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct RawCamera {
     attr: CameraAttr,
     view: Matrix,
-    projection: Perspective,
+    mode: ProjectionMode,
+    aspect: f32,
+    near: f32,
+    far: f32,
+    projection: Matrix,
+    log_depth: bool,
+    target_fov: f32,
+    fov_speed: f32,
 }
 
 impl RawCamera {
+    const NEAR: f32 = 0.01;
+    const FAR: f32 = 1000.0;
+
+    /// The bounds (in degrees) of [`RawCamera::set_target_fov`].
+    pub const MIN_FOV: f32 = 10.0;
+    pub const MAX_FOV: f32 = 120.0;
+
+    /// How quickly the field of view eases toward its target (see [`RawCamera::update_fov`]).
+    const FOV_SPEED: f32 = 12.0;
+
+    /// Differences (in degrees) below this snap to the target.
+    const FOV_EPSILON: f32 = 0.01;
+
     pub fn new((w, h): (u32, u32)) -> Self {
         let aspect = Self::calc_aspect_ratio(w as i32, h as i32);
         Self::init(aspect)
@@ -185,35 +300,155 @@ impl RawCamera {
     }
 
     pub fn projection(&self) -> &Matrix {
-        self.projection.as_matrix()
+        &self.projection
+    }
+
+    pub const fn projection_mode(&self) -> ProjectionMode {
+        self.mode
+    }
+
+    /// Switch projections, a perspective one also setting (and targeting) its field of view
+    /// (clamped to [`RawCamera::MIN_FOV`]..=[`RawCamera::MAX_FOV`]).
+    pub fn set_projection_mode(&mut self, mut mode: ProjectionMode) {
+        if let ProjectionMode::Perspective { fov } = &mut mode {
+            *fov = fov.clamp(Self::MIN_FOV, Self::MAX_FOV);
+            self.attr.fov = *fov;
+            self.target_fov = *fov;
+        }
+        self.mode = mode;
+        self.upt_projection()
     }
 
     pub const fn pos(&self) -> &Vector {
         &self.attr.eye
     }
 
+    pub const fn near(&self) -> f32 {
+        self.near
+    }
+
+    pub const fn far(&self) -> f32 {
+        self.far
+    }
+
+    /// Move the clipping planes, returning `false` (keeping the current ones) unless both are
+    /// finite, positive, and `near` is closer than `far`.
+    pub fn set_near_far(&mut self, near: f32, far: f32) -> bool {
+        if !(near.is_finite() && far.is_finite() && 0.0 < near && near < far) {
+            return false;
+        }
+        self.near = near;
+        self.far = far;
+        self.upt_projection();
+        true
+    }
+
+    /// Whether logarithmic depth is written (never with an orthographic projection, whose
+    /// clip-space `w` is constant).
+    pub const fn is_log_depth(&self) -> bool {
+        self.log_depth && matches!(self.mode, ProjectionMode::Perspective { .. })
+    }
+
+    /// Write logarithmic depth from the shaders, greatly improving far-distance precision.
+    ///
+    /// The shaders must be compiled with logarithmic depth for this to take effect.
+    pub fn set_log_depth(&mut self, enabled: bool) {
+        self.log_depth = enabled
+    }
+
+    pub fn log_depth_coef(&self) -> f32 {
+        log_depth_coef(self.far())
+    }
+
     pub fn reset(&mut self) {
-        let aspect = self.projection.aspect();
+        let aspect = self.aspect;
+        let (near, far) = (self.near(), self.far());
+        let log_depth = self.log_depth;
+        let mode = self.mode;
+        let fov_speed = self.fov_speed;
+
         *self = Self::init(aspect);
+
+        // keep the depth and projection configuration
+        self.set_near_far(near, far);
+        self.log_depth = log_depth;
+        self.fov_speed = fov_speed;
+        if let ProjectionMode::Orthographic { .. } = mode {
+            self.set_projection_mode(mode)
+        }
     }
 
     pub fn upt_aspect_ratio(&mut self, w: i32, h: i32) {
-        let aspect = Self::calc_aspect_ratio(w, h);
-        self.projection.set_aspect(aspect);
+        self.aspect = Self::calc_aspect_ratio(w, h);
+        self.upt_projection();
         self.upt();
     }
 
     pub fn upt_fov(&mut self, precise_y: f32) {
         self.attr.upt_fov(precise_y);
-        self.projection.set_fovy(self.attr.fov * RADIAN);
+        self.target_fov = self.attr.fov;
+
+        if let ProjectionMode::Perspective { fov } = &mut self.mode {
+            *fov = self.attr.fov;
+            self.upt_projection()
+        }
         self.upt();
     }
 
+    /// The field of view (in degrees) [`RawCamera::update_fov`] eases toward.
+    pub const fn target_fov(&self) -> f32 {
+        self.target_fov
+    }
+
+    /// Zoom toward `fov` degrees (clamped to [`RawCamera::MIN_FOV`]..=[`RawCamera::MAX_FOV`]).
+    pub fn set_target_fov(&mut self, fov: f32) {
+        self.target_fov = fov.clamp(Self::MIN_FOV, Self::MAX_FOV)
+    }
+
+    pub const fn fov_speed(&self) -> f32 {
+        self.fov_speed
+    }
+
+    /// The rate (per second) at which the remaining difference to the target shrinks.
+    pub fn set_fov_speed(&mut self, speed: f32) {
+        self.fov_speed = speed.max(0.0)
+    }
+
+    /// Ease the field of view toward its target by `dt` seconds, returning whether it changed.
+    ///
+    /// The easing is exponential, so it's independent of the frame rate. Orthographic
+    /// cameras have no field of view, so they're left untouched.
+    pub fn update_fov(&mut self, dt: f32) -> bool {
+        let ProjectionMode::Perspective { fov } = &mut self.mode else {
+            return false;
+        };
+
+        let diff = self.target_fov - *fov;
+        if diff == 0.0 {
+            return false;
+        }
+
+        *fov = if diff.abs() < Self::FOV_EPSILON {
+            self.target_fov
+        } else {
+            *fov + diff * (1.0 - (-self.fov_speed * dt).exp())
+        };
+        self.attr.fov = *fov;
+
+        self.upt_projection();
+        true
+    }
+
     pub fn look_at(&mut self, xrel: i32, yrel: i32) {
         self.attr.look_at(xrel, yrel);
         self.upt();
     }
 
+    pub fn apply_look(&mut self, dx: f32, dy: f32, sensitivity: f32) {
+        self.attr.apply_look(dx, dy, sensitivity);
+        self.upt();
+    }
+
     pub fn input(&mut self, input: Flags) {
         self.attr.input(input);
         self.upt();
@@ -232,19 +467,75 @@ impl RawCamera {
         );
     }
 
+    /// The region currently visible to the camera.
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(&(self.projection() * self.view))
+    }
+
+    /// Project a world-space point into `viewport` (in pixels; origin: bottom-left).
+    ///
+    /// Returns `None` if the point is behind the camera.
+    pub fn world_to_screen(&self, point: Vector, viewport: Viewport) -> Option<(f32, f32)> {
+        let clip = self.projection() * self.view * point.push(1.0);
+
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        let ndc = clip.xy() / clip.w;
+
+        Some((
+            viewport.x as f32 + (ndc.x + 1.0) / 2.0 * viewport.width as f32,
+            viewport.y as f32 + (ndc.y + 1.0) / 2.0 * viewport.height as f32,
+        ))
+    }
+
+    /// The world-space ray (origin on the near plane; unit direction) through a pixel of `viewport`.
+    ///
+    /// The inverse of [`RawCamera::world_to_screen`].
+    pub fn screen_to_ray(&self, x: f32, y: f32, viewport: Viewport) -> (Vector, Vector) {
+        let ndc_x = 2.0 * (x - viewport.x as f32) / viewport.width as f32 - 1.0;
+        let ndc_y = 2.0 * (y - viewport.y as f32) / viewport.height as f32 - 1.0;
+
+        let inv = (self.projection() * self.view)
+            .try_inverse()
+            .unwrap_or_else(Matrix::identity);
+
+        let unproject = |z: f32| {
+            let world = inv * nalgebra::Vector4::new(ndc_x, ndc_y, z, 1.0);
+            world.xyz() / world.w
+        };
+
+        let near = unproject(-1.0);
+        let far = unproject(1.0);
+
+        (near, (far - near).normalize())
+    }
+
     fn calc_aspect_ratio(w: i32, h: i32) -> f32 {
         w as f32 / h as f32
     }
 
+    fn upt_projection(&mut self) {
+        self.projection = self.mode.matrix(self.aspect, self.near, self.far)
+    }
+
     fn init(aspect: f32) -> Self {
         let attr = CameraAttr::default();
         let view = Matrix::identity();
-        let projection = Perspective::new(aspect, attr.fov * RADIAN, 0.01, 1000.0);
+        let mode = ProjectionMode::Perspective { fov: attr.fov };
 
         let mut cam = Self {
             attr,
             view,
-            projection,
+            mode,
+            aspect,
+            near: Self::NEAR,
+            far: Self::FAR,
+            projection: mode.matrix(aspect, Self::NEAR, Self::FAR),
+            log_depth: false,
+            target_fov: attr.fov,
+            fov_speed: Self::FOV_SPEED,
         };
 
         // initial setup
@@ -253,3 +544,154 @@ impl RawCamera {
         cam
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_look_clamps_pitch_and_wraps_yaw() {
+        let mut attr = CameraAttr::default();
+
+        // 1° per unit of delta
+        attr.apply_look(0.0, -100.0, 1.0);
+        assert_eq!(attr.pitch.radians(), 89.0 * RADIAN);
+        attr.apply_look(0.0, 200.0, 1.0);
+        assert_eq!(attr.pitch.radians(), -89.0 * RADIAN);
+
+        attr.apply_look(370.0, 0.0, 1.0);
+        assert!((attr.yaw.radians() - 10.0 * RADIAN).abs() < 1e-5);
+        attr.apply_look(-20.0, 0.0, 1.0);
+        assert!((attr.yaw.radians() - 350.0 * RADIAN).abs() < 1e-5);
+        assert!((0.0..std::f32::consts::TAU).contains(&attr.yaw.radians()));
+    }
+
+    #[test]
+    fn points_project_to_the_screen_and_back() {
+        let mut cam = RawCamera::new((800, 600));
+        cam.apply_look(30.0, 10.0, 1.0);
+        let viewport = Viewport::new(0, 0, 800, 600);
+        let (eye, forward) = (cam.attr().eye, cam.attr().target);
+
+        let (x, y) = cam.world_to_screen(eye + forward * 10.0, viewport).unwrap();
+        assert!((x - 400.0).abs() < 1e-2 && (y - 300.0).abs() < 1e-2);
+        assert_eq!(cam.world_to_screen(eye - forward * 10.0, viewport), None);
+
+        let (_, dir) = cam.screen_to_ray(400.0, 300.0, viewport);
+        assert!((dir - forward).norm() < 1e-4);
+    }
+
+    #[test]
+    fn projection_modes_match_reference_matrices() {
+        let close = |a: Matrix, b: Matrix| (a - b).abs().max() < 1e-6;
+
+        // 90° (so a focal length of 1), twice as wide as tall, depth in [1, 3]
+        let perspective = ProjectionMode::Perspective { fov: 90.0 }.matrix(2.0, 1.0, 3.0);
+        #[rustfmt::skip]
+        let expected = Matrix::new(
+            0.5, 0.0,  0.0,  0.0,
+            0.0, 1.0,  0.0,  0.0,
+            0.0, 0.0, -2.0, -3.0,
+            0.0, 0.0, -1.0,  0.0,
+        );
+        assert!(close(perspective, expected));
+
+        // 4 units tall, so 8 wide
+        let orthographic = ProjectionMode::Orthographic { size: 4.0 }.matrix(2.0, 1.0, 3.0);
+        #[rustfmt::skip]
+        let expected = Matrix::new(
+            0.25, 0.0,  0.0,  0.0,
+            0.0,  0.5,  0.0,  0.0,
+            0.0,  0.0, -1.0, -2.0,
+            0.0,  0.0,  0.0,  1.0,
+        );
+        assert!(close(orthographic, expected));
+    }
+
+    #[test]
+    fn look_at_clamps_pitch() {
+        let mut attr = CameraAttr::default();
+
+        // 0.1° per unit, so 10000 units would be 1000°
+        attr.look_at(0, -10000);
+        assert_eq!(attr.pitch.radians(), 89.0 * RADIAN);
+
+        attr.look_at(0, 20000);
+        assert_eq!(attr.pitch.radians(), -89.0 * RADIAN);
+        assert!(attr.target.y > -1.0);
+    }
+
+    #[test]
+    fn log_depth_is_monotonic() {
+        let far = 1000.0;
+        let depths = [0.01, 0.1, 1.0, 10.0, 100.0, 999.0, far].map(|w| log_depth(w, far));
+
+        assert!(depths.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(depths[0] > 0.0);
+        assert!((depths[depths.len() - 1] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn log_depth_is_disabled_when_orthographic() {
+        let mut cam = RawCamera::new((800, 600));
+        cam.set_log_depth(true);
+        assert!(cam.is_log_depth());
+
+        cam.set_projection_mode(ProjectionMode::Orthographic { size: 10.0 });
+        assert!(!cam.is_log_depth());
+
+        cam.set_projection_mode(ProjectionMode::Perspective { fov: 80.0 });
+        assert!(cam.is_log_depth());
+    }
+
+    #[test]
+    fn perspective_mode_sets_the_fov() {
+        let mut cam = RawCamera::new((800, 600));
+
+        cam.set_projection_mode(ProjectionMode::Perspective { fov: 50.0 });
+        assert_eq!(cam.attr().fov, 50.0);
+        assert_eq!(cam.target_fov(), 50.0);
+        assert!(!cam.update_fov(1.0));
+
+        cam.set_projection_mode(ProjectionMode::Perspective { fov: 500.0 });
+        assert_eq!(cam.target_fov(), RawCamera::MAX_FOV);
+    }
+
+    #[test]
+    fn invalid_clipping_planes_are_rejected() {
+        let mut cam = RawCamera::new((800, 600));
+
+        for (near, far) in [(1.0, 1.0), (10.0, 1.0), (0.0, 10.0), (1.0, f32::INFINITY)] {
+            assert!(!cam.set_near_far(near, far));
+        }
+        assert_eq!((cam.near(), cam.far()), (RawCamera::NEAR, RawCamera::FAR));
+
+        assert!(cam.set_near_far(0.5, 50.0));
+        assert!(cam.projection().iter().all(|c| c.is_finite()));
+    }
+
+    #[test]
+    fn movement_is_reproducible() {
+        let mut attr = CameraAttr::default();
+        let step = FixedVector::from(attr.target.normalize() * attr.speed);
+
+        for _ in 0..1000 {
+            attr.input(Flags::W);
+        }
+
+        // no drift accumulates, unlike summing floats
+        assert_eq!(FixedVector::from(attr.eye), step * Fixed::from_int(1000));
+    }
+
+    #[test]
+    fn look_at_wraps_yaw() {
+        let mut attr = CameraAttr::default();
+
+        attr.look_at(3700, 0);
+        assert!((attr.yaw.radians() - 10.0 * RADIAN).abs() < 1e-4);
+
+        attr.look_at(-200, 0);
+        assert!((attr.yaw.radians() - 350.0 * RADIAN).abs() < 1e-4);
+        assert!((attr.target.norm() - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,546 @@
+use crate::*;
+
+/// A face of a box (or cube), named like the faces of the cube meshes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CubeFace {
+    /// +Z
+    Front,
+    /// -Z
+    Back,
+    /// -X
+    Left,
+    /// +X
+    Right,
+    /// +Y
+    Top,
+    /// -Y
+    Bottom,
+}
+
+impl CubeFace {
+    /// The face perpendicular to the `axis` (0: x, 1: y, 2: z) on the `positive` side.
+    pub const fn from_axis(axis: usize, positive: bool) -> Self {
+        match (axis, positive) {
+            (0, false) => Self::Left,
+            (0, true) => Self::Right,
+            (1, false) => Self::Bottom,
+            (1, true) => Self::Top,
+            (_, false) => Self::Back,
+            (_, true) => Self::Front,
+        }
+    }
+
+    /// The outward unit normal.
+    pub const fn normal(&self) -> Vector {
+        match self {
+            Self::Front => Z_AXIS,
+            Self::Back => Vector::new(0.0, 0.0, -1.0),
+            Self::Left => Vector::new(-1.0, 0.0, 0.0),
+            Self::Right => X_AXIS,
+            Self::Top => Y_AXIS,
+            Self::Bottom => Vector::new(0.0, -1.0, 0.0),
+        }
+    }
+}
+
+/// Which objects collide with which, as bitsets of layers.
+///
+/// An object is on the layers of `layer` and collides with those of `mask`; a pair is only
+/// tested if each is on a layer the other collides with (e.g., debris on its own layer,
+/// masking that layer out, passes through other debris).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollisionFilter {
+    pub layer: u32,
+    pub mask: u32,
+}
+
+impl CollisionFilter {
+    /// On the first layer, colliding with every layer.
+    pub const DEFAULT: Self = Self::new(1, u32::MAX);
+
+    pub const fn new(layer: u32, mask: u32) -> Self {
+        Self { layer, mask }
+    }
+
+    pub const fn collides_with(&self, other: &Self) -> bool {
+        (self.layer & other.mask) != 0 && (other.layer & self.mask) != 0
+    }
+}
+
+impl Default for CollisionFilter {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// An axis-aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector,
+    pub max: Vector,
+}
+
+impl Aabb {
+    pub const fn new(min: Vector, max: Vector) -> Self {
+        Self { min, max }
+    }
+
+    /// The box of an object at `pos` (the unit geometry spans [-1, 1], scaled by `dim`).
+    pub fn from_center(pos: &Vector, dim: &Vector) -> Self {
+        let half = dim.abs();
+        Self::new(pos - half, pos + half)
+    }
+
+    pub fn contains(&self, point: &Vector) -> bool {
+        (0..3).all(|i| (self.min[i]..=self.max[i]).contains(&point[i]))
+    }
+
+    /// Whether the boxes overlap (touching counts).
+    pub fn intersects(&self, other: &Self) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && other.min[i] <= self.max[i])
+    }
+
+    /// The smallest box enclosing both boxes.
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(self.min.inf(&other.min), self.max.sup(&other.max))
+    }
+
+    /// The point of the box closest to `point`.
+    pub fn closest_point(&self, point: &Vector) -> Vector {
+        point.sup(&self.min).inf(&self.max)
+    }
+
+    /// The distance along the ray (`dir` need not be unit length) to where it enters the box,
+    /// along with the face it enters through.
+    ///
+    /// Rays starting inside the box never hit it.
+    pub fn ray_intersection(&self, origin: &Vector, dir: &Vector) -> Option<(f32, CubeFace)> {
+        let (mut t_near, mut t_far) = (f32::NEG_INFINITY, f32::INFINITY);
+        let mut face = None;
+
+        for i in 0..3 {
+            if dir[i].abs() <= f32::EPSILON {
+                // parallel to the slab
+                if !(self.min[i]..=self.max[i]).contains(&origin[i]) {
+                    return None;
+                }
+                continue;
+            }
+
+            let t1 = (self.min[i] - origin[i]) / dir[i];
+            let t2 = (self.max[i] - origin[i]) / dir[i];
+
+            // moving towards +axis enters through the negative face
+            let entry = CubeFace::from_axis(i, dir[i] < 0.0);
+
+            if t1.min(t2) > t_near {
+                t_near = t1.min(t2);
+                face = Some(entry);
+            }
+            t_far = t_far.min(t1.max(t2));
+        }
+
+        if t_near > t_far || t_near < 0.0 {
+            return None;
+        }
+        face.map(|face| (t_near, face))
+    }
+}
+
+/// A plane of points `p` where `normal · p = distance`, used to slice the scene.
+///
+/// Everything behind the plane (opposite of `normal`) is clipped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClipPlane {
+    pub normal: Vector,
+    pub distance: f32,
+}
+
+impl ClipPlane {
+    /// The plane through `point` facing `normal` (normalized).
+    pub fn new(normal: Vector, point: &Vector) -> Self {
+        let normal = normal.normalize();
+        Self {
+            normal,
+            distance: normal.dot(point),
+        }
+    }
+
+    /// Positive in front of the plane, negative behind it.
+    pub fn signed_distance(&self, point: &Vector) -> f32 {
+        self.normal.dot(point) - self.distance
+    }
+
+    pub fn is_clipped(&self, point: &Vector) -> bool {
+        self.signed_distance(point) < 0.0
+    }
+
+    /// The plane as `(normal, -distance)`, so a shader computes `dot(plane, vec4(p, 1.0))`.
+    pub fn to_vec4(&self) -> [f32; 4] {
+        [self.normal.x, self.normal.y, self.normal.z, -self.distance]
+    }
+}
+
+/// The six planes (facing inwards) bounding what a camera can see.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    planes: [ClipPlane; 6],
+}
+
+impl Frustum {
+    /// Extract the planes of a (projection * view) matrix.
+    pub fn from_matrix(m: &Matrix) -> Self {
+        let row = |i: usize| m.row(i).transpose();
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+
+        // left, right, bottom, top, near, far
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2].map(|p| {
+            let len = p.xyz().norm().max(f32::EPSILON);
+            ClipPlane {
+                normal: p.xyz() / len,
+                distance: -p.w / len,
+            }
+        });
+        Self { planes }
+    }
+
+    pub fn intersects_sphere(&self, center: &Vector, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(center) >= -radius)
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // the corner furthest along the normal
+            let corner = Vector::from_fn(|i, _| {
+                if plane.normal[i] >= 0.0 {
+                    aabb.max[i]
+                } else {
+                    aabb.min[i]
+                }
+            });
+            plane.signed_distance(&corner) >= 0.0
+        })
+    }
+}
+
+/// A vertical segment swept by a sphere of `radius`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Capsule {
+    pub a: Vector,
+    pub b: Vector,
+    pub radius: f32,
+}
+
+impl Capsule {
+    /// The total height (caps included) of a player.
+    pub const PLAYER_HEIGHT: f32 = 2.0;
+    pub const PLAYER_RADIUS: f32 = 0.5;
+
+    /// How far below a capsule a floor still counts as touching it.
+    pub const GROUND_SKIN: f32 = 0.01;
+
+    pub const fn new(a: Vector, b: Vector, radius: f32) -> Self {
+        Self { a, b, radius }
+    }
+
+    /// The capsule of a player centered at `pos` (matching its dimensions).
+    pub fn player(pos: Vector) -> Self {
+        let half = Y_AXIS * (Self::PLAYER_HEIGHT / 2.0 - Self::PLAYER_RADIUS);
+        Self::new(pos - half, pos + half, Self::PLAYER_RADIUS)
+    }
+
+    pub fn translate(&mut self, offset: Vector) {
+        self.a += offset;
+        self.b += offset;
+    }
+
+    fn point(&self, t: f32) -> Vector {
+        self.a.lerp(&self.b, t)
+    }
+}
+
+/// How far (and in which direction) a shape must move to stop overlapping.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    /// Unit vector pointing away from the other shape.
+    pub normal: Vector,
+    pub depth: f32,
+}
+
+impl Contact {
+    /// The offset which resolves the overlap.
+    pub fn correction(&self) -> Vector {
+        self.normal * self.depth
+    }
+
+    /// Whether the other shape is underneath (a floor, or a slope up to 45 degrees).
+    pub fn is_floor(&self) -> bool {
+        self.normal.y >= std::f32::consts::FRAC_1_SQRT_2
+    }
+}
+
+/// Test a capsule against a box, returning the contact (relative to the capsule) if they overlap.
+pub fn capsule_vs_aabb(capsule: &Capsule, aabb: &Aabb) -> Option<Contact> {
+    let distance = |t: f32| {
+        let p = capsule.point(t);
+        (p - aabb.closest_point(&p)).norm()
+    };
+
+    // the distance to a convex shape is convex along the segment
+    let (mut lo, mut hi) = (0.0, 1.0);
+
+    for _ in 0..32 {
+        let m1 = lo + (hi - lo) / 3.0;
+        let m2 = hi - (hi - lo) / 3.0;
+
+        if distance(m1) <= distance(m2) {
+            hi = m2
+        } else {
+            lo = m1
+        }
+    }
+
+    let p = capsule.point((lo + hi) / 2.0);
+    let q = aabb.closest_point(&p);
+    let d = (p - q).norm();
+
+    if d >= capsule.radius {
+        return None;
+    }
+
+    if d > f32::EPSILON {
+        return Some(Contact {
+            normal: (p - q) / d,
+            depth: capsule.radius - d,
+        });
+    }
+
+    // the segment passes through the box (push out through the nearest face)
+    let (normal, depth) = (0..3)
+        .flat_map(|i| {
+            let axis = Vector::ith(i, 1.0);
+            let extent = |v: &Vector| axis.dot(v);
+
+            // extents of the segment along the axis
+            let (lo, hi) = {
+                let (a, b) = (extent(&capsule.a), extent(&capsule.b));
+                (a.min(b), a.max(b))
+            };
+
+            [
+                (axis, extent(&aabb.max) - lo),
+                (-axis, hi - extent(&aabb.min)),
+            ]
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+    Some(Contact {
+        normal,
+        depth: depth + capsule.radius,
+    })
+}
+
+/// Test two boxes, returning the contact (relative to `a`) if they overlap.
+///
+/// The contact pushes `a` out through the axis of least overlap (touching doesn't count).
+pub fn aabb_vs_aabb(a: &Aabb, b: &Aabb) -> Option<Contact> {
+    (0..3)
+        .flat_map(|i| {
+            let axis = Vector::ith(i, 1.0);
+            [(axis, b.max[i] - a.min[i]), (-axis, a.max[i] - b.min[i])]
+        })
+        .min_by(|(_, x), (_, y)| x.total_cmp(y))
+        .filter(|(_, depth)| *depth > 0.0)
+        .map(|(normal, depth)| Contact { normal, depth })
+}
+
+/// Push two overlapping objects apart, returning whether they collided.
+///
+/// Kinematic objects are never pushed (the other one moves the whole way), while two
+/// dynamic objects split the correction.
+pub fn resolve_basics(a: &mut BasicData, b: &mut BasicData) -> bool {
+    let Some(contact) = aabb_vs_aabb(&a.bounds(), &b.bounds()) else {
+        return false;
+    };
+    let correction = contact.correction();
+
+    match (a.is_kinematic(), b.is_kinematic()) {
+        (true, true) => (),
+        (true, false) => b.resolve(-correction),
+        (false, true) => a.resolve(correction),
+        (false, false) => {
+            a.resolve(correction / 2.0);
+            b.resolve(-correction / 2.0);
+        }
+    }
+    true
+}
+
+/// Push `capsule` out of every box, returning the total correction.
+pub fn resolve_capsule(capsule: &mut Capsule, boxes: impl IntoIterator<Item = Aabb>) -> Vector {
+    let mut total = Vector::zeros();
+
+    for aabb in boxes {
+        if let Some(contact) = capsule_vs_aabb(capsule, &aabb) {
+            let correction = contact.correction();
+            capsule.translate(correction);
+            total += correction;
+        }
+    }
+    total
+}
+
+/// Whether `capsule` stands on any box (within [`Capsule::GROUND_SKIN`] of a floor).
+///
+/// Resolved capsules only touch what they rest on, so they're probed slightly lower.
+pub fn capsule_grounded(capsule: &Capsule, boxes: impl IntoIterator<Item = Aabb>) -> bool {
+    let mut probe = *capsule;
+    probe.translate(-Y_AXIS * Capsule::GROUND_SKIN);
+
+    boxes
+        .into_iter()
+        .any(|aabb| capsule_vs_aabb(&probe, &aabb).is_some_and(|contact| contact.is_floor()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAVITY: Vector = Vector::new(0.0, -9.81, 0.0);
+    const DT: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn boxes_are_pushed_through_the_axis_of_least_overlap() {
+        let a = Aabb::new(Vector::new(0.0, 0.0, 0.0), Vector::new(2.0, 2.0, 2.0));
+        let b = Aabb::new(Vector::new(1.5, -1.0, -1.0), Vector::new(4.0, 3.0, 3.0));
+
+        let contact = aabb_vs_aabb(&a, &b).unwrap();
+        assert_eq!(contact.normal, -Vector::x());
+        assert_eq!(contact.depth, 0.5);
+
+        // touching isn't overlapping
+        let c = Aabb::new(Vector::new(2.0, 0.0, 0.0), Vector::new(3.0, 1.0, 1.0));
+        assert_eq!(aabb_vs_aabb(&a, &c), None);
+    }
+
+    #[test]
+    fn kinematic_ignores_gravity_but_collides() {
+        let mut platform = BasicData::new(Vector::zeros(), Vector::new(4.0, 0.5, 4.0));
+        platform.set_kinematic(true);
+        let mut falling = BasicData::new(Vector::new(0.0, 3.0, 0.0), Vector::repeat(0.5));
+
+        let mut collided = false;
+        for _ in 0..120 {
+            for body in [&mut platform, &mut falling] {
+                body.apply_gravity(&GRAVITY, DT);
+                body.integrate(DT);
+            }
+            collided |= resolve_basics(&mut platform, &mut falling);
+        }
+
+        // the platform stays put, while the other one comes to rest on it
+        assert!(collided);
+        assert_eq!(*platform.pos(), Vector::zeros());
+        assert_eq!(*platform.vel(), Vector::zeros());
+        assert!(falling.bounds().min.y >= platform.bounds().max.y - 0.2);
+        assert!(falling.vel().y > -1.0);
+    }
+
+    #[test]
+    fn resting_box_falls_asleep_until_woken() {
+        let mut ground = BasicData::new(Vector::zeros(), Vector::new(4.0, 1.0, 4.0));
+        ground.set_kinematic(true);
+        let mut body = BasicData::new(Vector::new(0.0, 1.0, 0.0), Vector::repeat(1.0));
+        body.set_sleep_timeout(0.5);
+
+        // in the order of `RawObjects` (integrate, resolve, settle, then accelerate)
+        let mut step = |body: &mut BasicData| {
+            body.integrate(DT);
+            resolve_basics(&mut ground, body);
+            body.settle(DT);
+            body.apply_gravity(&GRAVITY, DT);
+        };
+
+        for _ in 0..20 {
+            step(&mut body);
+        }
+        assert!(!body.is_asleep());
+
+        for _ in 0..20 {
+            step(&mut body);
+        }
+        assert!(body.is_asleep());
+
+        // asleep, it's no longer integrated
+        let pos = *body.pos();
+        step(&mut body);
+        assert_eq!(*body.pos(), pos);
+
+        body.wake();
+        body.apply_impulse(Vector::new(0.0, 5.0, 0.0));
+        step(&mut body);
+        assert!(!body.is_asleep());
+        assert!(body.pos().y > pos.y);
+    }
+
+    fn approx(a: Vector, b: Vector) -> bool {
+        (a - b).norm() < 1e-3
+    }
+
+    #[test]
+    fn capsule_rests_on_top_of_a_box() {
+        let floor = Aabb::new(Vector::new(-2.0, -1.0, -2.0), Vector::new(2.0, 0.0, 2.0));
+
+        // sunk 0.1 into the floor
+        let capsule = Capsule::player(Vector::new(0.0, 0.9, 0.0));
+        let contact = capsule_vs_aabb(&capsule, &floor).unwrap();
+        assert!(approx(contact.normal, Y_AXIS));
+        assert!((contact.depth - 0.1).abs() < 1e-3);
+        assert!(contact.is_floor());
+
+        // hovering above it
+        let capsule = Capsule::player(Vector::new(0.0, 1.1, 0.0));
+        assert_eq!(capsule_vs_aabb(&capsule, &floor), None);
+    }
+
+    #[test]
+    fn capsule_is_pushed_diagonally_off_an_edge() {
+        let floor = Aabb::new(Vector::new(-2.0, -1.0, -2.0), Vector::new(2.0, 0.0, 2.0));
+
+        // the bottom cap overlaps the edge at x = 2
+        let capsule = Capsule::player(Vector::new(2.2, 0.9, 0.0));
+        let contact = capsule_vs_aabb(&capsule, &floor).unwrap();
+
+        let offset = Vector::new(0.2, 0.4, 0.0);
+        assert!(approx(contact.normal, offset.normalize()));
+        assert!((contact.depth - (0.5 - offset.norm())).abs() < 1e-3);
+    }
+
+    #[test]
+    fn capsule_slides_along_a_vertical_wall() {
+        let wall = Aabb::new(Vector::new(2.0, -5.0, -5.0), Vector::new(3.0, 5.0, 5.0));
+        let mut capsule = Capsule::player(Vector::new(1.6, 0.0, 0.0));
+
+        let contact = capsule_vs_aabb(&capsule, &wall).unwrap();
+        assert!(approx(contact.normal, -Vector::x()));
+        assert!(!contact.is_floor());
+
+        // only pushed out horizontally, so it keeps moving along the wall
+        let correction = resolve_capsule(&mut capsule, [wall]);
+        assert!(approx(correction, Vector::new(-0.1, 0.0, 0.0)));
+        capsule.translate(Vector::new(0.0, 1.0, 1.0));
+        assert!(resolve_capsule(&mut capsule, [wall]).norm() < 1e-3);
+    }
+
+    #[test]
+    fn dynamic_objects_split_the_correction() {
+        let mut a = BasicData::new(Vector::zeros(), Vector::repeat(1.0));
+        let mut b = BasicData::new(Vector::new(1.5, 0.0, 0.0), Vector::repeat(1.0));
+
+        assert!(resolve_basics(&mut a, &mut b));
+        assert_eq!(*a.pos(), Vector::new(-0.25, 0.0, 0.0));
+        assert_eq!(*b.pos(), Vector::new(1.75, 0.0, 0.0));
+    }
+}
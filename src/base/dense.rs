@@ -0,0 +1,161 @@
+use crate::*;
+use std::{collections::HashMap, ops::Index};
+
+/// A map of [`Id`]s stored densely in a [`Vec`], with a side index for lookups.
+///
+/// Iterating is a linear walk over contiguous memory, and a lookup is one hash.
+/// Removal swaps the last entry into the hole, so the order isn't stable.
+#[derive(Clone, Debug)]
+pub struct DenseMap<T> {
+    entries: Vec<(Id, T)>,
+    index: HashMap<Id, usize>,
+}
+
+impl<T> Default for DenseMap<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<T> DenseMap<T> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, id: &Id) -> bool {
+        self.index.contains_key(id)
+    }
+
+    pub fn get(&self, id: &Id) -> Option<&T> {
+        self.index.get(id).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, id: &Id) -> Option<&mut T> {
+        self.index.get(id).map(|&i| &mut self.entries[i].1)
+    }
+
+    /// Insert `value`, returning the value previously stored under `id`.
+    pub fn insert(&mut self, id: Id, value: T) -> Option<T> {
+        if let Some(&i) = self.index.get(&id) {
+            return Some(std::mem::replace(&mut self.entries[i].1, value));
+        }
+        self.index.insert(id, self.entries.len());
+        self.entries.push((id, value));
+        None
+    }
+
+    pub fn remove(&mut self, id: &Id) -> Option<T> {
+        let i = self.index.remove(id)?;
+        let (_, value) = self.entries.swap_remove(i);
+
+        // the last entry now fills the hole
+        if let Some((moved, _)) = self.entries.get(i) {
+            self.index.insert(*moved, i);
+        }
+        Some(value)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Id> {
+        self.entries.iter().map(|(id, _)| id)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.entries.iter_mut().map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Id, &T)> {
+        self.entries.iter().map(|(id, value)| (id, value))
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = (Id, T)> + '_ {
+        self.index.clear();
+        self.entries.drain(..)
+    }
+
+    /// Keep only the entries for which `f` returns `true`.
+    pub fn retain(&mut self, mut f: impl FnMut(&Id, &mut T) -> bool) {
+        self.entries.retain_mut(|(id, value)| f(id, value));
+
+        self.index.clear();
+        self.index
+            .extend(self.entries.iter().enumerate().map(|(i, (id, _))| (*id, i)));
+    }
+}
+
+impl<T> Index<&Id> for DenseMap<T> {
+    type Output = T;
+
+    fn index(&self, id: &Id) -> &Self::Output {
+        self.get(id).expect("no entry found for id")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every id resolves to its own entry.
+    fn assert_indexed(map: &DenseMap<i32>) {
+        assert_eq!(map.index.len(), map.len());
+
+        for (id, value) in map.iter() {
+            assert_eq!(map[id], *value);
+            assert_eq!(*value, *id as i32 * 10);
+        }
+    }
+
+    #[test]
+    fn remove_moves_the_last_entry_into_the_hole() {
+        let mut map = DenseMap::default();
+        for id in 0..5 {
+            map.insert(id, id as i32 * 10);
+        }
+
+        assert_eq!(map.remove(&1), Some(10));
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [0, 4, 2, 3]);
+        assert_indexed(&map);
+
+        // the last entry itself
+        assert_eq!(map.remove(&3), Some(30));
+        assert_eq!(map.remove(&3), None);
+        assert!(!map.contains_key(&3));
+        assert_indexed(&map);
+    }
+
+    #[test]
+    fn insert_replaces_in_place() {
+        let mut map = DenseMap::default();
+        map.insert(1, 10);
+        map.insert(2, 0);
+
+        assert_eq!(map.insert(2, 20), Some(0));
+        assert_eq!(map.len(), 2);
+        assert_indexed(&map);
+    }
+
+    #[test]
+    fn retain_and_drain_reindex() {
+        let mut map = DenseMap::default();
+        for id in 0..6 {
+            map.insert(id, id as i32 * 10);
+        }
+
+        map.retain(|id, _| id % 2 == 1);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 3, 5]);
+        assert_indexed(&map);
+
+        assert_eq!(map.drain().count(), 3);
+        assert!(map.is_empty() && map.get(&1).is_none());
+    }
+}
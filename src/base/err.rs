@@ -10,6 +10,11 @@ pub type BlazedResult<T = (), E = BlazedError> = std::result::Result<T, E>;
 pub enum HandshakeError {
     InvalidContent,
     InvalidType,
+    #[strum(to_string = "VersionMismatch (expected {expected}, found {found})")]
+    VersionMismatch {
+        expected: u16,
+        found: u16,
+    },
     Unknown,
 }
 
@@ -0,0 +1,299 @@
+use crate::*;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// A signed Q16.16 fixed-point number.
+///
+/// Every operation is plain integer arithmetic, so results are
+/// bit-identical on every platform (unlike `f32`). Results out of range
+/// saturate at [`Fixed::MIN`] or [`Fixed::MAX`] (division by zero included).
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    pub const FRAC_BITS: u32 = 16;
+    pub const ONE: Self = Self(1 << Self::FRAC_BITS);
+    pub const ZERO: Self = Self(0);
+    pub const MIN: Self = Self(i32::MIN);
+    pub const MAX: Self = Self(i32::MAX);
+
+    pub const fn from_bits(bits: i32) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    pub const fn from_int(value: i32) -> Self {
+        Self(value.saturating_mul(Self::ONE.0))
+    }
+
+    pub fn from_f32(value: f32) -> Self {
+        Self((value * Self::ONE.0 as f32).round() as i32)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / Self::ONE.0 as f32
+    }
+
+    pub const fn abs(self) -> Self {
+        Self(self.0.saturating_abs())
+    }
+
+    /// Divide by `rhs`, returning `None` if it's zero.
+    pub const fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.0 == 0 {
+            return None;
+        }
+        Some(Self::saturate(
+            ((self.0 as i64) << Self::FRAC_BITS) / rhs.0 as i64,
+        ))
+    }
+
+    const fn saturate(bits: i64) -> Self {
+        if bits > i32::MAX as i64 {
+            Self::MAX
+        } else if bits < i32::MIN as i64 {
+            Self::MIN
+        } else {
+            Self(bits as i32)
+        }
+    }
+}
+
+impl Add for Fixed {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::saturate((self.0 as i64 * rhs.0 as i64) >> Self::FRAC_BITS)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Self;
+
+    /// Saturates towards the sign of `self` when dividing by zero (see [`Fixed::checked_div`]).
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(rhs).unwrap_or(match self.0.signum() {
+            1 => Self::MAX,
+            -1 => Self::MIN,
+            _ => Self::ZERO,
+        })
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(self.0.saturating_neg())
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+    }
+}
+
+/// A deterministic counterpart of [`Vector`] for authoritative simulation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FixedVector {
+    pub x: Fixed,
+    pub y: Fixed,
+    pub z: Fixed,
+}
+
+impl FixedVector {
+    pub const fn new(x: Fixed, y: Fixed, z: Fixed) -> Self {
+        Self { x, y, z }
+    }
+
+    pub const fn zeros() -> Self {
+        Self::new(Fixed::ZERO, Fixed::ZERO, Fixed::ZERO)
+    }
+
+    pub fn dot(&self, rhs: &Self) -> Fixed {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    pub fn cross(&self, rhs: &Self) -> Self {
+        Self::new(
+            self.y * rhs.z - self.z * rhs.y,
+            self.z * rhs.x - self.x * rhs.z,
+            self.x * rhs.y - self.y * rhs.x,
+        )
+    }
+
+    pub fn component_mul(&self, rhs: &Self) -> Self {
+        Self::new(self.x * rhs.x, self.y * rhs.y, self.z * rhs.z)
+    }
+
+    pub fn norm_squared(&self) -> Fixed {
+        self.dot(self)
+    }
+}
+
+impl Add for FixedVector {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Sub for FixedVector {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl Mul<Fixed> for FixedVector {
+    type Output = Self;
+
+    fn mul(self, rhs: Fixed) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl Div<Fixed> for FixedVector {
+    type Output = Self;
+
+    fn div(self, rhs: Fixed) -> Self::Output {
+        Self::new(self.x / rhs, self.y / rhs, self.z / rhs)
+    }
+}
+
+impl Neg for FixedVector {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl AddAssign for FixedVector {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs
+    }
+}
+
+impl SubAssign for FixedVector {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs
+    }
+}
+
+impl From<Vector> for FixedVector {
+    fn from(value: Vector) -> Self {
+        Self::new(
+            Fixed::from_f32(value.x),
+            Fixed::from_f32(value.y),
+            Fixed::from_f32(value.z),
+        )
+    }
+}
+
+impl From<FixedVector> for Vector {
+    fn from(value: FixedVector) -> Self {
+        Vector::new(value.x.to_f32(), value.y.to_f32(), value.z.to_f32())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector(x: i32, y: i32, z: i32) -> FixedVector {
+        FixedVector::new(Fixed::from_int(x), Fixed::from_int(y), Fixed::from_int(z))
+    }
+
+    #[test]
+    fn fixed_arithmetic() {
+        let (a, b) = (Fixed::from_f32(1.5), Fixed::from_f32(-0.25));
+
+        assert_eq!((a + b).to_f32(), 1.25);
+        assert_eq!((a - b).to_f32(), 1.75);
+        assert_eq!((a * b).to_f32(), -0.375);
+        assert_eq!((a / b).to_f32(), -6.0);
+        assert_eq!((-b).abs(), Fixed::from_f32(0.25));
+        assert_eq!(Fixed::from_int(3).to_bits(), 3 << Fixed::FRAC_BITS);
+    }
+
+    #[test]
+    fn out_of_range_saturates() {
+        let big = Fixed::from_int(30000);
+
+        assert_eq!(big + big, Fixed::MAX);
+        assert_eq!(-big - big, Fixed::MIN);
+        assert_eq!(big * big, Fixed::MAX);
+        assert_eq!(big * -big, Fixed::MIN);
+        assert_eq!(Fixed::from_int(40000), Fixed::MAX);
+        assert_eq!(-Fixed::MIN, Fixed::MAX);
+    }
+
+    #[test]
+    fn division_by_zero_saturates() {
+        let one = Fixed::ONE;
+
+        assert_eq!(one.checked_div(Fixed::ZERO), None);
+        assert_eq!(one / Fixed::ZERO, Fixed::MAX);
+        assert_eq!(-one / Fixed::ZERO, Fixed::MIN);
+        assert_eq!(Fixed::ZERO / Fixed::ZERO, Fixed::ZERO);
+        assert_eq!(Fixed::MAX / Fixed::from_f32(0.5), Fixed::MAX);
+    }
+
+    #[test]
+    fn vector_products() {
+        let (x, y) = (vector(1, 0, 0), vector(0, 1, 0));
+
+        assert_eq!(x.cross(&y), vector(0, 0, 1));
+        assert_eq!(x.dot(&y), Fixed::ZERO);
+        assert_eq!(vector(1, 2, 3).dot(&vector(4, 5, 6)), Fixed::from_int(32));
+        assert_eq!(vector(1, 2, 3).norm_squared(), Fixed::from_int(14));
+        assert_eq!(
+            vector(1, 2, 3).component_mul(&vector(2, 2, -1)),
+            vector(2, 4, -3)
+        );
+    }
+
+    #[test]
+    fn vector_ops_are_deterministic() {
+        let mut v = vector(1, -2, 3);
+        v += vector(1, 1, 1) * Fixed::from_f32(0.5);
+        v -= -vector(0, 0, 1);
+
+        assert_eq!(v, FixedVector::from(Vector::new(1.5, -1.5, 4.5)));
+        assert_eq!(
+            Vector::from(v / Fixed::from_int(2)),
+            Vector::new(0.75, -0.75, 2.25)
+        );
+        assert_eq!(FixedVector::from(Vector::from(v)), v);
+    }
+}
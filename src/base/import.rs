@@ -0,0 +1,373 @@
+use crate::*;
+use std::collections::HashMap;
+
+/// The up-axis convention of imported assets.
+///
+/// The engine is right-handed and Y-up (the default); imports are rotated to match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CoordinateSystem {
+    #[default]
+    YUp,
+
+    /// Right-handed and Z-up (e.g., Blender, most CAD tools).
+    ZUp,
+}
+
+impl CoordinateSystem {
+    /// The rotation from this convention into the engine's.
+    pub fn to_engine(&self) -> Rotation {
+        match self {
+            Self::YUp => Rotation::identity(),
+            Self::ZUp => Rotation::from_axis_angle(&Unit::new_unchecked(X_AXIS), -90.0 * RADIAN),
+        }
+    }
+
+    pub fn convert(&self, v: &Vector) -> Vector {
+        match self {
+            Self::YUp => *v,
+            // exact version of [`CoordinateSystem::to_engine`]
+            Self::ZUp => Vector::new(v.x, v.z, -v.y),
+        }
+    }
+}
+
+/// How an imported file is turned into a [`Mesh`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ImportOptions {
+    pub system: CoordinateSystem,
+    /// The bits of the welding epsilon (kept as bits so options can be hashed).
+    weld: Option<u32>,
+}
+
+impl ImportOptions {
+    pub const fn new(system: CoordinateSystem) -> Self {
+        Self { system, weld: None }
+    }
+
+    /// Merge vertices within `epsilon` of each other (see [`weld_vertices`]).
+    pub fn with_weld(mut self, epsilon: f32) -> Self {
+        self.weld = Some(epsilon.max(0.0).to_bits());
+        self
+    }
+
+    pub fn weld(&self) -> Option<f32> {
+        self.weld.map(f32::from_bits)
+    }
+}
+
+impl From<CoordinateSystem> for ImportOptions {
+    fn from(system: CoordinateSystem) -> Self {
+        Self::new(system)
+    }
+}
+
+/// Merge the vertices (of `stride` floats each) whose every component is within `epsilon`.
+///
+/// Returns the distinct vertices, and the index of each original vertex among them.
+pub fn weld_vertices(vertices: &[f32], stride: usize, epsilon: f32) -> (Vec<f32>, Vec<u32>) {
+    let stride = stride.max(1);
+    let epsilon = epsilon.max(0.0);
+
+    // vertices are bucketed by their position (at most 3 components), and compared against
+    // the neighboring buckets too, as the ones within epsilon may straddle a boundary
+    let dims = stride.min(3);
+    let cell_size = epsilon.max(f32::EPSILON);
+    let cell = |v: &[f32]| -> [i64; 3] {
+        let mut cell = [0; 3];
+        for (c, x) in cell.iter_mut().zip(&v[..dims]) {
+            *c = (x / cell_size).floor() as i64
+        }
+        cell
+    };
+
+    let mut welded = Vec::<f32>::new();
+    let mut remap = Vec::with_capacity(vertices.len() / stride);
+    let mut cells = HashMap::<[i64; 3], Vec<u32>>::new();
+
+    for vertex in vertices.chunks_exact(stride) {
+        let home = cell(vertex);
+
+        let found = (0..3_i64.pow(dims as u32))
+            .map(|n| {
+                let mut neighbor = home;
+                for (i, c) in neighbor.iter_mut().take(dims).enumerate() {
+                    *c += n / 3_i64.pow(i as u32) % 3 - 1
+                }
+                neighbor
+            })
+            .filter_map(|neighbor| cells.get(&neighbor))
+            .flatten()
+            .copied()
+            .find(|&i| {
+                let other = &welded[i as usize * stride..][..stride];
+                vertex
+                    .iter()
+                    .zip(other)
+                    .all(|(a, b)| (a - b).abs() <= epsilon)
+            });
+
+        let index = found.unwrap_or_else(|| {
+            let index = (welded.len() / stride) as u32;
+            welded.extend_from_slice(vertex);
+            cells.entry(home).or_default().push(index);
+            index
+        });
+        remap.push(index);
+    }
+    (welded, remap)
+}
+
+/// Triangulated geometry (one normal per vertex) in the engine's coordinate system.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<Vector>,
+    pub normals: Vec<Vector>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// Interleave the positions (and normals) into a vertex buffer.
+    pub fn vertices(&self, has_norms: bool) -> Vec<f32> {
+        self.positions
+            .iter()
+            .zip(&self.normals)
+            .flat_map(|(p, n)| {
+                let norm = has_norms.then_some([n.x, n.y, n.z]);
+                [p.x, p.y, p.z]
+                    .into_iter()
+                    .chain(norm.into_iter().flatten())
+            })
+            .collect()
+    }
+
+    /// Merge the vertices (positions and normals) within `epsilon`, remapping the indices.
+    pub fn weld(&mut self, epsilon: f32) {
+        let (welded, remap) = weld_vertices(&self.vertices(true), 6, epsilon);
+
+        (self.positions, self.normals) = welded
+            .chunks_exact(6)
+            .map(|v| (Vector::new(v[0], v[1], v[2]), Vector::new(v[3], v[4], v[5])))
+            .unzip();
+
+        for i in &mut self.indices {
+            *i = remap[*i as usize]
+        }
+    }
+
+    /// Faces of a smaller (doubled) area are degenerate, so they don't contribute to normals.
+    pub const DEGENERATE_AREA: f32 = 1e-12;
+
+    /// Replace every normal with the area-weighted average of the faces sharing its vertex.
+    ///
+    /// Degenerate faces are skipped, and a vertex without any other face falls back to
+    /// the direction of its position (or +Y at the origin).
+    pub fn compute_normals(&mut self) {
+        self.normals = vec![Vector::zeros(); self.positions.len()];
+
+        for tri in self.indices.chunks_exact(3) {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| i as usize);
+            let [pa, pb, pc] = [a, b, c].map(|i| self.positions[i]);
+
+            // the length of the cross product is twice the area
+            let face = (pb - pa).cross(&(pc - pa));
+
+            // (NaN coordinates are just as degenerate)
+            let area = face.norm();
+            if area.is_nan() || area < Self::DEGENERATE_AREA {
+                continue;
+            }
+
+            for i in [a, b, c] {
+                self.normals[i] += face
+            }
+        }
+
+        for (n, pos) in self.normals.iter_mut().zip(&self.positions) {
+            *n = n
+                .try_normalize(f32::EPSILON)
+                .or_else(|| pos.try_normalize(f32::EPSILON))
+                .unwrap_or(Y_AXIS)
+        }
+    }
+}
+
+/// Parse the geometry of a Wavefront OBJ file, converting it from `system`.
+///
+/// Only positions, normals, and faces are read; polygons are triangulated as fans.
+/// Without any normals, smooth normals are computed.
+pub fn parse_obj(src: &str, system: CoordinateSystem) -> BlazedResult<Mesh> {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+
+    // (position, normal) -> vertex
+    let mut vertices = HashMap::<(usize, Option<usize>), u32>::new();
+    let mut mesh = Mesh::default();
+    let mut has_norms = true;
+
+    let resolve = |index: &str, len: usize, line: usize| -> BlazedResult<usize> {
+        let index = index
+            .parse::<isize>()
+            .map_err(|_| format!("[obj:{}] Invalid index '{}'", line, index))?;
+
+        // 1-based, or relative to the end when negative
+        let resolved = if index < 0 {
+            len as isize + index
+        } else {
+            index - 1
+        };
+        (0..len as isize)
+            .contains(&resolved)
+            .then_some(resolved as usize)
+            .ok_or(format!("[obj:{}] Index out of bounds ({})", line, index).into())
+    };
+
+    for (line, content) in src.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+        let mut parts = content.split_whitespace();
+
+        match parts.next() {
+            Some(kind @ ("v" | "vn")) => {
+                let coords = parts
+                    .take(3)
+                    .map(str::parse::<f32>)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| format!("[obj:{}] Invalid coordinate", line))?;
+
+                let [x, y, z] = coords[..] else {
+                    return Err(format!("[obj:{}] Expected 3 coordinates", line).into());
+                };
+                let v = system.convert(&Vector::new(x, y, z));
+
+                if kind == "v" {
+                    positions.push(v)
+                } else {
+                    normals.push(v)
+                }
+            }
+            Some("f") => {
+                let mut face = Vec::new();
+
+                for corner in parts {
+                    // v, v/vt, v//vn, or v/vt/vn
+                    let mut refs = corner.split('/');
+                    let pos = resolve(refs.next().unwrap_or_default(), positions.len(), line)?;
+                    let norm = match refs.nth(1) {
+                        Some(n) if !n.is_empty() => Some(resolve(n, normals.len(), line)?),
+                        _ => None,
+                    };
+                    has_norms &= norm.is_some();
+
+                    let next = vertices.len() as u32;
+                    let vertex = *vertices.entry((pos, norm)).or_insert_with(|| {
+                        mesh.positions.push(positions[pos]);
+                        mesh.normals
+                            .push(norm.map_or(Vector::zeros(), |n| normals[n]));
+                        next
+                    });
+                    face.push(vertex);
+                }
+
+                if face.len() < 3 {
+                    return Err(format!("[obj:{}] Faces need at least 3 vertices", line).into());
+                }
+
+                for i in 1..face.len() - 1 {
+                    mesh.indices.extend([face[0], face[i], face[i + 1]])
+                }
+            }
+            _ => (), // comments, groups, materials, texture coordinates, etc.
+        }
+    }
+
+    if !has_norms {
+        mesh.compute_normals()
+    }
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unit quad as two triangles (positions only), sharing the edge (1, 0)-(0, 1).
+    const QUAD: [f32; 18] = [
+        0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, //
+        1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0,
+    ];
+
+    #[test]
+    fn degenerate_faces_fall_back_without_nans() {
+        let mut mesh = Mesh {
+            positions: vec![
+                Vector::zeros(),
+                Vector::x(),
+                Vector::y(),
+                // collapsed onto a line
+                Vector::zeros(),
+                Vector::zeros(),
+                Vector::new(2.0, 0.0, 0.0),
+            ],
+            normals: Vec::new(),
+            indices: vec![0, 1, 2, 3, 4, 5],
+        };
+        mesh.compute_normals();
+
+        assert!(mesh.normals.iter().all(|n| !n.iter().any(|c| c.is_nan())));
+        assert_eq!(mesh.normals[..3], [Vector::z(); 3]);
+        assert_eq!(mesh.normals[3..], [Y_AXIS, Y_AXIS, Vector::x()]);
+    }
+
+    #[test]
+    fn quad_welds_six_vertices_into_four() {
+        let (welded, remap) = weld_vertices(&QUAD, 3, 1e-5);
+
+        assert_eq!(welded.len(), 4 * 3);
+        assert_eq!(remap, [0, 1, 2, 1, 3, 2]);
+    }
+
+    #[test]
+    fn vertices_straddling_a_cell_are_welded() {
+        let epsilon = 0.1;
+        let vertices = [0.099, 0.0, 0.0, 0.101, 0.0, 0.0, 0.5, 0.0, 0.0];
+
+        let (welded, remap) = weld_vertices(&vertices, 3, epsilon);
+
+        assert_eq!(welded.len(), 2 * 3);
+        assert_eq!(remap, [0, 0, 1]);
+    }
+
+    #[test]
+    fn every_component_must_match() {
+        // the same position with opposite normals (e.g., a hard edge)
+        let vertices = [0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0];
+
+        let (welded, remap) = weld_vertices(&vertices, 6, 1e-5);
+
+        assert_eq!(welded, vertices);
+        assert_eq!(remap, [0, 1]);
+    }
+
+    #[test]
+    fn welded_mesh_keeps_its_triangles() {
+        let mut mesh = Mesh {
+            positions: QUAD
+                .chunks_exact(3)
+                .map(|p| Vector::new(p[0], p[1], p[2]))
+                .collect(),
+            normals: vec![Vector::z(); 6],
+            indices: (0..6).collect(),
+        };
+        let triangles = |mesh: &Mesh| {
+            mesh.indices
+                .iter()
+                .map(|i| mesh.positions[*i as usize])
+                .collect::<Vec<_>>()
+        };
+        let before = triangles(&mesh);
+
+        mesh.weld(1e-5);
+
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.normals.len(), 4);
+        assert_eq!(triangles(&mesh), before);
+    }
+}
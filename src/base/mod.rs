@@ -1,15 +1,29 @@
 mod atom;
+mod bvh;
 mod cam;
+mod collide;
+mod dense;
 mod err;
+mod fixed;
+mod import;
 mod keys;
 mod net;
+mod palette;
+mod sky;
 mod util;
 
 pub use atom::*;
+pub use bvh::*;
 pub use cam::*;
+pub use collide::*;
+pub use dense::*;
 pub use err::*;
+pub use fixed::*;
+pub use import::*;
 pub use keys::*;
 pub use net::*;
+pub use palette::*;
+pub use sky::*;
 pub use util::*;
 
 pub use crossbeam_utils::Backoff;
@@ -4,7 +4,7 @@ use bincode::serialize;
 use packet_enum::*;
 use std::{
     io::{Read, Write},
-    net::{SocketAddr, TcpStream, UdpSocket},
+    net::{Shutdown, SocketAddr, TcpStream, UdpSocket},
 };
 
 pub trait UdpConn {
@@ -29,9 +29,11 @@ pub trait UdpConn {
 pub trait TcpConn {
     fn stream(&self) -> &TcpStream;
 
-    fn send(&self, packet: &impl AsPacketSend) -> BlazedResult<()> {
+    /// Send `packet`, returning the number of bytes written.
+    fn send(&self, packet: &impl AsPacketSend) -> BlazedResult<usize> {
         let bytes = serialize(packet)?;
-        self.stream().write_all(&bytes).map_err(Into::into)
+        self.stream().write_all(&bytes)?;
+        Ok(bytes.len())
     }
 
     fn recv<'a, K: AsPacketKind, T: AsPacketRecv<'a, K>, const N: usize>(
@@ -42,4 +44,12 @@ pub trait TcpConn {
         let bytes = self.stream().read(buf)?;
         recv(&buf[..bytes], kind)
     }
+
+    /// Flush everything sent so far, then shut the connection down (both ways).
+    fn close(&self) -> BlazedResult<()> {
+        let mut stream = self.stream();
+        stream.flush()?;
+        stream.shutdown(Shutdown::Both)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,174 @@
+use crate::*;
+use std::collections::VecDeque;
+
+/// The positions of every object as of a tick.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Snapshot {
+    tick: u64,
+    positions: Vec<(Id, Vector)>,
+}
+
+impl Snapshot {
+    pub fn new(tick: u64, positions: impl IntoIterator<Item = (Id, Vector)>) -> Self {
+        Self {
+            tick,
+            positions: positions.into_iter().collect(),
+        }
+    }
+
+    pub const fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn positions(&self) -> &[(Id, Vector)] {
+        &self.positions
+    }
+
+    /// The position of `id` as of the tick (if it existed).
+    pub fn pos(&self, id: Id) -> Option<&Vector> {
+        self.positions
+            .iter()
+            .find_map(|(other, pos)| (*other == id).then_some(pos))
+    }
+
+    /// The id of the nearest player (where it was as of the tick) hit by the ray.
+    ///
+    /// Rays starting inside a player (e.g., the shooter) never hit it.
+    pub fn raycast(&self, origin: &Vector, dir: &Vector) -> Option<Id> {
+        let half = Vector::new(
+            Capsule::PLAYER_RADIUS,
+            Capsule::PLAYER_HEIGHT / 2.0,
+            Capsule::PLAYER_RADIUS,
+        );
+
+        Bvh::build(
+            self.positions
+                .iter()
+                .map(|(id, pos)| (*id, Aabb::from_center(pos, &half))),
+        )
+        .raycast(origin, dir)
+    }
+}
+
+/// The most recent snapshots of the world, for rewinding it to when a (lagging) client acted.
+///
+/// Snapshots are kept by ascending tick. The oldest one is evicted once full.
+#[derive(Debug)]
+pub struct SnapshotHistory {
+    capacity: usize,
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl SnapshotHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            snapshots: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Keep `snapshot`, dropping it if it's not newer than the latest one.
+    pub fn record(&mut self, snapshot: Snapshot) -> bool {
+        if self
+            .latest()
+            .is_some_and(|latest| snapshot.tick <= latest.tick)
+        {
+            return false;
+        }
+
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+        true
+    }
+
+    /// The snapshot of exactly `tick` (if it's still kept).
+    pub fn snapshot_at(&self, tick: u64) -> Option<&Snapshot> {
+        self.snapshots
+            .binary_search_by_key(&tick, Snapshot::tick)
+            .ok()
+            .map(|i| &self.snapshots[i])
+    }
+
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.snapshots.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single player walking along x, one unit per tick.
+    fn history(ticks: u64) -> SnapshotHistory {
+        let mut history = SnapshotHistory::new(MAX_HISTORY_TICKS);
+
+        for tick in 1..=ticks {
+            history.record(Snapshot::new(
+                tick,
+                [(0, Vector::new(tick as f32, 0.0, 0.0))],
+            ));
+        }
+        history
+    }
+
+    #[test]
+    fn rewinding_yields_the_positions_of_the_tick() {
+        let history = history(10);
+        let snapshot = history.snapshot_at(5).unwrap();
+
+        assert_eq!(snapshot.tick(), 5);
+        assert_eq!(snapshot.pos(0), Some(&Vector::new(5.0, 0.0, 0.0)));
+        assert_eq!(
+            history.latest().unwrap().pos(0),
+            Some(&Vector::new(10.0, 0.0, 0.0))
+        );
+        assert!(history.snapshot_at(11).is_none());
+    }
+
+    #[test]
+    fn hit_scans_see_the_past() {
+        let history = history(10);
+
+        // aimed at where the player was at tick 5, since gone
+        let origin = Vector::new(5.0, 0.0, 10.0);
+        let dir = Vector::new(0.0, 0.0, -1.0);
+
+        assert_eq!(
+            history.snapshot_at(5).unwrap().raycast(&origin, &dir),
+            Some(0)
+        );
+        assert_eq!(history.latest().unwrap().raycast(&origin, &dir), None);
+    }
+
+    #[test]
+    fn oldest_ticks_are_evicted() {
+        let mut history = SnapshotHistory::new(4);
+
+        for tick in 1..=10 {
+            assert!(history.record(Snapshot::new(tick, [])));
+        }
+        assert!(!history.record(Snapshot::new(10, [])));
+
+        assert_eq!(history.len(), 4);
+        assert!(history.snapshot_at(6).is_none());
+        assert!(history.snapshot_at(7).is_some());
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Smooths out variable latency by holding packets for a fixed delay before releasing them.
+///
+/// Packets are reordered by sequence number. Duplicates, and packets older than the
+/// last released one, are dropped. The oldest packet is evicted once full.
+#[derive(Debug)]
+pub struct JitterBuffer<T> {
+    delay: Duration,
+    capacity: usize,
+    packets: VecDeque<(u32, Instant, T)>,
+    released: Option<u32>,
+}
+
+impl<T> JitterBuffer<T> {
+    pub fn new(delay: Duration, capacity: usize) -> Self {
+        Self {
+            delay,
+            capacity: capacity.max(1),
+            packets: VecDeque::with_capacity(capacity),
+            released: None,
+        }
+    }
+
+    pub const fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay = delay
+    }
+
+    /// Hold `state` as of now, returning `false` if it was dropped (duplicate or late).
+    pub fn push(&mut self, seq: u32, state: T) -> bool {
+        self.push_at(seq, state, Instant::now())
+    }
+
+    /// Hold `state` as if it arrived at `arrival`.
+    pub fn push_at(&mut self, seq: u32, state: T, arrival: Instant) -> bool {
+        if self.released.is_some_and(|released| seq <= released) {
+            return false;
+        }
+
+        let Err(i) = self.packets.binary_search_by_key(&seq, |(s, ..)| *s) else {
+            return false;
+        };
+
+        self.packets.insert(i, (seq, arrival, state));
+
+        if self.packets.len() > self.capacity {
+            if let Some((seq, ..)) = self.packets.pop_front() {
+                self.released = Some(seq);
+            }
+        }
+        true
+    }
+
+    /// Release the next packet (in sequence) once it has been held for the delay.
+    pub fn pop(&mut self, render_time: Instant) -> Option<T> {
+        let (_, arrival, _) = self.packets.front()?;
+
+        if render_time < *arrival + self.delay {
+            return None;
+        }
+
+        let (seq, _, state) = self.packets.pop_front()?;
+        self.released = Some(seq);
+        Some(state)
+    }
+
+    /// The sequence number of the last released (or evicted) packet.
+    pub const fn released(&self) -> Option<u32> {
+        self.released
+    }
+
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn out_of_order_packets_are_reordered() {
+        let start = Instant::now();
+        let mut buffer = JitterBuffer::new(DELAY, 8);
+
+        for seq in [3, 1, 2] {
+            assert!(buffer.push_at(seq, seq * 10, start));
+        }
+
+        let released = std::iter::from_fn(|| buffer.pop(start + DELAY)).collect::<Vec<_>>();
+        assert_eq!(released, [10, 20, 30]);
+        assert_eq!(buffer.released(), Some(3));
+    }
+
+    #[test]
+    fn duplicate_and_late_packets_are_dropped() {
+        let start = Instant::now();
+        let mut buffer = JitterBuffer::new(DELAY, 8);
+
+        assert!(buffer.push_at(2, "first", start));
+        assert!(!buffer.push_at(2, "duplicate", start));
+        assert_eq!(buffer.len(), 1);
+
+        assert_eq!(buffer.pop(start + DELAY), Some("first"));
+        assert!(!buffer.push_at(1, "late", start));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn packets_are_held_for_the_delay() {
+        let start = Instant::now();
+        let mut buffer = JitterBuffer::new(DELAY, 8);
+
+        buffer.push_at(1, (), start);
+        assert_eq!(buffer.pop(start + DELAY / 2), None);
+        assert_eq!(buffer.pop(start + DELAY), Some(()));
+    }
+
+    #[test]
+    fn oldest_packet_is_evicted_once_full() {
+        let start = Instant::now();
+        let mut buffer = JitterBuffer::new(DELAY, 2);
+
+        for seq in 1..=3 {
+            buffer.push_at(seq, seq, start);
+        }
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.released(), Some(1));
+        assert_eq!(buffer.pop(start + DELAY), Some(2));
+    }
+}
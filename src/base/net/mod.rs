@@ -1,4 +1,6 @@
 mod conn;
+mod history;
+mod jitter;
 mod obj;
 mod packet;
 mod tcp;
@@ -6,10 +8,12 @@ mod udp;
 mod util;
 
 pub use conn::*;
+pub use history::*;
+pub use jitter::*;
 pub use obj::*;
 pub use packet::*;
 pub use tcp::*;
 pub use udp::*;
-pub use util::get_socket_addr;
+pub use util::{get_socket_addr, packet_len, state_hash};
 
 use util::recv;
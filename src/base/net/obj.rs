@@ -1,5 +1,10 @@
 use crate::*;
 use enum_unit::*;
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser::SerializeTuple,
+    Deserializer, Serializer,
+};
 use std::ops::{Deref, DerefMut};
 
 #[derive(Clone, Copy, Debug)]
@@ -86,12 +91,25 @@ impl DerefMut for PlayerMut<'_> {
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct PlayerData {
     attr: CameraAttr,
+
+    // each peer resolves its own collisions
+    #[serde(skip)]
+    grounded: bool,
+
+    // only the local player is extrapolated
+    #[serde(skip)]
+    vel: Vector,
 }
 
 impl PlayerData {
+    /// The furthest (in seconds) [`PlayerData::extrapolated_pos`] projects ahead.
+    pub const MAX_EXTRAPOLATION: f32 = 0.5;
+
     pub fn new(pos: Vector) -> Self {
         Self {
             attr: CameraAttr::new(pos),
+            grounded: false,
+            vel: Vector::zeros(),
         }
     }
 
@@ -107,6 +125,51 @@ impl PlayerData {
         &DIAGONAL
     }
 
+    pub fn collider(&self) -> Capsule {
+        Capsule::player(*self.pos())
+    }
+
+    /// The velocity (per second) as of the last simulation tick.
+    pub const fn vel(&self) -> &Vector {
+        &self.vel
+    }
+
+    pub fn set_vel(&mut self, vel: Vector) {
+        self.vel = vel
+    }
+
+    /// The position `partial_dt` seconds past the last tick, projected along the velocity.
+    ///
+    /// The projection is limited to [`PlayerData::MAX_EXTRAPOLATION`] (a long stall
+    /// would otherwise overshoot), and negative times are treated as none.
+    pub fn extrapolated_pos(&self, partial_dt: f32) -> Vector {
+        self.extrapolated_pos_within(partial_dt, Self::MAX_EXTRAPOLATION)
+    }
+
+    /// [`PlayerData::extrapolated_pos`] projecting at most `max` seconds ahead.
+    pub fn extrapolated_pos_within(&self, partial_dt: f32, max: f32) -> Vector {
+        let dt = partial_dt.clamp(0.0, max.max(0.0));
+        self.pos() + self.vel * dt
+    }
+
+    /// Whether the player stood on a floor as of the last [`PlayerData::resolve_collisions`].
+    pub const fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// Push the player out of every box, returning the correction, then check if it's grounded.
+    pub fn resolve_collisions(&mut self, boxes: impl IntoIterator<Item = Aabb>) -> Vector {
+        self.grounded = false;
+
+        let boxes = boxes.into_iter().collect::<Vec<_>>();
+        let mut capsule = self.collider();
+        let correction = resolve_capsule(&mut capsule, boxes.iter().copied());
+
+        self.attr.eye += correction;
+        self.grounded = capsule_grounded(&capsule, boxes);
+        correction
+    }
+
     pub const fn attr(&self) -> CameraAttr {
         self.attr
     }
@@ -174,15 +237,59 @@ impl DerefMut for BasicMut<'_> {
     }
 }
 
+/// Tracks how long an object has been at rest (see [`BasicData::is_asleep`]).
+#[derive(Clone, Copy, Debug)]
+pub struct Sleep {
+    rest: f32,
+    timeout: f32,
+    asleep: bool,
+}
+
+impl Sleep {
+    /// Objects slower than this (units/sec) are at rest.
+    pub const THRESHOLD: f32 = 0.01;
+
+    const DEFAULT: Self = Self {
+        rest: 0.0,
+        timeout: 1.0,
+        asleep: false,
+    };
+}
+
+impl Default for Sleep {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct BasicData {
     pos: Vector,
     dim: Vector,
+    vel: Vector,
+    lifetime: Option<f32>,
+    kinematic: bool,
+
+    // each peer decides when to skip its own integration
+    #[serde(skip)]
+    sleep: Sleep,
 }
 
 impl BasicData {
     pub const fn new(pos: Vector, dim: Vector) -> Self {
-        Self { pos, dim }
+        Self::with_motion(pos, dim, Vector::new(0.0, 0.0, 0.0), None)
+    }
+
+    /// Construct data that moves at `vel` units/sec and expires after `lifetime` seconds.
+    pub const fn with_motion(pos: Vector, dim: Vector, vel: Vector, lifetime: Option<f32>) -> Self {
+        Self {
+            pos,
+            dim,
+            vel,
+            lifetime,
+            kinematic: false,
+            sleep: Sleep::DEFAULT,
+        }
     }
 
     pub const fn pos(&self) -> &Vector {
@@ -200,6 +307,127 @@ impl BasicData {
     pub fn dim_mut(&mut self) -> &mut Vector {
         &mut self.dim
     }
+
+    /// The geometry is unit-sized, so resizing only affects the scaling (see [`ObjectData::set_dim`]).
+    pub fn set_dim(&mut self, dim: Vector) {
+        self.dim = dim
+    }
+
+    pub const fn vel(&self) -> &Vector {
+        &self.vel
+    }
+
+    pub fn vel_mut(&mut self) -> &mut Vector {
+        &mut self.vel
+    }
+
+    /// The remaining lifetime (in seconds), if the object expires.
+    pub const fn lifetime(&self) -> Option<f32> {
+        self.lifetime
+    }
+
+    /// Whether the object is only moved by its velocity (or scripts), never by other objects.
+    ///
+    /// Unlike dynamic objects, kinematic objects ignore gravity and collision responses
+    /// (including impulses), but others still collide with (and resolve against) them.
+    pub const fn is_kinematic(&self) -> bool {
+        self.kinematic
+    }
+
+    pub fn set_kinematic(&mut self, kinematic: bool) {
+        self.kinematic = kinematic;
+        self.wake()
+    }
+
+    /// The box enclosing the object (see [`Aabb::from_center`]).
+    pub fn bounds(&self) -> Aabb {
+        Aabb::from_center(&self.pos, &self.dim)
+    }
+
+    /// Accelerate by `gravity` for `dt` seconds (ignored if kinematic or asleep).
+    pub fn apply_gravity(&mut self, gravity: &Vector, dt: f32) {
+        if !self.kinematic && !self.sleep.asleep {
+            self.vel += gravity * dt
+        }
+    }
+
+    /// Move by `correction` (out of another object), stopping any motion back into it
+    /// (ignored if kinematic).
+    pub fn resolve(&mut self, correction: Vector) {
+        let Some(normal) = correction.try_normalize(f32::EPSILON) else {
+            return;
+        };
+        if self.kinematic {
+            return;
+        }
+        self.pos += correction;
+
+        let towards = self.vel.dot(&normal);
+        if towards < 0.0 {
+            self.vel -= normal * towards
+        }
+    }
+
+    /// Add `impulse` to the velocity, waking the object (ignored if kinematic).
+    pub fn apply_impulse(&mut self, impulse: Vector) {
+        if self.kinematic {
+            return;
+        }
+        self.vel += impulse;
+        self.wake()
+    }
+
+    /// Whether the object has been at rest for long enough to skip its integration.
+    pub const fn is_asleep(&self) -> bool {
+        self.sleep.asleep
+    }
+
+    /// Resume integration (e.g., after an external change of velocity).
+    pub fn wake(&mut self) {
+        self.sleep.rest = 0.0;
+        self.sleep.asleep = false
+    }
+
+    /// How long (in seconds) the object must rest before falling asleep.
+    pub const fn sleep_timeout(&self) -> f32 {
+        self.sleep.timeout
+    }
+
+    pub fn set_sleep_timeout(&mut self, timeout: f32) {
+        self.sleep.timeout = timeout
+    }
+
+    /// Count `dt` seconds towards falling asleep if at rest, otherwise start over.
+    ///
+    /// Called once collisions are resolved, since the velocity of a resting object is only
+    /// zero once its contacts cancel the gravity. Kinematic objects are scripted, so they
+    /// never fall asleep.
+    pub fn settle(&mut self, dt: f32) {
+        if self.sleep.asleep {
+            return;
+        }
+        if !self.kinematic && self.vel.norm() < Sleep::THRESHOLD {
+            self.sleep.rest += dt;
+            self.sleep.asleep = self.sleep.rest >= self.sleep.timeout;
+        } else {
+            self.sleep.rest = 0.0
+        }
+    }
+
+    /// Advance the position by `dt` seconds (unless asleep), returning `false` once expired.
+    pub fn integrate(&mut self, dt: f32) -> bool {
+        if !self.sleep.asleep {
+            self.pos += self.vel * dt;
+        }
+
+        match self.lifetime.as_mut() {
+            Some(lifetime) => {
+                *lifetime -= dt;
+                *lifetime > 0.0
+            }
+            None => true,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, EnumUnit)]
@@ -231,6 +459,9 @@ pub struct Color {
 }
 
 impl Color {
+    pub const BLACK: Self = Self::new([0.0, 0.0, 0.0, 1.0], false);
+    pub const WHITE: Self = Self::new([1.0, 1.0, 1.0, 1.0], false);
+
     pub const fn new(inner: [f32; 4], emits: bool) -> Self {
         Self { inner, emits }
     }
@@ -243,9 +474,35 @@ impl Color {
         self.inner[3]
     }
 
+    pub const fn rgb(&self) -> [f32; 3] {
+        let [r, g, b, _] = self.inner;
+        [r, g, b]
+    }
+
     pub const fn is_opaque(alpha: f32) -> bool {
         alpha as i32 == 1
     }
+
+    /// The RGB channels multiplied by alpha, as the shaders output (and blend) it.
+    pub const fn premultiplied(&self) -> Self {
+        let [r, g, b, a] = self.inner;
+        Self::new([r * a, g * a, b * a, a], self.emits)
+    }
+
+    /// Component-wise multiply of the RGB channels (alpha and emission are kept).
+    pub fn multiply(&self, rhs: &Self) -> Self {
+        let [r, g, b, a] = self.inner;
+        Self::new(
+            [r * rhs.inner[0], g * rhs.inner[1], b * rhs.inner[2], a],
+            self.emits,
+        )
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::BLACK
+    }
 }
 
 impl Deref for Color {
@@ -256,12 +513,156 @@ impl Deref for Color {
     }
 }
 
+/// The base color of an object, either stored in full or referencing a [`Palette`].
+///
+/// Serialized as a tag byte followed by either the color, or only the index (one byte), the
+/// palette entry carrying the alpha and whether it emits light.
+#[derive(Clone, Copy, Debug)]
+struct Paint {
+    color: Color,
+    index: Option<u8>,
+}
+
+impl Paint {
+    const RGBA: u8 = 0;
+    const INDEXED: u8 = 1;
+}
+
+impl Serialize for Paint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.index {
+            Some(index) => {
+                let mut tuple = serializer.serialize_tuple(2)?;
+                tuple.serialize_element(&Self::INDEXED)?;
+                tuple.serialize_element(&index)?;
+                tuple.end()
+            }
+            None => {
+                let mut tuple = serializer.serialize_tuple(2)?;
+                tuple.serialize_element(&Self::RGBA)?;
+                tuple.serialize_element(&self.color)?;
+                tuple.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Paint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct PaintVisitor;
+
+        impl<'de> Visitor<'de> for PaintVisitor {
+            type Value = Paint;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a tagged color or palette index")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> std::result::Result<Paint, A::Error> {
+                let tag: u8 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+                match tag {
+                    Paint::RGBA => {
+                        let color = seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                        Ok(Paint { color, index: None })
+                    }
+                    Paint::INDEXED => {
+                        let index = seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                        // resolved at draw time (see [`ObjectData::resolved_color`])
+                        Ok(Paint {
+                            color: Color::WHITE,
+                            index: Some(index),
+                        })
+                    }
+                    tag => Err(de::Error::invalid_value(
+                        de::Unexpected::Unsigned(tag as u64),
+                        &self,
+                    )),
+                }
+            }
+        }
+
+        deserializer.deserialize_tuple(2, PaintVisitor)
+    }
+}
+
+/// Blinn-Phong surface properties (only used by the 'normal' program).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct MaterialData {
+    shininess: f32,
+    specular: Color,
+}
+
+impl MaterialData {
+    /// A dull material, resembling the original (Phong; exponent 16) highlights.
+    pub const MATTE: Self = Self::new(64.0, Color::new([0.5, 0.5, 0.5, 1.0], false));
+
+    pub const fn new(shininess: f32, specular: Color) -> Self {
+        Self {
+            shininess,
+            specular,
+        }
+    }
+
+    pub const fn shininess(&self) -> f32 {
+        self.shininess
+    }
+
+    pub const fn specular(&self) -> &[f32] {
+        self.specular.inner.as_slice()
+    }
+
+    /// The specular intensity of a surface, given unit vectors from the surface.
+    ///
+    /// Mirrors `get_specular` of the 'normal' fragment shader.
+    pub fn specular_factor(&self, norm: &Vector, light_dir: &Vector, view_dir: &Vector) -> f32 {
+        (light_dir + view_dir)
+            .try_normalize(f32::EPSILON)
+            .map_or(0.0, |half| norm.dot(&half).max(0.0).powf(self.shininess))
+    }
+}
+
+impl Default for MaterialData {
+    fn default() -> Self {
+        Self::MATTE
+    }
+}
+
+/// One of the three axes of the world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CardinalAxis {
+    X,
+    Y,
+    Z,
+}
+
+impl CardinalAxis {
+    pub const fn index(&self) -> usize {
+        match self {
+            Self::X => 0,
+            Self::Y => 1,
+            Self::Z => 2,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Transformations {
     translation: Translation,
     rotation: UnitQuaternion,
     scaling: Scale,
+    mirror: Vector,
     model: Matrix,
+    normal_matrix: Matrix3,
 }
 
 impl Transformations {
@@ -282,13 +683,53 @@ impl Default for Transformations {
         let translation = Translation::identity();
         let rotation = UnitQuaternion::identity();
         let scaling = Scale::identity();
+        let mirror = DIAGONAL;
         let model = Matrix::identity();
+        let normal_matrix = Matrix3::identity();
 
         Self {
             translation,
             rotation,
             scaling,
+            mirror,
             model,
+            normal_matrix,
+        }
+    }
+}
+
+/// The inverse-transpose of the upper-left 3x3 of `model`.
+///
+/// Unlike the model matrix itself, it keeps normals perpendicular to their surface
+/// under non-uniform scaling. Singular models (a zero dimension) are used as is.
+pub fn normal_matrix(model: &Matrix) -> Matrix3 {
+    let linear = model.fixed_view::<3, 3>(0, 0).into_owned();
+    linear.try_inverse().unwrap_or(linear).transpose()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RenderState {
+    visible: bool,
+    cast_shadow: bool,
+    receive_shadow: bool,
+    priority: i32,
+    wire_overlay: Option<Color>,
+    highlight: Option<Color>,
+    two_pass: bool,
+    intensity: f32,
+}
+
+impl Default for RenderState {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            cast_shadow: true,
+            receive_shadow: true,
+            priority: 0,
+            wire_overlay: None,
+            highlight: None,
+            two_pass: false,
+            intensity: 1.0,
         }
     }
 }
@@ -296,11 +737,29 @@ impl Default for Transformations {
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct ObjectData {
     id: Id,
-    color: Color,
+    paint: Paint,
     data: RawObjectData,
+    parent: Option<Id>,
+
+    // appearance is render-local (keeps object packets small)
+    #[serde(skip)]
+    material: MaterialData,
+
+    #[serde(skip)]
+    emissive: Color,
+
+    #[serde(skip)]
+    tint: Color,
 
     #[serde(skip)]
     transform: Transformations,
+
+    #[serde(skip)]
+    render: RenderState,
+
+    // each peer resolves its own collisions
+    #[serde(skip)]
+    collision: CollisionFilter,
 }
 
 impl Deref for ObjectData {
@@ -316,9 +775,15 @@ impl ObjectData {
         let transform = Transformations::new(*data.pos(), *data.dim());
         Self {
             id,
-            color,
+            paint: Paint { color, index: None },
             data,
+            parent: None,
+            material: Default::default(),
+            emissive: Default::default(),
+            tint: Color::WHITE,
             transform,
+            render: Default::default(),
+            collision: Default::default(),
         }
     }
 
@@ -326,16 +791,170 @@ impl ObjectData {
         self.id
     }
 
+    pub fn set_id(&mut self, id: Id) {
+        self.id = id
+    }
+
+    /// The object this one hangs off of, if any (only a link; see [`ObjectData::set_parent`]).
+    pub const fn parent(&self) -> Option<Id> {
+        self.parent
+    }
+
+    /// The parent isn't required to exist (yet), so snapshots may reference ids sent later.
+    pub fn set_parent(&mut self, parent: Option<Id>) {
+        self.parent = parent
+    }
+
     pub const fn color(&self) -> &[f32] {
-        self.color.inner.as_slice()
+        self.paint.color.inner.as_slice()
+    }
+
+    pub const fn raw_color(&self) -> Color {
+        self.paint.color
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.paint.color = color
+    }
+
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.paint.color.inner[3] = alpha
     }
 
     pub const fn alpha(&self) -> f32 {
-        self.color.alpha()
+        self.paint.color.alpha()
     }
 
     pub const fn is_light(&self) -> bool {
-        self.color.emits
+        self.paint.color.emits
+    }
+
+    /// The factor the color of a light is scaled by when it lights the scene.
+    pub const fn light_intensity(&self) -> f32 {
+        self.render.intensity
+    }
+
+    pub fn set_light_intensity(&mut self, intensity: f32) {
+        self.render.intensity = intensity
+    }
+
+    /// The color the light shines with (its color scaled by its intensity).
+    pub fn light_color(&self) -> [f32; 3] {
+        self.paint.color.rgb().map(|c| c * self.render.intensity)
+    }
+
+    /// The [`Palette`] entry used instead of the stored color, if any.
+    ///
+    /// Only the index is sent over the network (the stored color isn't, the palette entry
+    /// carrying the alpha and whether it emits light).
+    pub const fn palette_index(&self) -> Option<u8> {
+        self.paint.index
+    }
+
+    pub fn set_palette_index(&mut self, index: Option<u8>) {
+        self.paint.index = index
+    }
+
+    /// The color to draw with: the palette entry (its alpha scaled by [`ObjectData::alpha`],
+    /// so fades still apply) or the stored color if there's no such entry.
+    pub fn resolved_color(&self, palette: &Palette) -> Color {
+        match self.paint.index.and_then(|index| palette.get(index)) {
+            Some(mut color) => {
+                color.inner[3] *= self.alpha();
+                color
+            }
+            None => self.paint.color,
+        }
+    }
+
+    pub const fn material(&self) -> &MaterialData {
+        &self.material
+    }
+
+    pub fn set_material(&mut self, material: MaterialData) {
+        self.material = material
+    }
+
+    /// The color added after lighting, so it's never darkened (black emits nothing).
+    pub const fn emissive(&self) -> Color {
+        self.emissive
+    }
+
+    pub fn set_emissive(&mut self, emissive: Color) {
+        self.emissive = emissive
+    }
+
+    /// Multiplied into the base color, so a single mesh can take per-team colors (white is a no-op).
+    pub const fn tint(&self) -> Color {
+        self.tint
+    }
+
+    pub fn set_tint(&mut self, tint: Color) {
+        self.tint = tint
+    }
+
+    /// Hidden objects are kept around (buffers included) but never drawn.
+    pub const fn is_visible(&self) -> bool {
+        self.render.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.render.visible = visible
+    }
+
+    /// Whether the object is drawn into shadow depth passes.
+    pub const fn cast_shadow(&self) -> bool {
+        self.render.cast_shadow
+    }
+
+    pub fn set_cast_shadow(&mut self, cast_shadow: bool) {
+        self.render.cast_shadow = cast_shadow
+    }
+
+    /// Whether the object is shadowed (otherwise it's always fully lit).
+    pub const fn receive_shadow(&self) -> bool {
+        self.render.receive_shadow
+    }
+
+    pub fn set_receive_shadow(&mut self, receive_shadow: bool) {
+        self.render.receive_shadow = receive_shadow
+    }
+
+    /// Objects of higher priority are drawn later (i.e., on top of equal depths).
+    pub const fn priority(&self) -> i32 {
+        self.render.priority
+    }
+
+    pub fn set_priority(&mut self, priority: i32) {
+        self.render.priority = priority
+    }
+
+    /// The color of the wireframe drawn on top of the filled mesh, if any.
+    pub const fn wire_overlay(&self) -> Option<Color> {
+        self.render.wire_overlay
+    }
+
+    pub fn set_wire_overlay(&mut self, color: Option<Color>) {
+        self.render.wire_overlay = color
+    }
+
+    /// A transient tint multiplied on top of [`ObjectData::tint`] (e.g., while hovered).
+    pub const fn highlight(&self) -> Option<Color> {
+        self.render.highlight
+    }
+
+    pub fn set_highlight(&mut self, highlight: Option<Color>) {
+        self.render.highlight = highlight
+    }
+
+    /// Whether the (convex) object is drawn back faces first, then front faces, while
+    /// transparent, so its far side shows through its near side.
+    pub const fn two_pass_transparency(&self) -> bool {
+        self.render.two_pass
+    }
+
+    pub fn set_two_pass_transparency(&mut self, two_pass: bool) {
+        self.render.two_pass = two_pass
     }
 
     pub const fn player(&self) -> Option<Player> {
@@ -398,6 +1017,58 @@ impl ObjectData {
         }
     }
 
+    /// The position of either kind of object (call [`ObjectData::translation_upt`] after moving it).
+    pub fn pos_mut(&mut self) -> &mut Vector {
+        match &mut self.data {
+            RawObjectData::Player(data) => data.pos_mut(),
+            RawObjectData::Basic(data) => data.pos_mut(),
+        }
+    }
+
+    /// The bitset of layers the object is on (see [`CollisionFilter`]).
+    pub const fn collision_layer(&self) -> u32 {
+        self.collision.layer
+    }
+
+    pub fn set_collision_layer(&mut self, layer: u32) {
+        self.collision.layer = layer
+    }
+
+    /// The bitset of layers the object collides with (see [`CollisionFilter`]).
+    pub const fn collision_mask(&self) -> u32 {
+        self.collision.mask
+    }
+
+    pub fn set_collision_mask(&mut self, mask: u32) {
+        self.collision.mask = mask
+    }
+
+    pub const fn collision_filter(&self) -> CollisionFilter {
+        self.collision
+    }
+
+    pub const fn collides_with(&self, other: &Self) -> bool {
+        self.collision.collides_with(&other.collision)
+    }
+
+    /// The squared distance between the position of the object and `point` (cheap to compare).
+    pub fn distance_sq_to(&self, point: &Vector) -> f32 {
+        (self.pos() - point).norm_squared()
+    }
+
+    /// The world-space center and radius of a sphere enclosing the (unit) geometry.
+    ///
+    /// Unlike [`ObjectData::bounds`], it doesn't change as the object rotates.
+    pub fn bounding_sphere(&self) -> (Vector, f32) {
+        (self.translation().vector, self.scaling().vector.norm())
+    }
+
+    /// The world-space box enclosing the (unit) geometry, as transformed by the model matrix.
+    pub fn bounds(&self) -> Aabb {
+        let linear = self.model().fixed_view::<3, 3>(0, 0).abs();
+        Aabb::from_center(&self.translation().vector, &(linear * Vector::repeat(1.0)))
+    }
+
     pub fn translation(&self) -> &Translation {
         &self.transform.translation
     }
@@ -410,6 +1081,12 @@ impl ObjectData {
         &self.transform.rotation
     }
 
+    /// Orient the object (players are reoriented by their view in [`ObjectData::rotation_upt`]).
+    pub fn set_rotation(&mut self, rotation: UnitQuaternion) {
+        self.transform.rotation = rotation;
+        self.model_upt();
+    }
+
     pub fn rotation_upt(&mut self) {
         if let Some(p) = self.player() {
             let attr = p.attr();
@@ -431,7 +1108,33 @@ impl ObjectData {
     }
 
     pub fn scaling_upt(&mut self) {
-        self.transform.scaling = Scale::from(*self.dim());
+        self.transform.scaling = Scale::from(self.dim().component_mul(&self.transform.mirror));
+    }
+
+    /// Resize a basic object in place (the buffers are kept), returning `false` for players.
+    pub fn set_dim(&mut self, dim: Vector) -> bool {
+        let Some(mut basic) = self.basic_mut() else {
+            return false;
+        };
+        basic.set_dim(dim);
+
+        self.scaling_upt();
+        self.model_upt();
+        true
+    }
+
+    /// Mirror the object across the plane perpendicular to `axis` (through its position).
+    pub fn mirror(&mut self, axis: CardinalAxis) {
+        self.transform.mirror[axis.index()] *= -1.0;
+        self.scaling_upt();
+        self.model_upt();
+    }
+
+    /// Whether the model matrix has a negative determinant (i.e., inverts the winding order).
+    ///
+    /// Translation and rotation never flip, so only the sign of the scaling matters.
+    pub fn flips_winding(&self) -> bool {
+        self.scaling().vector.product() < 0.0
     }
 
     pub fn model(&self) -> &Matrix {
@@ -446,7 +1149,13 @@ impl ObjectData {
         let iso = Isometry::from_parts(*t, *r).to_homogeneous();
         let model = iso * s.to_homogeneous();
 
-        self.transform.model = model
+        self.transform.model = model;
+        self.transform.normal_matrix = normal_matrix(&model)
+    }
+
+    /// The matrix which transforms normals into world space (see [`normal_matrix`]).
+    pub fn normal_matrix(&self) -> &Matrix3 {
+        &self.transform.normal_matrix
     }
 
     pub fn transform_upt(&mut self) {
@@ -456,3 +1165,156 @@ impl ObjectData {
         self.model_upt();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bincode::{deserialize, serialize};
+
+    #[test]
+    fn mirroring_twice_restores_the_transform() {
+        let mut data = ObjectData::new(
+            1,
+            Color::WHITE,
+            RawObjectData::Basic(BasicData::new(
+                Vector::new(1.0, 2.0, 3.0),
+                Vector::new(1.0, 2.0, 4.0),
+            )),
+        );
+        data.model_upt();
+        let model = *data.model();
+
+        data.mirror(CardinalAxis::Y);
+        assert!(data.flips_winding());
+        assert!(data.model().determinant() < 0.0);
+        assert_eq!(data.model()[(1, 1)], -model[(1, 1)]);
+
+        data.mirror(CardinalAxis::Y);
+        assert!(!data.flips_winding());
+        assert_eq!(*data.model(), model);
+    }
+
+    #[test]
+    fn unit_cube_bounding_sphere_is_stable_under_rotation() {
+        let pos = Vector::new(1.0, -2.0, 3.0);
+        let mut data = ObjectData::new(
+            1,
+            Color::WHITE,
+            RawObjectData::Basic(BasicData::new(pos, Vector::repeat(1.0))),
+        );
+        data.model_upt();
+        assert_eq!(data.bounding_sphere(), (pos, 3f32.sqrt()));
+
+        // unlike the box, which grows as the cube turns
+        data.set_rotation(UnitQuaternion::from_axis_angle(&Y_AXIS_UNIT, 0.7));
+        assert_eq!(data.bounding_sphere(), (pos, 3f32.sqrt()));
+        assert!(data.bounds().max.x - pos.x > 1.0);
+    }
+
+    #[test]
+    fn normals_stay_perpendicular_under_non_uniform_scale() {
+        let mut data = ObjectData::new(
+            1,
+            Color::WHITE,
+            RawObjectData::Basic(BasicData::new(Vector::zeros(), Vector::new(2.0, 1.0, 1.0))),
+        );
+        data.model_upt();
+        let (model, normals) = (data.model().fixed_view::<3, 3>(0, 0), data.normal_matrix());
+
+        let up = (normals * Vector::y()).normalize();
+        assert!((up - Vector::y()).norm() < 1e-6);
+
+        // a slanted face, whose tangent is stretched along x
+        let (tangent, normal) = (Vector::new(1.0, -1.0, 0.0), Vector::new(1.0, 1.0, 0.0));
+        let (tangent, normal) = (model * tangent, normals * normal);
+        assert!(tangent.dot(&normal).abs() < 1e-6);
+        assert!((model * Vector::new(1.0, 1.0, 0.0)).dot(&tangent).abs() > 1.0);
+    }
+
+    #[test]
+    fn players_are_grounded_only_on_a_floor() {
+        let floor = Aabb::new(Vector::new(-2.0, -1.0, -2.0), Vector::new(2.0, 0.0, 2.0));
+        let wall = Aabb::new(Vector::new(4.0, -5.0, -2.0), Vector::new(5.0, 5.0, 2.0));
+
+        // sunk slightly into the floor
+        let mut resting = PlayerData::new(Vector::new(0.0, 0.95, 0.0));
+        resting.resolve_collisions([floor, wall]);
+        assert!(resting.is_grounded());
+        assert!((resting.pos().y - 1.0).abs() < 1e-3);
+
+        let mut falling = PlayerData::new(Vector::new(0.0, 3.0, 0.0));
+        falling.resolve_collisions([floor, wall]);
+        assert!(!falling.is_grounded());
+
+        // against the wall, off the floor
+        let mut sliding = PlayerData::new(Vector::new(3.6, 0.0, 0.0));
+        sliding.resolve_collisions([floor, wall]);
+        assert!(!sliding.is_grounded());
+
+        // reset once it leaves the floor
+        resting.attr_mut().eye.y = 3.0;
+        resting.resolve_collisions([floor, wall]);
+        assert!(!resting.is_grounded());
+    }
+
+    #[test]
+    fn extrapolation_follows_the_velocity_up_to_the_max() {
+        let pos = Vector::new(1.0, 2.0, 3.0);
+        let mut player = PlayerData::new(pos);
+        player.set_vel(Vector::new(10.0, 0.0, 0.0));
+
+        assert_eq!(
+            player.extrapolated_pos(0.5),
+            pos + Vector::new(5.0, 0.0, 0.0)
+        );
+
+        let furthest = pos + player.vel() * PlayerData::MAX_EXTRAPOLATION;
+        assert_eq!(player.extrapolated_pos(3.0), furthest);
+        assert_eq!(player.extrapolated_pos_within(3.0, 0.1), pos + Vector::x());
+        assert_eq!(player.extrapolated_pos(-1.0), pos);
+    }
+
+    #[test]
+    fn indexed_paint_resolves_to_the_palette_entry() {
+        let mut data = ObjectData::new(
+            1,
+            Color::WHITE,
+            RawObjectData::Basic(BasicData::new(Vector::zeros(), Vector::repeat(1.0))),
+        );
+        data.set_palette_index(Some(3));
+
+        let data: ObjectData = deserialize(&serialize(&data).unwrap()).unwrap();
+        let entry = Color::new([1.0, 1.0, 0.8, 0.5], true);
+        let palette = Palette::new([Color::BLACK; 3].into_iter().chain([entry]));
+
+        assert_eq!(data.palette_index(), Some(3));
+        assert_eq!(&data.resolved_color(&palette)[..], &entry[..]);
+        assert!(data.resolved_color(&palette).emits());
+    }
+
+    #[test]
+    fn indexed_paint_is_one_byte_past_its_tag() {
+        let indexed = Paint {
+            color: Color::WHITE,
+            index: Some(3),
+        };
+        let rgba = Paint {
+            color: Color::WHITE,
+            index: None,
+        };
+
+        assert_eq!(serialize(&indexed).unwrap(), [Paint::INDEXED, 3]);
+        assert_eq!(
+            serialize(&rgba).unwrap().len(),
+            1 + serialize(&Color::WHITE).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn premultiplied_scales_rgb_by_alpha() {
+        let color = Color::new([1.0, 0.5, 0.25, 0.5], true).premultiplied();
+
+        assert_eq!(&color[..], &[0.5, 0.25, 0.125, 0.5]);
+        assert!(color.emits());
+    }
+}
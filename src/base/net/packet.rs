@@ -1,56 +1,111 @@
 use crate::*;
 use packet_enum::*;
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Duration};
+
+/// Bumped whenever the wire format changes (peers of different versions can't talk).
+pub const PROTOCOL_VERSION: u16 = 11;
 
 #[derive(Clone, Copy, Debug)]
 pub struct ClientHandshake;
 
 #[derive(Clone, Copy, Debug)]
-pub struct ServerHandshake(Id);
+pub struct ServerHandshake {
+    id: Id,
+    tick_rate: Duration,
+}
 
 impl ServerHandshake {
     pub const fn id(&self) -> Id {
-        self.0
+        self.id
+    }
+
+    /// The server's simulation rate.
+    pub const fn tick_rate(&self) -> Duration {
+        self.tick_rate
+    }
+}
+
+/// The exchange which precedes any game data.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum HandshakeMessage {
+    ClientHello {
+        version: u16,
+    },
+    ServerWelcome {
+        version: u16,
+        tick_rate: Duration,
+        player_id: Id,
+    },
+}
+
+impl HandshakeMessage {
+    pub const fn version(&self) -> u16 {
+        match self {
+            Self::ClientHello { version } | Self::ServerWelcome { version, .. } => *version,
+        }
     }
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Handshake {
     secret: [u8; 3],
-    is_client: Option<Id>,
+    message: HandshakeMessage,
 }
 
 impl Handshake {
     // TODO - improve this (arbitrary for now)
     const SECRET: [u8; 3] = [1, 0, 1];
 
-    const fn new(is_client: Option<Id>) -> Handshake {
+    const fn new(message: HandshakeMessage) -> Handshake {
         Self {
             secret: Self::SECRET,
-            is_client,
+            message,
         }
     }
 
     pub const fn client() -> Handshake {
-        Self::new(None)
+        Self::hello(PROTOCOL_VERSION)
+    }
+
+    /// A client hello claiming to speak `version` (see [`Handshake::verify`]).
+    pub const fn hello(version: u16) -> Handshake {
+        Self::new(HandshakeMessage::ClientHello { version })
     }
 
     pub const fn server(id: Id) -> Handshake {
-        Self::new(Some(id))
+        Self::new(HandshakeMessage::ServerWelcome {
+            version: PROTOCOL_VERSION,
+            tick_rate: TICK_RATE,
+            player_id: id,
+        })
+    }
+
+    pub const fn message(&self) -> &HandshakeMessage {
+        &self.message
     }
 
+    /// Reject foreign packets, as well as peers speaking another protocol version.
     pub const fn verify(&self) -> BlazedResult<()> {
-        if let Self::SECRET = self.secret {
-            Ok(())
-        } else {
-            Err(BlazedError::Packet(PacketError::Handshake(
+        let Self::SECRET = self.secret else {
+            return Err(BlazedError::Packet(PacketError::Handshake(
                 HandshakeError::InvalidContent,
-            )))
+            )));
+        };
+
+        let found = self.message.version();
+        if found != PROTOCOL_VERSION {
+            return Err(BlazedError::Packet(PacketError::Handshake(
+                HandshakeError::VersionMismatch {
+                    expected: PROTOCOL_VERSION,
+                    found,
+                },
+            )));
         }
+        Ok(())
     }
 
     pub const fn into_client(self) -> Option<ClientHandshake> {
-        if self.is_client.is_none() {
+        if let HandshakeMessage::ClientHello { .. } = self.message {
             Some(ClientHandshake)
         } else {
             None
@@ -58,8 +113,16 @@ impl Handshake {
     }
 
     pub const fn into_server(self) -> Option<ServerHandshake> {
-        if let Some(id) = self.is_client {
-            Some(ServerHandshake(id))
+        if let HandshakeMessage::ServerWelcome {
+            tick_rate,
+            player_id,
+            ..
+        } = self.message
+        {
+            Some(ServerHandshake {
+                id: player_id,
+                tick_rate,
+            })
         } else {
             None
         }
@@ -78,20 +141,97 @@ pub type Keybaord = Flags;
 pub enum Input {
     Mouse(Mouse),
     Keyboard(Keybaord),
+
+    /// Keys held for a number of ticks (consolidated by the client's input sampler).
+    Held {
+        keys: Keybaord,
+        ticks: u16,
+    },
+}
+
+/// Why the server refused to spawn a requested object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RejectReason {
+    /// Outside of where objects may be placed.
+    OutOfBounds,
+    /// The requester already has too many objects.
+    LimitReached,
+    /// Objects of this kind (or look) can't be requested.
+    Forbidden,
+    /// Every id of this kind is taken.
+    NoIds,
+}
+
+/// Why the server ended a connection (shown to the disconnected client).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisconnectReason {
+    /// Removed by moderation.
+    Kicked,
+    /// The server is going down.
+    ShuttingDown,
+    /// The client broke the protocol (e.g., sent unexpected packets).
+    Misbehaving,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PacketEnum)]
 pub enum Packet {
     // initialization
-    Handshake { handshake: Handshake },
+    Handshake {
+        handshake: Handshake,
+    },
 
     // client-related
-    Input { input: Input },
+    Input {
+        input: Input,
+    },
 
     // object-related
-    AddObj { data: ObjectData },
-    RemObj { id: Id },
-    UptObj { data: ObjectData },
+    AddObj {
+        data: ObjectData,
+    },
+    RemObj {
+        id: Id,
+    },
+    // stamped with the tick of the server (which hit-scans refer to, see `Fire`)
+    UptObj {
+        data: ObjectData,
+        tick: u64,
+    },
+
+    // the server allocates the id of requested objects (the requested id is ignored)
+    SpawnReq {
+        request: u16,
+        data: ObjectData,
+    },
+    SpawnConfirm {
+        request: u16,
+        data: ObjectData,
+    },
+    SpawnReject {
+        request: u16,
+        reason: RejectReason,
+    },
+
+    // a hit-scan against the players as of `tick` (lag compensated), distributed if it hit
+    Fire {
+        tick: u64,
+        origin: Vector,
+        dir: Vector,
+    },
+    Hit {
+        shooter: Id,
+        target: Id,
+    },
+
+    // the server changed its simulation rate (e.g., slowing down under load)
+    TickRate {
+        tick_rate: Duration,
+    },
+
+    // the server is about to close the connection
+    Disconnect {
+        reason: DisconnectReason,
+    },
 
     // misc functionality
     Flush,
@@ -138,3 +278,40 @@ impl Packet {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hello(version: u16) -> Packet {
+        Packet::Handshake {
+            handshake: Handshake::hello(version),
+        }
+    }
+
+    #[test]
+    fn mismatched_version_is_rejected() {
+        let found = PROTOCOL_VERSION + 1;
+
+        match hello(found).into_client_handshake() {
+            Err(BlazedError::Packet(PacketError::Handshake(HandshakeError::VersionMismatch {
+                expected,
+                found: actual,
+            }))) => {
+                assert_eq!(expected, PROTOCOL_VERSION);
+                assert_eq!(actual, found);
+            }
+            other => panic!("Expected a version mismatch, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn matching_version_is_accepted() {
+        assert!(hello(PROTOCOL_VERSION).into_client_handshake().is_ok());
+        assert!(Packet::Handshake {
+            handshake: Handshake::server(0)
+        }
+        .into_client_handshake()
+        .is_err());
+    }
+}
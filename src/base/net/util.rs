@@ -1,5 +1,5 @@
 use crate::*;
-use bincode::deserialize;
+use bincode::{deserialize, serialized_size};
 use packet_enum::*;
 use std::net::{SocketAddr, ToSocketAddrs};
 
@@ -18,6 +18,11 @@ pub fn recv<'a, K: AsPacketKind, T: AsPacketRecv<'a, K>>(
     Ok(packet)
 }
 
+/// The number of bytes `packet` occupies on the wire.
+pub fn packet_len(packet: &impl AsPacketSend) -> BlazedResult<usize> {
+    Ok(serialized_size(packet)? as usize)
+}
+
 /// Retrieve a default socket with specified port number.
 pub fn get_socket_addr(port: u16) -> SocketAddr {
     ("127.0.0.1", port)
@@ -26,3 +31,96 @@ pub fn get_socket_addr(port: u16) -> SocketAddr {
         .next()
         .expect("No available socket address(s)")
 }
+
+/// A stable (id-ordered; platform-independent) hash of the synchronized state of `objects`.
+///
+/// Covers the id and position of every object, and the view direction of players.
+/// Peers with the same state produce the same hash, so a mismatch signals a desync.
+pub fn state_hash<'a>(objects: impl IntoIterator<Item = &'a ObjectData>) -> u64 {
+    // FNV-1a (unlike `DefaultHasher`, it's guaranteed to never change)
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let mut objects = objects.into_iter().collect::<Vec<_>>();
+    objects.sort_unstable_by_key(|obj| obj.id());
+
+    let mut hash = OFFSET;
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes {
+            hash = (hash ^ *byte as u64).wrapping_mul(PRIME)
+        }
+    };
+
+    for obj in objects {
+        write(&obj.id().to_le_bytes());
+
+        let orientation = obj.player_ref().map(|player| player.attr().target);
+        for v in [Some(*obj.pos()), orientation].into_iter().flatten() {
+            v.iter().for_each(|c| write(&c.to_bits().to_le_bytes()))
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn basic(id: Id, pos: Vector) -> ObjectData {
+        ObjectData::new(
+            id,
+            Color::WHITE,
+            RawObjectData::Basic(BasicData::new(pos, Vector::repeat(1.0))),
+        )
+    }
+
+    fn scene() -> Vec<ObjectData> {
+        vec![
+            basic(-1, Vector::new(1.0, 2.0, 3.0)),
+            basic(-2, Vector::new(-4.0, 0.5, 0.0)),
+            ObjectData::new(
+                0,
+                Color::BLACK,
+                RawObjectData::Player(PlayerData::new(Vector::zeros())),
+            ),
+        ]
+    }
+
+    #[test]
+    fn hash_ignores_order() {
+        let objects = scene();
+        let hash = state_hash(&objects);
+
+        assert_eq!(hash, state_hash(objects.iter().rev()));
+        assert_eq!(hash, state_hash(&scene()));
+    }
+
+    #[test]
+    fn hash_covers_ids_and_positions() {
+        let objects = scene();
+        let hash = state_hash(&objects);
+
+        let mut moved = scene();
+        *moved[0].pos_mut() += Vector::new(0.0, 0.001, 0.0);
+        assert_ne!(hash, state_hash(&moved));
+
+        let mut renumbered = scene();
+        renumbered[1] = basic(-3, *renumbered[1].pos());
+        assert_ne!(hash, state_hash(&renumbered));
+
+        assert_ne!(hash, state_hash(&objects[..2]));
+    }
+
+    #[test]
+    fn hash_ignores_appearance() {
+        let mut recolored = scene();
+        recolored[0].set_color(Color::BLACK);
+
+        assert_eq!(state_hash(&scene()), state_hash(&recolored));
+    }
+
+    #[test]
+    fn empty_hash_is_the_offset() {
+        assert_eq!(state_hash([]), 0xcbf29ce484222325);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::*;
+
+/// A color vision deficiency, which team/tag colors can be corrected for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CvdMode {
+    #[default]
+    None,
+    /// No red cones.
+    Protanopia,
+    /// No green cones.
+    Deuteranopia,
+    /// No blue cones.
+    Tritanopia,
+}
+
+impl CvdMode {
+    /// How RGB is perceived with the deficiency (Machado et al. 2009, at full severity).
+    pub const fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            Self::None => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            Self::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Self::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Self::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+
+    /// `rgb` as perceived with the deficiency.
+    pub fn simulate(&self, rgb: [f32; 3]) -> [f32; 3] {
+        self.matrix()
+            .map(|row| row.iter().zip(rgb).map(|(m, c)| m * c).sum::<f32>())
+    }
+
+    /// `rgb` shifted so the difference lost to the deficiency shows up in the channels
+    /// still perceived (daltonization).
+    pub fn correct(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let [dr, dg, db] = {
+            let [sr, sg, sb] = self.simulate(rgb);
+            [rgb[0] - sr, rgb[1] - sg, rgb[2] - sb]
+        };
+        let [r, g, b] = rgb;
+
+        // the lost difference is redistributed towards the green and blue channels
+        [r, g + 0.7 * dr + dg, b + 0.7 * dr + db].map(|c| c.clamp(0.0, 1.0))
+    }
+}
+
+/// A shared table of up to 256 colors, referenced by index (see [`ObjectData::palette_index`]).
+#[derive(Clone, Debug, Default)]
+pub struct Palette {
+    colors: Vec<Color>,
+}
+
+impl Palette {
+    pub const MAX_COLORS: usize = u8::MAX as usize + 1;
+
+    /// Construct a palette of `colors` (any past [`Palette::MAX_COLORS`] are dropped).
+    pub fn new(colors: impl IntoIterator<Item = Color>) -> Self {
+        Self {
+            colors: colors.into_iter().take(Self::MAX_COLORS).collect(),
+        }
+    }
+
+    /// Append `color`, returning its index (or `None` if the palette is full).
+    pub fn push(&mut self, color: Color) -> Option<u8> {
+        let index = u8::try_from(self.colors.len()).ok()?;
+        self.colors.push(color);
+        Some(index)
+    }
+
+    pub fn get(&self, index: u8) -> Option<Color> {
+        self.colors.get(index as usize).copied()
+    }
+
+    /// Replace the color at `index`, returning `false` if there's none.
+    pub fn set(&mut self, index: u8, color: Color) -> bool {
+        let Some(slot) = self.colors.get_mut(index as usize) else {
+            return false;
+        };
+        *slot = color;
+        true
+    }
+
+    /// A copy of this palette with every color corrected for `mode` (see [`CvdMode::correct`]).
+    pub fn apply_cvd(&self, mode: CvdMode) -> Self {
+        Self::new(self.colors.iter().map(|color| {
+            let [r, g, b] = mode.correct(color.rgb());
+            Color::new([r, g, b, color.alpha()], color.emits())
+        }))
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    #[test]
+    fn red_is_remapped_per_the_protanopia_matrix() {
+        let red = [1.0, 0.0, 0.0];
+
+        // the first column of the matrix
+        let simulated = CvdMode::Protanopia.simulate(red);
+        assert!(close(simulated, [0.152286, 0.114503, -0.003882]));
+
+        // 70% of the red lost, plus what each channel lost itself
+        let palette = Palette::new([Color::new([1.0, 0.0, 0.0, 0.5], true)]);
+        let corrected = palette.apply_cvd(CvdMode::Protanopia).get(0).unwrap();
+        let lost = 1.0 - 0.152286;
+        assert!(close(
+            corrected.rgb(),
+            [1.0, 0.7 * lost - 0.114503, 0.7 * lost + 0.003882]
+        ));
+        assert_eq!((corrected.alpha(), corrected.emits()), (0.5, true));
+
+        // the stored colors are left as they are
+        assert!(close(palette.get(0).unwrap().rgb(), red));
+        assert!(close(CvdMode::None.correct(red), red));
+    }
+}
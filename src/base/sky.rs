@@ -0,0 +1,52 @@
+use crate::*;
+use std::f32::consts::TAU;
+
+/// A sun that arcs across the sky over the course of a day.
+///
+/// The time of day is in `[0, 1)`, where `0` is dawn, `0.25` is noon,
+/// `0.5` is dusk, and `0.75` is midnight.
+#[derive(Clone, Copy, Debug)]
+pub struct SkyCycle {
+    dawn: Vector,
+    noon: Vector,
+    dusk: Vector,
+    night: Vector,
+}
+
+impl SkyCycle {
+    pub const fn new(dawn: Vector, noon: Vector, dusk: Vector, night: Vector) -> Self {
+        Self {
+            dawn,
+            noon,
+            dusk,
+            night,
+        }
+    }
+
+    /// The direction towards the sun, rotating about the z-axis (rising from +x).
+    pub fn sun_direction(&self, t: f32) -> Vector {
+        let theta = t.rem_euclid(1.0) * TAU;
+        Vector::new(theta.cos(), theta.sin(), 0.0)
+    }
+
+    /// The color of the sunlight, interpolated between the nearest two presets.
+    pub fn light_color(&self, t: f32) -> Vector {
+        let presets = [self.dawn, self.noon, self.dusk, self.night, self.dawn];
+
+        let t = t.rem_euclid(1.0) * 4.0;
+        let i = (t as usize).min(3);
+
+        presets[i].lerp(&presets[i + 1], t - i as f32)
+    }
+}
+
+impl Default for SkyCycle {
+    fn default() -> Self {
+        Self::new(
+            Vector::new(1.0, 0.6, 0.4),
+            Vector::new(1.0, 1.0, 0.8),
+            Vector::new(0.9, 0.5, 0.3),
+            Vector::new(0.1, 0.1, 0.2),
+        )
+    }
+}
@@ -8,6 +8,9 @@ use std::time::Duration;
 pub type Id = i8;
 pub type AtomicId = <Id as AtomicInt>::Atomic;
 
+// ids the server never hands out, left for objects local to a client (e.g., its scenery)
+pub const LOCAL_IDS: std::ops::Range<Id> = Id::MIN..Id::MIN + 16;
+
 // default dynamic ports (arbitrary)
 pub const TCP_PORT: u16 = 54269;
 pub const UDP_PORT: u16 = 54277;
@@ -23,6 +26,15 @@ pub const MILISECOND: Duration = Duration::from_millis(1);
 pub const TICK_RATE: Duration = Duration::from_millis(4);
 pub const PING_MINIMUM: Duration = Duration::from_millis(10);
 
+// the most seconds a single simulation step may cover (hitches are slowed down instead)
+pub const MAX_DT: f32 = 0.1;
+
+// the most ticks a single consolidated input may cover (one second)
+pub const MAX_HELD_TICKS: u16 = (SECOND.as_millis() / TICK_RATE.as_millis()) as u16;
+
+// the most ticks the world may be rewound for lag compensation (one second)
+pub const MAX_HISTORY_TICKS: usize = MAX_HELD_TICKS as usize;
+
 // common mathematical values
 pub const RADIAN: f32 = std::f32::consts::PI / 180.0;
 